@@ -0,0 +1,40 @@
+module regulated_coin_example::compliance {
+    use std::string::String;
+
+    use sui::coin::{DenyCap, DenyCapV2};
+    use sui::event;
+
+    /// Emitted in the same transaction as a deny-list change, linking it to its case.
+    struct DenyListChange<phantom T> has copy, drop {
+        addr: address,
+        /// `true` if added to the deny-list, `false` if removed
+        added: bool,
+        reason: String,
+        case_id: String,
+    }
+
+    /// Records why `addr` is added to or removed from the deny-list of `T`. Takes the `DenyCap`,
+    /// so that only its owner can record changes, and is meant to be called in the same
+    /// programmable transaction as `coin::deny_list_add` or `coin::deny_list_remove`.
+    public fun record_deny_list_change<T>(
+        _deny_cap: &DenyCap<T>,
+        addr: address,
+        added: bool,
+        reason: String,
+        case_id: String,
+    ) {
+        event::emit(DenyListChange<T> { addr, added, reason, case_id });
+    }
+
+    /// Like `record_deny_list_change`, for coins created with `coin::create_regulated_currency_v2`,
+    /// whose deny-list is changed with `coin::deny_list_v2_add` and `coin::deny_list_v2_remove`.
+    public fun record_deny_list_change_v2<T>(
+        _deny_cap: &DenyCapV2<T>,
+        addr: address,
+        added: bool,
+        reason: String,
+        case_id: String,
+    ) {
+        event::emit(DenyListChange<T> { addr, added, reason, case_id });
+    }
+}
@@ -0,0 +1,240 @@
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use shared_crypto::intent::{Intent, IntentMessage, PersonalMessage};
+use sui_sdk::rpc_types::SuiTransactionBlockResponse;
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::crypto::{Signature, SuiKeyPair, SuiSignature};
+use sui_sdk::types::digests::TransactionDigest;
+
+use crate::tx_run::{execution_error, AppCommand, CommandContext};
+
+pub const DEFAULT_AUDIT_LOG: &str = "audit.log";
+/// The `prev_hash` of the first record
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditStatus {
+    /// Executed successfully
+    Success,
+    /// Executed, but the transaction failed
+    Failure,
+    /// Not executed, eg. refused by the policy or the pre-flight checks
+    Error,
+}
+
+/// What was run, by whom, and with which outcome.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Position in the log, from 0, set when appended
+    pub seq: u64,
+    /// Hash of the previous record, set when appended
+    pub prev_hash: String,
+    pub timestamp_ms: u64,
+    pub operator: SuiAddress,
+    pub host: String,
+    pub coin_type: String,
+    pub command: AppCommand,
+    pub context: CommandContext,
+    pub digest: Option<TransactionDigest>,
+    pub status: AuditStatus,
+    pub error: Option<String>,
+}
+
+impl AuditRecord {
+    /// The record of `command` signed by `operator`, given what executing it returned.
+    pub fn new(
+        operator: SuiAddress,
+        coin_type: String,
+        command: &AppCommand,
+        context: &CommandContext,
+        result: &Result<SuiTransactionBlockResponse>,
+    ) -> Self {
+        let (digest, status, error) = match result {
+            Ok(resp) => match execution_error(resp) {
+                None => (Some(resp.digest), AuditStatus::Success, None),
+                Some(error) => (Some(resp.digest), AuditStatus::Failure, Some(error)),
+            },
+            Err(e) => (None, AuditStatus::Error, Some(e.to_string())),
+        };
+        AuditRecord {
+            seq: 0,
+            prev_hash: GENESIS_HASH.to_string(),
+            timestamp_ms: Utc::now().timestamp_millis() as u64,
+            operator,
+            host: host(),
+            coin_type,
+            command: command.clone(),
+            context: context.clone(),
+            digest,
+            status,
+            error,
+        }
+    }
+
+    /// Hex SHA-256 of the record's JSON, which covers `prev_hash` and so the whole log before it.
+    pub fn hash(&self) -> Result<String> {
+        Ok(hex::encode(Sha256::digest(serde_json::to_vec(self)?)))
+    }
+}
+
+/// A line of the log: the record, its hash, and the operator's signature of the hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedAuditRecord {
+    #[serde(flatten)]
+    pub record: AuditRecord,
+    pub hash: String,
+    pub signature: Signature,
+}
+
+/// An append-only log of JSON lines, each record chained to the previous one by its hash.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+/// The outcome of [`AuditLog::verify`].
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    pub records: u64,
+    /// The hash of the last record, to keep elsewhere: records removed from the end of the log
+    /// can only be detected by comparing it
+    pub head: Option<String>,
+    /// Gaps, edits and bad signatures, by line
+    pub problems: Vec<String>,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        AuditLog { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Chains `record` to the last record of the log, signs it with `signer` and appends it.
+    pub fn append(
+        &self,
+        signer: &SuiKeyPair,
+        mut record: AuditRecord,
+    ) -> Result<SignedAuditRecord> {
+        if let Some(last) = self.last()? {
+            record.seq = last.record.seq + 1;
+            record.prev_hash = last.hash;
+        }
+        let hash = record.hash()?;
+        let signature = Signature::new_secure(&personal_message(&hash), signer);
+        let signed = SignedAuditRecord {
+            record,
+            hash,
+            signature,
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&signed)?)?;
+        file.sync_all()?;
+        Ok(signed)
+    }
+
+    fn last(&self) -> Result<Option<SignedAuditRecord>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        let file = std::fs::File::open(&self.path)?;
+        let Some(line) = BufReader::new(file).lines().last().transpose()? else {
+            return Ok(None);
+        };
+        serde_json::from_str(&line).map(Some).map_err(|e| {
+            anyhow!(
+                "The last record of {} is unreadable, run `audit verify`: {e}",
+                self.path.display()
+            )
+        })
+    }
+
+    /// Checks every record: its position, its link to the previous record, its hash and its
+    /// signature by the operator.
+    pub fn verify(&self) -> Result<VerifyReport> {
+        let file = std::fs::File::open(&self.path)?;
+        let mut report = VerifyReport::default();
+        let mut expected_seq = 0;
+        let mut prev_hash = GENESIS_HASH.to_string();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line_no = i + 1;
+            let signed: SignedAuditRecord = match serde_json::from_str(&line?) {
+                Ok(signed) => signed,
+                Err(e) => {
+                    report
+                        .problems
+                        .push(format!("line {line_no}: unreadable: {e}"));
+                    continue;
+                }
+            };
+            report.records += 1;
+            let record = &signed.record;
+            if record.seq != expected_seq {
+                report.problems.push(format!(
+                    "line {line_no}: expected record {expected_seq}, found {}",
+                    record.seq
+                ));
+            }
+            if record.prev_hash != prev_hash {
+                report.problems.push(format!(
+                    "line {line_no}: does not follow the previous record"
+                ));
+            }
+            if record.hash()? != signed.hash {
+                report
+                    .problems
+                    .push(format!("line {line_no}: edited, its hash does not match"));
+            }
+            let signature = &signed.signature;
+            if signature
+                .verify_secure(
+                    &personal_message(&signed.hash),
+                    record.operator,
+                    signature.scheme(),
+                )
+                .is_err()
+            {
+                report.problems.push(format!(
+                    "line {line_no}: not signed by its operator {}",
+                    record.operator
+                ));
+            }
+            expected_seq = record.seq + 1;
+            prev_hash = signed.hash.clone();
+            report.head = Some(signed.hash);
+        }
+        Ok(report)
+    }
+}
+
+fn personal_message(hash: &str) -> IntentMessage<PersonalMessage> {
+    IntentMessage::new(
+        Intent::personal_message(),
+        PersonalMessage {
+            message: hash.as_bytes().to_vec(),
+        },
+    )
+}
+
+/// The name of this machine, from `HOSTNAME` or `/etc/hostname`.
+fn host() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
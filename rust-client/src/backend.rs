@@ -0,0 +1,247 @@
+pub mod mock;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use sui_sdk::rpc_types::{
+    Balance, BalanceChange, CheckpointPage, EventFilter, EventPage, ObjectChange, ObjectsPage,
+    SuiCoinMetadata, SuiExecutionResult, SuiExecutionStatus, SuiObjectDataOptions,
+    SuiObjectResponse, SuiObjectResponseQuery, SuiTransactionBlockEffectsAPI,
+    SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+    SuiTransactionBlockResponseQuery, TransactionBlocksPage,
+};
+use sui_sdk::types::balance::Supply;
+use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+use sui_sdk::types::digests::TransactionDigest;
+use sui_sdk::types::event::EventID;
+use sui_sdk::types::quorum_driver_types::ExecuteTransactionRequestType;
+use sui_sdk::types::sui_serde::BigInt;
+use sui_sdk::types::transaction::{Transaction, TransactionData, TransactionKind};
+use sui_sdk::SuiClient;
+
+/// The outcome of dry-running a transaction.
+#[derive(Debug, Clone)]
+pub struct DryRunOutcome {
+    /// `Err` holds the execution error
+    pub status: Result<(), String>,
+    /// Net gas usage: computation plus storage cost, minus storage rebate
+    pub gas_used: i64,
+    pub object_changes: Vec<ObjectChange>,
+    pub balance_changes: Vec<BalanceChange>,
+}
+
+/// Everything the client needs from the chain.
+/// Implemented for [`SuiClient`] and for [`mock::MockBackend`], which allows testing without a
+/// fullnode.
+#[async_trait]
+pub trait ChainBackend: Send + Sync {
+    async fn get_object_with_options(
+        &self,
+        id: ObjectID,
+        options: SuiObjectDataOptions,
+    ) -> Result<SuiObjectResponse>;
+
+    async fn get_owned_objects(
+        &self,
+        owner: SuiAddress,
+        query: Option<SuiObjectResponseQuery>,
+        cursor: Option<ObjectID>,
+        limit: Option<usize>,
+    ) -> Result<ObjectsPage>;
+
+    async fn get_reference_gas_price(&self) -> Result<u64>;
+
+    /// The current epoch of the network.
+    async fn get_epoch(&self) -> Result<u64>;
+
+    async fn get_coin_metadata(&self, coin_type: String) -> Result<Option<SuiCoinMetadata>>;
+
+    async fn get_total_supply(&self, coin_type: String) -> Result<Supply>;
+
+    async fn get_balance(&self, owner: SuiAddress, coin_type: String) -> Result<Balance>;
+
+    /// Runs a transaction without gas or signatures, returning the results of each command.
+    async fn dev_inspect(
+        &self,
+        sender: SuiAddress,
+        tx: TransactionKind,
+    ) -> Result<Vec<SuiExecutionResult>>;
+
+    async fn dry_run(&self, tx_data: TransactionData) -> Result<DryRunOutcome>;
+
+    /// Executes a signed transaction, waiting for local execution.
+    async fn execute(&self, tx: Transaction) -> Result<SuiTransactionBlockResponse>;
+
+    async fn query_transaction_blocks(
+        &self,
+        query: SuiTransactionBlockResponseQuery,
+        cursor: Option<TransactionDigest>,
+        limit: Option<usize>,
+        descending: bool,
+    ) -> Result<TransactionBlocksPage>;
+
+    async fn query_events(
+        &self,
+        query: EventFilter,
+        cursor: Option<EventID>,
+        limit: Option<usize>,
+        descending: bool,
+    ) -> Result<EventPage>;
+
+    /// Checkpoints after `cursor`, a checkpoint sequence number.
+    async fn get_checkpoints(
+        &self,
+        cursor: Option<u64>,
+        limit: Option<usize>,
+        descending: bool,
+    ) -> Result<CheckpointPage>;
+
+    async fn multi_get_transaction_blocks(
+        &self,
+        digests: Vec<TransactionDigest>,
+        options: SuiTransactionBlockResponseOptions,
+    ) -> Result<Vec<SuiTransactionBlockResponse>>;
+}
+
+#[async_trait]
+impl ChainBackend for SuiClient {
+    async fn get_object_with_options(
+        &self,
+        id: ObjectID,
+        options: SuiObjectDataOptions,
+    ) -> Result<SuiObjectResponse> {
+        Ok(self.read_api().get_object_with_options(id, options).await?)
+    }
+
+    async fn get_owned_objects(
+        &self,
+        owner: SuiAddress,
+        query: Option<SuiObjectResponseQuery>,
+        cursor: Option<ObjectID>,
+        limit: Option<usize>,
+    ) -> Result<ObjectsPage> {
+        Ok(self
+            .read_api()
+            .get_owned_objects(owner, query, cursor, limit)
+            .await?)
+    }
+
+    async fn get_reference_gas_price(&self) -> Result<u64> {
+        Ok(self.read_api().get_reference_gas_price().await?)
+    }
+
+    async fn get_epoch(&self) -> Result<u64> {
+        Ok(self
+            .governance_api()
+            .get_latest_sui_system_state()
+            .await?
+            .epoch)
+    }
+
+    async fn get_coin_metadata(&self, coin_type: String) -> Result<Option<SuiCoinMetadata>> {
+        Ok(self.coin_read_api().get_coin_metadata(coin_type).await?)
+    }
+
+    async fn get_total_supply(&self, coin_type: String) -> Result<Supply> {
+        Ok(self.coin_read_api().get_total_supply(coin_type).await?)
+    }
+
+    async fn get_balance(&self, owner: SuiAddress, coin_type: String) -> Result<Balance> {
+        Ok(self
+            .coin_read_api()
+            .get_balance(owner, Some(coin_type))
+            .await?)
+    }
+
+    async fn dev_inspect(
+        &self,
+        sender: SuiAddress,
+        tx: TransactionKind,
+    ) -> Result<Vec<SuiExecutionResult>> {
+        let resp = self
+            .read_api()
+            .dev_inspect_transaction_block(sender, tx, None, None, None)
+            .await?;
+        if let Some(err) = resp.error {
+            return Err(anyhow!("Dev-inspect failed: {err}"));
+        }
+        Ok(resp.results.unwrap_or_default())
+    }
+
+    async fn dry_run(&self, tx_data: TransactionData) -> Result<DryRunOutcome> {
+        let resp = self.read_api().dry_run_transaction_block(tx_data).await?;
+        let status = match resp.effects.status() {
+            SuiExecutionStatus::Success => Ok(()),
+            SuiExecutionStatus::Failure { error } => Err(error.clone()),
+        };
+        Ok(DryRunOutcome {
+            status,
+            gas_used: resp.effects.gas_cost_summary().net_gas_usage(),
+            object_changes: resp.object_changes,
+            balance_changes: resp.balance_changes,
+        })
+    }
+
+    async fn execute(&self, tx: Transaction) -> Result<SuiTransactionBlockResponse> {
+        Ok(self
+            .quorum_driver_api()
+            .execute_transaction_block(
+                tx,
+                SuiTransactionBlockResponseOptions::new()
+                    .with_effects()
+                    .with_object_changes()
+                    .with_balance_changes()
+                    .with_input(),
+                Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+            )
+            .await?)
+    }
+
+    async fn query_transaction_blocks(
+        &self,
+        query: SuiTransactionBlockResponseQuery,
+        cursor: Option<TransactionDigest>,
+        limit: Option<usize>,
+        descending: bool,
+    ) -> Result<TransactionBlocksPage> {
+        Ok(self
+            .read_api()
+            .query_transaction_blocks(query, cursor, limit, descending)
+            .await?)
+    }
+
+    async fn query_events(
+        &self,
+        query: EventFilter,
+        cursor: Option<EventID>,
+        limit: Option<usize>,
+        descending: bool,
+    ) -> Result<EventPage> {
+        Ok(self
+            .event_api()
+            .query_events(query, cursor, limit, descending)
+            .await?)
+    }
+
+    async fn get_checkpoints(
+        &self,
+        cursor: Option<u64>,
+        limit: Option<usize>,
+        descending: bool,
+    ) -> Result<CheckpointPage> {
+        Ok(self
+            .read_api()
+            .get_checkpoints(cursor.map(BigInt::from), limit, descending)
+            .await?)
+    }
+
+    async fn multi_get_transaction_blocks(
+        &self,
+        digests: Vec<TransactionDigest>,
+        options: SuiTransactionBlockResponseOptions,
+    ) -> Result<Vec<SuiTransactionBlockResponse>> {
+        Ok(self
+            .read_api()
+            .multi_get_transactions_with_options(digests, options)
+            .await?)
+    }
+}
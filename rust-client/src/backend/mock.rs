@@ -0,0 +1,1210 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use serde::de::DeserializeOwned;
+use sui_sdk::rpc_types::{
+    Balance, CheckpointPage, EventFilter, EventPage, ObjectChange, ObjectsPage, SuiCoinMetadata,
+    SuiExecutionResult, SuiObjectData, SuiObjectDataFilter, SuiObjectDataOptions,
+    SuiObjectResponse, SuiObjectResponseError, SuiObjectResponseQuery, SuiRawData,
+    SuiRawMoveObject, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+    SuiTransactionBlockResponseQuery, TransactionBlocksPage,
+};
+use sui_sdk::types::balance::Supply;
+use sui_sdk::types::base_types::{
+    MoveObjectType, ObjectID, ObjectRef, ObjectType, SequenceNumber, SuiAddress,
+};
+use sui_sdk::types::coin::{Coin, COIN_MODULE_NAME, COIN_TREASURE_CAP_NAME};
+use sui_sdk::types::digests::{ObjectDigest, TransactionDigest};
+use sui_sdk::types::event::EventID;
+use sui_sdk::types::gas_coin::GasCoin;
+use sui_sdk::types::id::UID;
+use sui_sdk::types::object::Owner;
+use sui_sdk::types::transaction::{
+    Argument, CallArg, Command, ObjectArg, ProgrammableTransaction, Transaction, TransactionData,
+    TransactionDataAPI, TransactionKind,
+};
+use sui_sdk::types::{SUI_DENY_LIST_OBJECT_ID, SUI_FRAMEWORK_ADDRESS, SUI_FRAMEWORK_PACKAGE_ID};
+
+use super::{ChainBackend, DryRunOutcome};
+use crate::tx_run::{COMPLIANCE_MODULE, RECORD_DENY_LIST_CHANGE, RECORD_DENY_LIST_CHANGE_V2};
+
+pub const MOCK_GAS_PRICE: u64 = 1_000;
+const DENY_LIST_INITIAL_SHARED_VERSION: u64 = 1;
+
+/// An in-memory chain that simulates gas coins, regulated coins, their caps and the deny-list.
+/// It only understands the transactions this client builds: `0x2::coin` calls to `mint`, `burn`,
+/// the v1 and v2 deny-list functions and the global pause, calls to
+/// `compliance::record_deny_list_change` and its v2 variant of any package, and object transfers.
+/// Clones share the same chain.
+#[derive(Debug, Clone, Default)]
+pub struct MockBackend {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct MockState {
+    objects: BTreeMap<ObjectID, MockObject>,
+    denied: BTreeMap<TypeTag, BTreeSet<SuiAddress>>,
+    /// The deny-list of coins with a `DenyCapV2`
+    denied_v2: BTreeMap<(TypeTag, SuiAddress), EpochFlag>,
+    paused: BTreeMap<TypeTag, EpochFlag>,
+    supplies: BTreeMap<TypeTag, u64>,
+    metadata: BTreeMap<TypeTag, SuiCoinMetadata>,
+    /// Deny-list changes recorded by the compliance module, in order
+    deny_reasons: Vec<(TypeTag, DenyReasonEvent)>,
+    /// The publish transactions of the coins and every executed transaction
+    transactions: BTreeMap<TransactionDigest, SuiTransactionBlockResponse>,
+    /// The packages of the coins, by the transaction that published them
+    packages: BTreeMap<ObjectID, TransactionDigest>,
+    lamport: u64,
+    epoch: u64,
+    /// The checkpoint new transactions are recorded in
+    checkpoint: u64,
+    /// Reads of owned objects fail, as on an unreachable node
+    owned_objects_unavailable: bool,
+}
+
+/// A `DenyListChange` event of the compliance module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DenyReasonEvent {
+    pub addr: SuiAddress,
+    pub added: bool,
+    pub reason: String,
+    pub case_id: String,
+}
+
+/// A v2 deny-list setting, which receivers see from the epoch after it was set.
+#[derive(Debug, Clone, Copy, Default)]
+struct EpochFlag {
+    previous: bool,
+    next: bool,
+    since_epoch: u64,
+}
+
+impl EpochFlag {
+    fn current(&self, epoch: u64) -> bool {
+        if epoch > self.since_epoch {
+            self.next
+        } else {
+            self.previous
+        }
+    }
+
+    fn set(&mut self, value: bool, epoch: u64) {
+        self.previous = self.current(epoch);
+        self.next = value;
+        self.since_epoch = epoch;
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockObject {
+    version: SequenceNumber,
+    digest: ObjectDigest,
+    owner: Owner,
+    previous_transaction: TransactionDigest,
+    contents: Contents,
+}
+
+#[derive(Debug, Clone)]
+enum Contents {
+    Gas(u64),
+    Coin {
+        coin_type: TypeTag,
+        value: u64,
+    },
+    TreasuryCap {
+        coin_type: TypeTag,
+    },
+    DenyCap {
+        coin_type: TypeTag,
+    },
+    DenyCapV2 {
+        coin_type: TypeTag,
+        allow_global_pause: bool,
+    },
+    Other(StructTag),
+}
+
+/// Why a simulated transaction did not go through.
+enum Failure {
+    /// Rejected before execution, eg. a denied sender. No response is returned.
+    Invalid(String),
+    /// Aborted during execution. The response carries the error.
+    Abort(String),
+}
+
+#[derive(Debug, Clone)]
+enum Value {
+    Object(ObjectID),
+    Pure(Vec<u8>),
+    Returned(Vec<u8>, TypeTag),
+}
+
+struct Executed {
+    results: Vec<Vec<Value>>,
+    object_changes: Vec<ObjectChange>,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the `TreasuryCap`, `DenyCap` and `CoinMetadata` of `coin_type` owned by `owner`,
+    /// as `0x2::coin::create_regulated_currency` would. Returns the ids of the caps.
+    pub fn create_regulated_coin(
+        &self,
+        coin_type: TypeTag,
+        owner: SuiAddress,
+    ) -> (ObjectID, ObjectID) {
+        let mut state = self.state.lock().unwrap();
+        let deny_cap = Contents::DenyCap {
+            coin_type: coin_type.clone(),
+        };
+        state.publish(coin_type, owner, deny_cap)
+    }
+
+    /// Like [`MockBackend::create_regulated_coin`], as `0x2::coin::create_regulated_currency_v2`
+    /// would, with a `DenyCapV2`.
+    pub fn create_regulated_coin_v2(
+        &self,
+        coin_type: TypeTag,
+        owner: SuiAddress,
+        allow_global_pause: bool,
+    ) -> (ObjectID, ObjectID) {
+        let mut state = self.state.lock().unwrap();
+        let deny_cap = Contents::DenyCapV2 {
+            coin_type: coin_type.clone(),
+            allow_global_pause,
+        };
+        state.publish(coin_type, owner, deny_cap)
+    }
+
+    /// Creates the `CoinMetadata` of a coin that is not regulated, without a `DenyCap`.
+    pub fn create_coin(&self, coin_type: TypeTag, owner: SuiAddress) -> ObjectID {
+        let mut state = self.state.lock().unwrap();
+        let digest = state.package_digest(&coin_type);
+        let treasury_cap = state.insert(
+            Owner::AddressOwner(owner),
+            Contents::TreasuryCap {
+                coin_type: coin_type.clone(),
+            },
+        );
+        let metadata = state.create_metadata(coin_type, owner);
+        state.record_publish(digest, owner, &[treasury_cap, metadata]);
+        treasury_cap
+    }
+
+    /// Changes the description of the `CoinMetadata` of `coin_type` in a new transaction, as
+    /// `0x2::coin::update_description` would.
+    pub fn update_metadata(&self, coin_type: &TypeTag, description: &str) {
+        let mut state = self.state.lock().unwrap();
+        let metadata = state.metadata.get_mut(coin_type).unwrap();
+        metadata.description = description.to_string();
+        let id = metadata.id.unwrap();
+        let digest = TransactionDigest::random();
+        let version = state.next_version();
+        let obj = state.objects.get_mut(&id).unwrap();
+        obj.version = version;
+        obj.previous_transaction = digest;
+        let mut resp = SuiTransactionBlockResponse::new(digest);
+        resp.checkpoint = Some(state.checkpoint);
+        state.transactions.insert(digest, resp);
+    }
+
+    pub fn add_gas(&self, owner: SuiAddress, value: u64) -> ObjectID {
+        let mut state = self.state.lock().unwrap();
+        state.insert(Owner::AddressOwner(owner), Contents::Gas(value))
+    }
+
+    /// Adds a coin to `owner`, increasing the supply of `coin_type`.
+    pub fn add_coin(&self, owner: SuiAddress, coin_type: TypeTag, value: u64) -> ObjectID {
+        let mut state = self.state.lock().unwrap();
+        *state.supplies.entry(coin_type.clone()).or_default() += value;
+        state.insert(
+            Owner::AddressOwner(owner),
+            Contents::Coin { coin_type, value },
+        )
+    }
+
+    /// Adds an arbitrary object, eg. an NFT, to `owner`.
+    pub fn add_object(&self, owner: SuiAddress, tag: StructTag) -> ObjectID {
+        let mut state = self.state.lock().unwrap();
+        state.insert(Owner::AddressOwner(owner), Contents::Other(tag))
+    }
+
+    /// The coins of `coin_type` owned by `owner`, with their values.
+    pub fn coins(&self, owner: SuiAddress, coin_type: &TypeTag) -> Vec<(ObjectID, u64)> {
+        let state = self.state.lock().unwrap();
+        state
+            .objects
+            .iter()
+            .filter(|(_, obj)| obj.owner == Owner::AddressOwner(owner))
+            .filter_map(|(id, obj)| match &obj.contents {
+                Contents::Coin {
+                    coin_type: t,
+                    value,
+                } if t == coin_type => Some((*id, *value)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn balance(&self, owner: SuiAddress, coin_type: &TypeTag) -> u64 {
+        self.coins(owner, coin_type)
+            .into_iter()
+            .map(|(_, value)| value)
+            .sum()
+    }
+
+    pub fn total_supply(&self, coin_type: &TypeTag) -> u64 {
+        let state = self.state.lock().unwrap();
+        state.supplies.get(coin_type).copied().unwrap_or_default()
+    }
+
+    pub fn is_denied(&self, coin_type: &TypeTag, addr: SuiAddress) -> bool {
+        let state = self.state.lock().unwrap();
+        state.is_denied(coin_type, addr)
+    }
+
+    /// Whether `coin_type` is globally paused, as of the latest change.
+    pub fn is_paused(&self, coin_type: &TypeTag) -> bool {
+        let state = self.state.lock().unwrap();
+        state.is_paused(coin_type)
+    }
+
+    pub fn owner(&self, id: ObjectID) -> Option<Owner> {
+        let state = self.state.lock().unwrap();
+        state.objects.get(&id).map(|obj| obj.owner)
+    }
+
+    /// Makes reads of owned objects fail with an error, or succeed again.
+    pub fn set_owned_objects_unavailable(&self, unavailable: bool) {
+        self.state.lock().unwrap().owned_objects_unavailable = unavailable;
+    }
+
+    /// Moves the chain to `epoch`.
+    pub fn set_epoch(&self, epoch: u64) {
+        self.state.lock().unwrap().epoch = epoch;
+    }
+
+    /// Records the transactions from now on in `checkpoint`.
+    pub fn set_checkpoint(&self, checkpoint: u64) {
+        self.state.lock().unwrap().checkpoint = checkpoint;
+    }
+
+    /// The deny-list changes of `coin_type` recorded by the compliance module, in order.
+    pub fn deny_reasons(&self, coin_type: &TypeTag) -> Vec<DenyReasonEvent> {
+        let state = self.state.lock().unwrap();
+        state
+            .deny_reasons
+            .iter()
+            .filter(|(t, _)| t == coin_type)
+            .map(|(_, event)| event.clone())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl ChainBackend for MockBackend {
+    async fn get_object_with_options(
+        &self,
+        id: ObjectID,
+        _options: SuiObjectDataOptions,
+    ) -> Result<SuiObjectResponse> {
+        let state = self.state.lock().unwrap();
+        if id == SUI_DENY_LIST_OBJECT_ID {
+            return Ok(SuiObjectResponse::new_with_data(deny_list_data()));
+        }
+        if let Some(digest) = state.packages.get(&id) {
+            return Ok(SuiObjectResponse::new_with_data(package_data(id, *digest)));
+        }
+        Ok(match state.objects.get(&id) {
+            Some(obj) => SuiObjectResponse::new_with_data(obj.data(id)),
+            None => SuiObjectResponse::new_with_error(SuiObjectResponseError::NotExists {
+                object_id: id,
+            }),
+        })
+    }
+
+    async fn get_owned_objects(
+        &self,
+        owner: SuiAddress,
+        query: Option<SuiObjectResponseQuery>,
+        _cursor: Option<ObjectID>,
+        _limit: Option<usize>,
+    ) -> Result<ObjectsPage> {
+        let filter = query.and_then(|query| query.filter);
+        let state = self.state.lock().unwrap();
+        if state.owned_objects_unavailable {
+            return Err(anyhow!("Owned objects of {owner} are unavailable"));
+        }
+        let data = state
+            .objects
+            .iter()
+            .filter(|(_, obj)| obj.owner == Owner::AddressOwner(owner))
+            .filter(|(_, obj)| match &filter {
+                Some(SuiObjectDataFilter::StructType(tag)) => &obj.contents.struct_tag() == tag,
+                Some(_) => false,
+                None => true,
+            })
+            .map(|(id, obj)| SuiObjectResponse::new_with_data(obj.data(*id)))
+            .collect();
+        Ok(ObjectsPage {
+            data,
+            next_cursor: None,
+            has_next_page: false,
+        })
+    }
+
+    async fn get_reference_gas_price(&self) -> Result<u64> {
+        Ok(MOCK_GAS_PRICE)
+    }
+
+    async fn get_epoch(&self) -> Result<u64> {
+        Ok(self.state.lock().unwrap().epoch)
+    }
+
+    async fn get_coin_metadata(&self, coin_type: String) -> Result<Option<SuiCoinMetadata>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .metadata
+            .iter()
+            .find(|(t, _)| t.to_string() == coin_type)
+            .map(|(_, metadata)| metadata.clone()))
+    }
+
+    async fn get_total_supply(&self, coin_type: String) -> Result<Supply> {
+        let state = self.state.lock().unwrap();
+        let value = state
+            .supplies
+            .iter()
+            .find(|(t, _)| t.to_string() == coin_type)
+            .map(|(_, value)| *value)
+            .ok_or(anyhow!("No coin of type {coin_type}"))?;
+        Ok(Supply { value })
+    }
+
+    async fn get_balance(&self, owner: SuiAddress, coin_type: String) -> Result<Balance> {
+        let state = self.state.lock().unwrap();
+        let values: Vec<u64> = state
+            .objects
+            .values()
+            .filter(|obj| obj.owner == Owner::AddressOwner(owner))
+            .filter_map(|obj| match &obj.contents {
+                Contents::Coin {
+                    coin_type: t,
+                    value,
+                } if t.to_string() == coin_type => Some(*value),
+                _ => None,
+            })
+            .collect();
+        Ok(Balance {
+            coin_type,
+            coin_object_count: values.len(),
+            total_balance: values.iter().map(|value| *value as u128).sum(),
+            locked_balance: Default::default(),
+        })
+    }
+
+    async fn dev_inspect(
+        &self,
+        sender: SuiAddress,
+        tx: TransactionKind,
+    ) -> Result<Vec<SuiExecutionResult>> {
+        let TransactionKind::ProgrammableTransaction(pt) = tx else {
+            return Err(anyhow!("Mock only supports programmable transactions"));
+        };
+        let mut state = self.state.lock().unwrap().clone();
+        let executed = state
+            .execute(sender, &pt, &[], TransactionDigest::random())
+            .map_err(|failure| anyhow!("Dev-inspect failed: {}", failure.message()))?;
+        Ok(executed
+            .results
+            .into_iter()
+            .map(|values| SuiExecutionResult {
+                mutable_reference_outputs: vec![],
+                return_values: values
+                    .into_iter()
+                    .filter_map(|value| match value {
+                        Value::Returned(bytes, type_tag) => Some((bytes, type_tag.into())),
+                        _ => None,
+                    })
+                    .collect(),
+            })
+            .collect())
+    }
+
+    async fn dry_run(&self, tx_data: TransactionData) -> Result<DryRunOutcome> {
+        let TransactionKind::ProgrammableTransaction(pt) = tx_data.kind() else {
+            return Err(anyhow!("Mock only supports programmable transactions"));
+        };
+        let mut state = self.state.lock().unwrap().clone();
+        let (status, object_changes) = match state.execute(
+            tx_data.sender(),
+            pt,
+            tx_data.gas(),
+            TransactionDigest::random(),
+        ) {
+            Ok(executed) => (Ok(()), executed.object_changes),
+            Err(Failure::Abort(e)) => (Err(e), vec![]),
+            Err(Failure::Invalid(e)) => return Err(anyhow!(e)),
+        };
+        Ok(DryRunOutcome {
+            status,
+            gas_used: 0,
+            object_changes,
+            balance_changes: vec![],
+        })
+    }
+
+    async fn execute(&self, tx: Transaction) -> Result<SuiTransactionBlockResponse> {
+        let digest = *tx.digest();
+        let tx_data = tx.data().transaction_data();
+        let TransactionKind::ProgrammableTransaction(pt) = tx_data.kind() else {
+            return Err(anyhow!("Mock only supports programmable transactions"));
+        };
+        let mut guard = self.state.lock().unwrap();
+        let mut state = guard.clone();
+        let mut resp = SuiTransactionBlockResponse::new(digest);
+        match state.execute(tx_data.sender(), pt, tx_data.gas(), digest) {
+            Ok(executed) => {
+                *guard = state;
+                resp.object_changes = Some(executed.object_changes);
+            }
+            Err(Failure::Abort(e)) => resp.errors = vec![e],
+            Err(Failure::Invalid(e)) => return Err(anyhow!(e)),
+        }
+        resp.confirmed_local_execution = Some(true);
+        resp.checkpoint = Some(guard.checkpoint);
+        guard.transactions.insert(digest, resp.clone());
+        Ok(resp)
+    }
+
+    /// The mock keeps no checkpoints, so queries return nothing. Transactions can only be read
+    /// by digest, with [`ChainBackend::multi_get_transaction_blocks`].
+    async fn query_transaction_blocks(
+        &self,
+        _query: SuiTransactionBlockResponseQuery,
+        _cursor: Option<TransactionDigest>,
+        _limit: Option<usize>,
+        _descending: bool,
+    ) -> Result<TransactionBlocksPage> {
+        Ok(TransactionBlocksPage {
+            data: vec![],
+            next_cursor: None,
+            has_next_page: false,
+        })
+    }
+
+    async fn query_events(
+        &self,
+        _query: EventFilter,
+        _cursor: Option<EventID>,
+        _limit: Option<usize>,
+        _descending: bool,
+    ) -> Result<EventPage> {
+        Ok(EventPage {
+            data: vec![],
+            next_cursor: None,
+            has_next_page: false,
+        })
+    }
+
+    async fn get_checkpoints(
+        &self,
+        _cursor: Option<u64>,
+        _limit: Option<usize>,
+        _descending: bool,
+    ) -> Result<CheckpointPage> {
+        Ok(CheckpointPage {
+            data: vec![],
+            next_cursor: None,
+            has_next_page: false,
+        })
+    }
+
+    async fn multi_get_transaction_blocks(
+        &self,
+        digests: Vec<TransactionDigest>,
+        _options: SuiTransactionBlockResponseOptions,
+    ) -> Result<Vec<SuiTransactionBlockResponse>> {
+        let state = self.state.lock().unwrap();
+        digests
+            .iter()
+            .map(|digest| {
+                state
+                    .transactions
+                    .get(digest)
+                    .cloned()
+                    .ok_or(anyhow!("Transaction {digest} not found"))
+            })
+            .collect()
+    }
+}
+
+impl MockState {
+    fn next_version(&mut self) -> SequenceNumber {
+        self.lamport += 1;
+        SequenceNumber::from_u64(self.lamport)
+    }
+
+    fn insert(&mut self, owner: Owner, contents: Contents) -> ObjectID {
+        let id = ObjectID::random();
+        let version = self.next_version();
+        self.objects.insert(
+            id,
+            MockObject {
+                version,
+                digest: ObjectDigest::random(),
+                owner,
+                previous_transaction: TransactionDigest::genesis_marker(),
+                contents,
+            },
+        );
+        id
+    }
+
+    /// Creates the caps and the metadata of a regulated coin in one publish transaction.
+    fn publish(
+        &mut self,
+        coin_type: TypeTag,
+        owner: SuiAddress,
+        deny_cap: Contents,
+    ) -> (ObjectID, ObjectID) {
+        let digest = self.package_digest(&coin_type);
+        let treasury_cap = self.insert(
+            Owner::AddressOwner(owner),
+            Contents::TreasuryCap {
+                coin_type: coin_type.clone(),
+            },
+        );
+        let deny_cap = self.insert(Owner::AddressOwner(owner), deny_cap);
+        let metadata = self.create_metadata(coin_type, owner);
+        self.record_publish(digest, owner, &[treasury_cap, deny_cap, metadata]);
+        (treasury_cap, deny_cap)
+    }
+
+    /// The transaction that published the package of `coin_type`. The coins of one package are
+    /// all created by it, in the `init` of their modules.
+    fn package_digest(&mut self, coin_type: &TypeTag) -> TransactionDigest {
+        let TypeTag::Struct(tag) = coin_type else {
+            return TransactionDigest::random();
+        };
+        *self
+            .packages
+            .entry(ObjectID::from(tag.address))
+            .or_insert_with(TransactionDigest::random)
+    }
+
+    /// Records `created` as created by the transaction `digest` of `sender`, adding them to the
+    /// transaction if it was recorded already.
+    fn record_publish(
+        &mut self,
+        digest: TransactionDigest,
+        sender: SuiAddress,
+        created: &[ObjectID],
+    ) {
+        let mut object_changes = vec![];
+        for id in created {
+            let obj = self.objects.get_mut(id).unwrap();
+            obj.previous_transaction = digest;
+            object_changes.push(ObjectChange::Created {
+                sender,
+                owner: obj.owner,
+                object_type: obj.contents.struct_tag(),
+                object_id: *id,
+                version: obj.version,
+                digest: obj.digest,
+            });
+        }
+        let checkpoint = self.checkpoint;
+        let resp = self.transactions.entry(digest).or_insert_with(|| {
+            let mut resp = SuiTransactionBlockResponse::new(digest);
+            resp.checkpoint = Some(checkpoint);
+            resp
+        });
+        resp.object_changes
+            .get_or_insert_with(Vec::new)
+            .extend(object_changes);
+    }
+
+    /// Creates the `CoinMetadata` and the empty supply of `coin_type`, returning the id of the
+    /// metadata.
+    fn create_metadata(&mut self, coin_type: TypeTag, owner: SuiAddress) -> ObjectID {
+        let name = match &coin_type {
+            TypeTag::Struct(tag) => tag.name.to_string(),
+            other => other.to_string(),
+        };
+        self.supplies.insert(coin_type.clone(), 0);
+        let tag = StructTag {
+            address: SUI_FRAMEWORK_ADDRESS,
+            module: Identifier::from(COIN_MODULE_NAME),
+            name: Identifier::new("CoinMetadata").unwrap(),
+            type_params: vec![coin_type.clone()],
+        };
+        // Transferred to the publisher, as the `init` of the coin templates does
+        let id = self.insert(Owner::AddressOwner(owner), Contents::Other(tag));
+        self.metadata.insert(
+            coin_type,
+            SuiCoinMetadata {
+                decimals: 5,
+                name: name.clone(),
+                symbol: name,
+                description: "Mock regulated coin".to_string(),
+                icon_url: None,
+                id: Some(id),
+            },
+        );
+        id
+    }
+
+    /// Whether `addr` cannot spend `coin_type`: v2 changes and the global pause apply to
+    /// spending at once.
+    fn is_denied(&self, coin_type: &TypeTag, addr: SuiAddress) -> bool {
+        self.denied
+            .get(coin_type)
+            .is_some_and(|denied| denied.contains(&addr))
+            || self
+                .denied_v2
+                .get(&(coin_type.clone(), addr))
+                .is_some_and(|flag| flag.next)
+            || self.is_paused(coin_type)
+    }
+
+    fn is_paused(&self, coin_type: &TypeTag) -> bool {
+        self.paused.get(coin_type).is_some_and(|flag| flag.next)
+    }
+
+    /// Runs `pt` against the state. On failure the state is left half-updated, so callers
+    /// execute on a copy.
+    fn execute(
+        &mut self,
+        sender: SuiAddress,
+        pt: &ProgrammableTransaction,
+        gas: &[ObjectRef],
+        digest: TransactionDigest,
+    ) -> Result<Executed, Failure> {
+        let mut mutated = BTreeSet::new();
+        for (id, version, _) in gas {
+            let obj = self.owned_by(*id, sender)?;
+            if obj.version != *version {
+                return Err(Failure::Invalid(format!(
+                    "Gas object {id} is not at version {version}"
+                )));
+            }
+            if !matches!(obj.contents, Contents::Gas(_)) {
+                return Err(Failure::Invalid(format!("Object {id} is not a gas coin")));
+            }
+            mutated.insert(*id);
+        }
+        for input in &pt.inputs {
+            match input {
+                CallArg::Object(ObjectArg::ImmOrOwnedObject((id, version, _))) => {
+                    let obj = self.owned_by(*id, sender)?;
+                    if obj.version != *version {
+                        return Err(Failure::Invalid(format!(
+                            "Object {id} is not at version {version}"
+                        )));
+                    }
+                    if let Contents::Coin { coin_type, .. } = &obj.contents {
+                        if self.is_denied(coin_type, sender) {
+                            return Err(Failure::Invalid(format!(
+                                "Address {sender} is denied for coin {coin_type}"
+                            )));
+                        }
+                    }
+                }
+                CallArg::Object(ObjectArg::SharedObject { id, .. }) => {
+                    if *id != SUI_DENY_LIST_OBJECT_ID {
+                        return Err(Failure::Invalid(format!("Unknown shared object {id}")));
+                    }
+                }
+                CallArg::Object(_) => {
+                    return Err(Failure::Invalid("Unsupported object argument".to_string()));
+                }
+                CallArg::Pure(_) => {}
+            }
+        }
+
+        let mut results: Vec<Vec<Value>> = vec![];
+        let mut created = BTreeSet::new();
+        let mut deleted = vec![];
+        // Created objects that have not been transferred yet
+        let mut unowned = BTreeSet::new();
+        for command in &pt.commands {
+            let arg_value = |arg: &Argument| resolve(arg, &pt.inputs, &results, gas);
+            let values = match command {
+                Command::MoveCall(call) if call.module.as_str() == COMPLIANCE_MODULE => {
+                    let function = call.function.as_str();
+                    if function != RECORD_DENY_LIST_CHANGE && function != RECORD_DENY_LIST_CHANGE_V2
+                    {
+                        return Err(Failure::Invalid(format!(
+                            "Mock does not support {COMPLIANCE_MODULE}::{function}"
+                        )));
+                    }
+                    let coin_type = call
+                        .type_arguments
+                        .first()
+                        .cloned()
+                        .ok_or(Failure::Invalid("Missing coin type argument".to_string()))?;
+                    let args = call
+                        .arguments
+                        .iter()
+                        .map(arg_value)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let [cap, addr, added, reason, case_id] = args.as_slice() else {
+                        return Err(Failure::Invalid(format!(
+                            "Mock does not support {COMPLIANCE_MODULE}::{function} with these arguments"
+                        )));
+                    };
+                    if function == RECORD_DENY_LIST_CHANGE {
+                        self.expect_deny_cap(object_id(cap)?, &coin_type)?;
+                    } else {
+                        self.expect_deny_cap_v2(object_id(cap)?, &coin_type)?;
+                    }
+                    let event = DenyReasonEvent {
+                        addr: pure(addr)?,
+                        added: pure(added)?,
+                        reason: pure(reason)?,
+                        case_id: pure(case_id)?,
+                    };
+                    self.deny_reasons.push((coin_type, event));
+                    vec![]
+                }
+                Command::MoveCall(call) => {
+                    if call.package != SUI_FRAMEWORK_PACKAGE_ID
+                        || call.module.as_str() != COIN_MODULE_NAME.as_str()
+                    {
+                        return Err(Failure::Invalid(format!(
+                            "Mock does not support {}::{}",
+                            call.package, call.module
+                        )));
+                    }
+                    let coin_type = call
+                        .type_arguments
+                        .first()
+                        .cloned()
+                        .ok_or(Failure::Invalid("Missing coin type argument".to_string()))?;
+                    let args = call
+                        .arguments
+                        .iter()
+                        .map(arg_value)
+                        .collect::<Result<Vec<_>, _>>()?;
+                    match (call.function.as_str(), args.as_slice()) {
+                        ("mint", [cap, amount]) => {
+                            let cap = object_id(cap)?;
+                            self.expect_treasury_cap(cap, &coin_type)?;
+                            let amount: u64 = pure(amount)?;
+                            let supply = self.supplies.entry(coin_type.clone()).or_default();
+                            *supply = supply
+                                .checked_add(amount)
+                                .ok_or(Failure::Abort("Supply overflow".to_string()))?;
+                            let id = self.insert(
+                                Owner::AddressOwner(sender),
+                                Contents::Coin {
+                                    coin_type,
+                                    value: amount,
+                                },
+                            );
+                            mutated.insert(cap);
+                            created.insert(id);
+                            unowned.insert(id);
+                            vec![Value::Object(id)]
+                        }
+                        ("burn", [cap, coin]) => {
+                            let (cap, coin) = (object_id(cap)?, object_id(coin)?);
+                            self.expect_treasury_cap(cap, &coin_type)?;
+                            let obj = self
+                                .objects
+                                .remove(&coin)
+                                .ok_or(Failure::Invalid(format!("Object {coin} not found")))?;
+                            let Contents::Coin {
+                                coin_type: t,
+                                value,
+                            } = &obj.contents
+                            else {
+                                return Err(Failure::Abort(format!("{coin} is not a coin")));
+                            };
+                            if t != &coin_type {
+                                return Err(Failure::Abort(format!("{coin} is not a {coin_type}")));
+                            }
+                            let value = *value;
+                            *self.supplies.entry(coin_type.clone()).or_default() -= value;
+                            mutated.insert(cap);
+                            deleted.push((coin, obj));
+                            vec![Value::Returned(
+                                bcs::to_bytes(&value).unwrap(),
+                                TypeTag::U64,
+                            )]
+                        }
+                        ("deny_list_add", [list, cap, addr])
+                        | ("deny_list_remove", [list, cap, addr]) => {
+                            expect_deny_list(list)?;
+                            let cap = object_id(cap)?;
+                            self.expect_deny_cap(cap, &coin_type)?;
+                            let addr: SuiAddress = pure(addr)?;
+                            let denied = self.denied.entry(coin_type).or_default();
+                            if call.function.as_str() == "deny_list_add" {
+                                denied.insert(addr);
+                            } else if !denied.remove(&addr) {
+                                return Err(Failure::Abort(format!("{addr} is not denied")));
+                            }
+                            mutated.insert(cap);
+                            vec![]
+                        }
+                        ("deny_list_contains", [list, addr]) => {
+                            expect_deny_list(list)?;
+                            let addr: SuiAddress = pure(addr)?;
+                            let contains = self
+                                .denied
+                                .get(&coin_type)
+                                .is_some_and(|denied| denied.contains(&addr));
+                            vec![Value::Returned(
+                                bcs::to_bytes(&contains).unwrap(),
+                                TypeTag::Bool,
+                            )]
+                        }
+                        ("deny_list_v2_add", [list, cap, addr])
+                        | ("deny_list_v2_remove", [list, cap, addr]) => {
+                            expect_deny_list(list)?;
+                            let cap = object_id(cap)?;
+                            self.expect_deny_cap_v2(cap, &coin_type)?;
+                            let addr: SuiAddress = pure(addr)?;
+                            let epoch = self.epoch;
+                            self.denied_v2
+                                .entry((coin_type, addr))
+                                .or_default()
+                                .set(call.function.as_str() == "deny_list_v2_add", epoch);
+                            mutated.insert(cap);
+                            vec![]
+                        }
+                        ("deny_list_v2_contains_current_epoch", [list, addr])
+                        | ("deny_list_v2_contains_next_epoch", [list, addr]) => {
+                            expect_deny_list(list)?;
+                            let addr: SuiAddress = pure(addr)?;
+                            let flag = self.denied_v2.get(&(coin_type, addr)).copied();
+                            let contains = match call.function.as_str() {
+                                "deny_list_v2_contains_next_epoch" => flag.is_some_and(|f| f.next),
+                                _ => flag.is_some_and(|f| f.current(self.epoch)),
+                            };
+                            vec![Value::Returned(
+                                bcs::to_bytes(&contains).unwrap(),
+                                TypeTag::Bool,
+                            )]
+                        }
+                        ("deny_list_v2_enable_global_pause", [list, cap])
+                        | ("deny_list_v2_disable_global_pause", [list, cap]) => {
+                            expect_deny_list(list)?;
+                            let cap = object_id(cap)?;
+                            if !self.expect_deny_cap_v2(cap, &coin_type)? {
+                                return Err(Failure::Abort(format!(
+                                    "{cap} does not allow global pause"
+                                )));
+                            }
+                            let epoch = self.epoch;
+                            self.paused.entry(coin_type).or_default().set(
+                                call.function.as_str() == "deny_list_v2_enable_global_pause",
+                                epoch,
+                            );
+                            mutated.insert(cap);
+                            vec![]
+                        }
+                        ("deny_list_v2_is_global_pause_enabled_current_epoch", [list])
+                        | ("deny_list_v2_is_global_pause_enabled_next_epoch", [list]) => {
+                            expect_deny_list(list)?;
+                            let flag = self.paused.get(&coin_type).copied();
+                            let paused = match call.function.as_str() {
+                                "deny_list_v2_is_global_pause_enabled_next_epoch" => {
+                                    flag.is_some_and(|f| f.next)
+                                }
+                                _ => flag.is_some_and(|f| f.current(self.epoch)),
+                            };
+                            vec![Value::Returned(
+                                bcs::to_bytes(&paused).unwrap(),
+                                TypeTag::Bool,
+                            )]
+                        }
+                        (function, _) => {
+                            return Err(Failure::Invalid(format!(
+                                "Mock does not support coin::{function} with these arguments"
+                            )))
+                        }
+                    }
+                }
+                Command::TransferObjects(objects, recipient) => {
+                    let recipient: SuiAddress = pure(&arg_value(recipient)?)?;
+                    for object in objects {
+                        let id = object_id(&arg_value(object)?)?;
+                        let obj = self
+                            .objects
+                            .get_mut(&id)
+                            .ok_or(Failure::Invalid(format!("Object {id} not found")))?;
+                        obj.owner = Owner::AddressOwner(recipient);
+                        unowned.remove(&id);
+                        mutated.insert(id);
+                    }
+                    vec![]
+                }
+                _ => {
+                    return Err(Failure::Invalid(
+                        "Mock does not support this command".to_string(),
+                    ))
+                }
+            };
+            results.push(values);
+        }
+        if let Some(id) = unowned.first() {
+            return Err(Failure::Abort(format!("Unused value without drop: {id}")));
+        }
+
+        let version = self.next_version();
+        let mut object_changes = vec![];
+        for (id, obj) in deleted {
+            object_changes.push(ObjectChange::Deleted {
+                sender,
+                object_type: obj.contents.struct_tag(),
+                object_id: id,
+                version,
+            });
+        }
+        for id in mutated.into_iter().chain(created.iter().copied()) {
+            let Some(obj) = self.objects.get_mut(&id) else {
+                // Burnt in this transaction
+                continue;
+            };
+            let previous_version = obj.version;
+            obj.version = version;
+            obj.digest = ObjectDigest::random();
+            obj.previous_transaction = digest;
+            object_changes.push(if created.contains(&id) {
+                ObjectChange::Created {
+                    sender,
+                    owner: obj.owner,
+                    object_type: obj.contents.struct_tag(),
+                    object_id: id,
+                    version,
+                    digest: obj.digest,
+                }
+            } else {
+                ObjectChange::Mutated {
+                    sender,
+                    owner: obj.owner,
+                    object_type: obj.contents.struct_tag(),
+                    object_id: id,
+                    version,
+                    previous_version,
+                    digest: obj.digest,
+                }
+            });
+        }
+
+        Ok(Executed {
+            results,
+            object_changes,
+        })
+    }
+
+    fn owned_by(&self, id: ObjectID, owner: SuiAddress) -> Result<&MockObject, Failure> {
+        let obj = self
+            .objects
+            .get(&id)
+            .ok_or(Failure::Invalid(format!("Object {id} not found")))?;
+        if obj.owner != Owner::AddressOwner(owner) {
+            return Err(Failure::Invalid(format!(
+                "Object {id} is not owned by {owner}"
+            )));
+        }
+        Ok(obj)
+    }
+
+    fn expect_treasury_cap(&self, id: ObjectID, coin_type: &TypeTag) -> Result<(), Failure> {
+        match self.objects.get(&id).map(|obj| &obj.contents) {
+            Some(Contents::TreasuryCap { coin_type: t }) if t == coin_type => Ok(()),
+            _ => Err(Failure::Abort(format!(
+                "{id} is not a TreasuryCap<{coin_type}>"
+            ))),
+        }
+    }
+
+    fn expect_deny_cap(&self, id: ObjectID, coin_type: &TypeTag) -> Result<(), Failure> {
+        match self.objects.get(&id).map(|obj| &obj.contents) {
+            Some(Contents::DenyCap { coin_type: t }) if t == coin_type => Ok(()),
+            _ => Err(Failure::Abort(format!(
+                "{id} is not a DenyCap<{coin_type}>"
+            ))),
+        }
+    }
+
+    /// Checks that `id` is a `DenyCapV2<coin_type>`, returning whether it allows global pause.
+    fn expect_deny_cap_v2(&self, id: ObjectID, coin_type: &TypeTag) -> Result<bool, Failure> {
+        match self.objects.get(&id).map(|obj| &obj.contents) {
+            Some(Contents::DenyCapV2 {
+                coin_type: t,
+                allow_global_pause,
+            }) if t == coin_type => Ok(*allow_global_pause),
+            _ => Err(Failure::Abort(format!(
+                "{id} is not a DenyCapV2<{coin_type}>"
+            ))),
+        }
+    }
+}
+
+impl MockObject {
+    fn data(&self, id: ObjectID) -> SuiObjectData {
+        let tag = self.contents.struct_tag();
+        SuiObjectData {
+            object_id: id,
+            version: self.version,
+            digest: self.digest,
+            type_: Some(ObjectType::Struct(MoveObjectType::from(tag.clone()))),
+            owner: Some(self.owner),
+            previous_transaction: Some(self.previous_transaction),
+            storage_rebate: None,
+            display: None,
+            content: None,
+            bcs: Some(SuiRawData::MoveObject(SuiRawMoveObject {
+                type_: tag,
+                has_public_transfer: true,
+                version: self.version,
+                bcs_bytes: self.contents.bcs_bytes(id),
+            })),
+        }
+    }
+}
+
+impl Contents {
+    fn struct_tag(&self) -> StructTag {
+        let coin_struct = |name: &str, coin_type: &TypeTag| StructTag {
+            address: SUI_FRAMEWORK_ADDRESS,
+            module: Identifier::from(COIN_MODULE_NAME),
+            name: Identifier::new(name).unwrap(),
+            type_params: vec![coin_type.clone()],
+        };
+        match self {
+            Contents::Gas(_) => GasCoin::type_(),
+            Contents::Coin { coin_type, .. } => Coin::type_(coin_type.clone()),
+            Contents::TreasuryCap { coin_type } => {
+                coin_struct(COIN_TREASURE_CAP_NAME.as_str(), coin_type)
+            }
+            Contents::DenyCap { coin_type } => coin_struct("DenyCap", coin_type),
+            Contents::DenyCapV2 { coin_type, .. } => coin_struct("DenyCapV2", coin_type),
+            Contents::Other(tag) => tag.clone(),
+        }
+    }
+
+    fn bcs_bytes(&self, id: ObjectID) -> Vec<u8> {
+        match self {
+            Contents::Gas(value) => bcs::to_bytes(&GasCoin::new(id, *value)),
+            Contents::Coin { value, .. } => bcs::to_bytes(&Coin::new(UID::new(id), *value)),
+            Contents::DenyCapV2 {
+                allow_global_pause, ..
+            } => bcs::to_bytes(&(UID::new(id), *allow_global_pause)),
+            _ => bcs::to_bytes(&UID::new(id)),
+        }
+        .unwrap()
+    }
+}
+
+impl Failure {
+    fn message(&self) -> &str {
+        match self {
+            Failure::Invalid(e) | Failure::Abort(e) => e,
+        }
+    }
+}
+
+fn deny_list_data() -> SuiObjectData {
+    let tag = StructTag {
+        address: SUI_FRAMEWORK_ADDRESS,
+        module: Identifier::new("deny_list").unwrap(),
+        name: Identifier::new("DenyList").unwrap(),
+        type_params: vec![],
+    };
+    let version = SequenceNumber::from_u64(DENY_LIST_INITIAL_SHARED_VERSION);
+    SuiObjectData {
+        object_id: SUI_DENY_LIST_OBJECT_ID,
+        version,
+        digest: ObjectDigest::random(),
+        type_: Some(ObjectType::Struct(MoveObjectType::from(tag))),
+        owner: Some(Owner::Shared {
+            initial_shared_version: version,
+        }),
+        previous_transaction: Some(TransactionDigest::genesis_marker()),
+        storage_rebate: None,
+        display: None,
+        content: None,
+        bcs: None,
+    }
+}
+
+fn package_data(id: ObjectID, publish: TransactionDigest) -> SuiObjectData {
+    SuiObjectData {
+        object_id: id,
+        version: SequenceNumber::from_u64(1),
+        digest: ObjectDigest::random(),
+        type_: Some(ObjectType::Package),
+        owner: Some(Owner::Immutable),
+        previous_transaction: Some(publish),
+        storage_rebate: None,
+        display: None,
+        content: None,
+        bcs: None,
+    }
+}
+
+fn resolve(
+    arg: &Argument,
+    inputs: &[CallArg],
+    results: &[Vec<Value>],
+    gas: &[ObjectRef],
+) -> Result<Value, Failure> {
+    let invalid = || Failure::Invalid(format!("Invalid argument {arg:?}"));
+    match arg {
+        Argument::GasCoin => gas
+            .first()
+            .map(|gas| Value::Object(gas.0))
+            .ok_or_else(invalid),
+        Argument::Input(i) => match inputs.get(*i as usize).ok_or_else(invalid)? {
+            CallArg::Pure(bytes) => Ok(Value::Pure(bytes.clone())),
+            CallArg::Object(obj) => Ok(Value::Object(obj.id())),
+        },
+        Argument::Result(i) => results
+            .get(*i as usize)
+            .and_then(|values| values.first())
+            .cloned()
+            .ok_or_else(invalid),
+        Argument::NestedResult(i, j) => results
+            .get(*i as usize)
+            .and_then(|values| values.get(*j as usize))
+            .cloned()
+            .ok_or_else(invalid),
+    }
+}
+
+fn object_id(value: &Value) -> Result<ObjectID, Failure> {
+    match value {
+        Value::Object(id) => Ok(*id),
+        _ => Err(Failure::Invalid("Expected an object argument".to_string())),
+    }
+}
+
+fn pure<T: DeserializeOwned>(value: &Value) -> Result<T, Failure> {
+    match value {
+        Value::Pure(bytes) | Value::Returned(bytes, _) => bcs::from_bytes(bytes)
+            .map_err(|e| Failure::Invalid(format!("Invalid pure argument: {e}"))),
+        Value::Object(_) => Err(Failure::Invalid("Expected a pure argument".to_string())),
+    }
+}
+
+fn expect_deny_list(value: &Value) -> Result<(), Failure> {
+    match value {
+        Value::Object(id) if *id == SUI_DENY_LIST_OBJECT_ID => Ok(()),
+        _ => Err(Failure::Invalid("Expected the deny-list".to_string())),
+    }
+}
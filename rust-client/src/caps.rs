@@ -0,0 +1,189 @@
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use move_core_types::language_storage::TypeTag;
+use sui_sdk::rpc_types::SuiObjectDataOptions;
+use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+use sui_sdk::types::digests::TransactionDigest;
+use sui_sdk::types::object::Owner;
+
+use crate::backend::ChainBackend;
+use crate::proposal::DryRunSummary;
+use crate::registry::CoinRegistry;
+use crate::tx_run::{
+    build_command, check_policy, execute_command_with, execution_error, get_coin_objects,
+    transaction_data, AppCommand, AppConfig, CommandContext,
+};
+
+/// A cap or the metadata of the coin, and who owns it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapLocation {
+    /// The struct, eg. `TreasuryCap` or `DenyCapV2`
+    pub name: String,
+    pub id: ObjectID,
+    pub owner: Owner,
+    /// What the registry knows the owner as, eg. `multisig treasury-2-of-3`
+    pub known_as: Option<String>,
+}
+
+/// Where the caps and the metadata of a coin are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapsReport {
+    pub type_tag: TypeTag,
+    pub caps: Vec<CapLocation>,
+    /// Caps the coin was not created with, or wrapped or deleted since
+    pub missing: Vec<String>,
+}
+
+impl CapsReport {
+    pub fn get(&self, name: &str) -> Option<&CapLocation> {
+        self.caps.iter().find(|cap| cap.name == name)
+    }
+}
+
+impl fmt::Display for CapsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Caps of {}", self.type_tag)?;
+        for cap in &self.caps {
+            write!(f, "{:<13} {}  {}", cap.name, cap.id, cap.owner)?;
+            match &cap.known_as {
+                Some(known_as) => writeln!(f, " ({known_as})")?,
+                None => writeln!(f)?,
+            }
+        }
+        for name in &self.missing {
+            writeln!(
+                f,
+                "{name:<13} not found, never created or wrapped or deleted since"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the owners of the `TreasuryCap`, the `DenyCap` or `DenyCapV2`, and the `CoinMetadata`
+/// of `type_tag`, by their ids in the transaction that created the coin. Failing reads of the
+/// chain are errors, not missing caps.
+pub async fn show<B: ChainBackend>(
+    client: &B,
+    type_tag: &TypeTag,
+    registry: &CoinRegistry,
+) -> Result<CapsReport> {
+    let objects = get_coin_objects(client, type_tag).await?;
+    let deny_cap = match objects.deny_cap {
+        Some((id, version)) => (version.struct_name(), Some(id)),
+        None => ("DenyCap", None),
+    };
+    let mut report = CapsReport {
+        type_tag: type_tag.clone(),
+        caps: vec![],
+        missing: vec![],
+    };
+    for (name, id) in [
+        ("TreasuryCap", objects.treasury_cap),
+        deny_cap,
+        ("CoinMetadata", Some(objects.metadata)),
+    ] {
+        let Some(id) = id else {
+            report.missing.push(name.to_string());
+            continue;
+        };
+        let owner = client
+            .get_object_with_options(id, SuiObjectDataOptions::new().with_owner())
+            .await?
+            .data
+            .and_then(|data| data.owner);
+        // Wrapped in another object or deleted since
+        let Some(owner) = owner else {
+            report.missing.push(name.to_string());
+            continue;
+        };
+        let known_as = match owner {
+            Owner::AddressOwner(address) => registry.describe(address),
+            _ => None,
+        };
+        report.caps.push(CapLocation {
+            name: name.to_string(),
+            id,
+            owner,
+            known_as,
+        });
+    }
+    Ok(report)
+}
+
+/// Checks the transfer of the caps to `to` against the policy, runs its pre-flight checks and
+/// dry-runs its transaction.
+pub async fn dry_run<B: ChainBackend>(
+    config: &AppConfig<B>,
+    to: SuiAddress,
+    context: &CommandContext,
+) -> Result<DryRunSummary> {
+    let command = AppCommand::TransferCaps(to);
+    check_policy(&command, context, config)?;
+    let pt = build_command(&command, context, config).await?;
+    let tx_data = transaction_data(&config.client, config.active_address, pt, None).await?;
+    Ok(DryRunSummary::from(config.client.dry_run(tx_data).await?))
+}
+
+/// A transfer of the caps, and where they are after it.
+#[derive(Debug, Clone)]
+pub struct CapsTransfer {
+    pub to: SuiAddress,
+    pub dry_run: DryRunSummary,
+    pub digest: TransactionDigest,
+    pub caps: CapsReport,
+    /// Whether the new owners were recorded in the registry, which only has registered coins
+    pub recorded: bool,
+}
+
+impl fmt::Display for CapsTransfer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Transaction:  {}", self.digest)?;
+        write!(f, "{}", self.caps)
+    }
+}
+
+/// Moves the `TreasuryCap` and the deny-cap of the coin from the active address to `to`, a
+/// multisig or key of the registry, once its dry-run succeeded. The owners read back from the
+/// chain are recorded in the registry saved at `registry_path`, if the coin is registered.
+pub async fn transfer<B: ChainBackend>(
+    config: &AppConfig<B>,
+    to: SuiAddress,
+    context: &CommandContext,
+    registry_path: &Path,
+) -> Result<CapsTransfer> {
+    let dry_run = dry_run(config, to, context).await?;
+    if let Some(error) = &dry_run.error {
+        return Err(anyhow!("Dry-run failed: {error}"));
+    }
+    let resp = execute_command_with(AppCommand::TransferCaps(to), context, config).await?;
+    if let Some(e) = execution_error(&resp) {
+        return Err(anyhow!("Transfer of the caps {} failed: {e}", resp.digest));
+    }
+    let caps = show(&config.client, &config.type_tag, &config.registry).await?;
+    let owner = |name: &str| match caps.get(name).map(|cap| &cap.owner) {
+        Some(Owner::AddressOwner(owner)) => Ok(*owner),
+        _ => Err(anyhow!(
+            "The {name} is not owned by an address after the transfer"
+        )),
+    };
+    let deny_cap = match caps.get("DenyCapV2") {
+        Some(_) => "DenyCapV2",
+        None => "DenyCap",
+    };
+    let mut registry = config.registry.clone();
+    let recorded =
+        registry.set_cap_owners(&config.type_tag, owner("TreasuryCap")?, owner(deny_cap)?);
+    if recorded {
+        registry.save(registry_path)?;
+    }
+    Ok(CapsTransfer {
+        to,
+        dry_run,
+        digest: resp.digest,
+        caps,
+        recorded,
+    })
+}
@@ -0,0 +1,301 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use move_core_types::language_storage::TypeTag;
+use sui_sdk::rpc_types::SuiObjectDataOptions;
+use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+use sui_sdk::types::digests::TransactionDigest;
+use sui_sdk::types::object::Owner;
+
+use crate::backend::ChainBackend;
+use crate::tx_run::{
+    deny_list_status, execute_command_with, execution_error, get_deny_cap, get_deny_list,
+    get_treasury_cap, global_pause_status, AppCommand, AppConfig, CommandContext, DenyCapVersion,
+    EpochStatus,
+};
+
+/// What an emergency freeze will do, worked out from the chain before it is confirmed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmergencyPlan {
+    pub type_tag: TypeTag,
+    pub deny_cap: ObjectID,
+    pub deny_cap_version: DenyCapVersion,
+    /// Whether the deny-cap can pause the coin at all
+    pub pause_allowed: bool,
+    pub already_paused: bool,
+    /// The addresses to deny, each once
+    pub deny: Vec<SuiAddress>,
+    pub already_denied: Vec<SuiAddress>,
+    /// Where the caps go, and the `TreasuryCap` moved with the deny-cap
+    pub cold_address: Option<SuiAddress>,
+    pub treasury_cap: Option<ObjectID>,
+}
+
+impl EmergencyPlan {
+    /// Whether the freeze pauses the coin: its cap allows it and it is not paused yet.
+    pub fn pause(&self) -> bool {
+        self.pause_allowed && !self.already_paused
+    }
+
+    pub fn command(&self) -> AppCommand {
+        AppCommand::EmergencyFreeze {
+            pause: self.pause(),
+            addresses: self.deny.clone(),
+            cold_address: self.cold_address,
+        }
+    }
+
+    /// What the operator types to execute the freeze, naming the coin so that a freeze of the
+    /// wrong coin is caught: `FREEZE <one-time-witness>`.
+    pub fn confirmation_phrase(&self) -> String {
+        let name = match &self.type_tag {
+            TypeTag::Struct(tag) => tag.name.to_string(),
+            other => other.to_string(),
+        };
+        format!("FREEZE {name}")
+    }
+
+    pub fn confirm(&self, typed: &str) -> Result<()> {
+        if typed.trim() != self.confirmation_phrase() {
+            return Err(anyhow!(
+                "Confirmation did not match `{}`, nothing was executed",
+                self.confirmation_phrase()
+            ));
+        }
+        Ok(())
+    }
+
+    fn pause_state(&self) -> String {
+        if self.already_paused {
+            "already enabled".to_string()
+        } else if self.pause_allowed {
+            "ENABLE".to_string()
+        } else {
+            match self.deny_cap_version {
+                DenyCapVersion::V1 => "not supported by the DenyCap of the coin".to_string(),
+                DenyCapVersion::V2 => "not allowed by the DenyCapV2 of the coin".to_string(),
+            }
+        }
+    }
+}
+
+impl fmt::Display for EmergencyPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Emergency freeze of {}", self.type_tag)?;
+        writeln!(f, "Global pause:    {}", self.pause_state())?;
+        writeln!(f, "Deny:            {} addresses", self.deny.len())?;
+        for address in &self.deny {
+            writeln!(f, "  {address}")?;
+        }
+        if !self.already_denied.is_empty() {
+            writeln!(
+                f,
+                "Already denied:  {} addresses",
+                self.already_denied.len()
+            )?;
+            for address in &self.already_denied {
+                writeln!(f, "  {address}")?;
+            }
+        }
+        match self.cold_address {
+            Some(cold_address) => writeln!(
+                f,
+                "Move caps to:    {cold_address} ({} and TreasuryCap)",
+                self.deny_cap_version.struct_name()
+            ),
+            None => writeln!(f, "Move caps to:    - (caps stay with the active address)"),
+        }
+    }
+}
+
+/// Works out the freeze of the coin: a global pause if its cap allows it, the addresses of
+/// `addresses` not denied yet, and moving the caps to `cold_address` if set.
+pub async fn plan<B: ChainBackend>(
+    config: &AppConfig<B>,
+    addresses: &[SuiAddress],
+    cold_address: Option<SuiAddress>,
+) -> Result<EmergencyPlan> {
+    let client = &config.client;
+    let type_tag = &config.type_tag;
+    let deny_list = get_deny_list(client).await?;
+    let deny_cap = get_deny_cap(client, config.active_address, type_tag.clone()).await?;
+    let already_paused = global_pause_status(client, type_tag.clone(), deny_list)
+        .await?
+        .next_epoch;
+
+    let mut seen = BTreeSet::new();
+    let (mut deny, mut already_denied) = (vec![], vec![]);
+    for &address in addresses {
+        if !seen.insert(address) {
+            continue;
+        }
+        let status = deny_list_status(client, type_tag.clone(), deny_list, address).await?;
+        if status.next_epoch {
+            already_denied.push(address);
+        } else {
+            deny.push(address);
+        }
+    }
+    let treasury_cap = match cold_address {
+        Some(_) => Some(
+            get_treasury_cap(client, config.active_address, type_tag.clone())
+                .await?
+                .0,
+        ),
+        None => None,
+    };
+    Ok(EmergencyPlan {
+        type_tag: type_tag.clone(),
+        deny_cap: deny_cap.object_ref.0,
+        deny_cap_version: deny_cap.version,
+        pause_allowed: deny_cap.allow_global_pause,
+        already_paused,
+        deny,
+        already_denied,
+        cold_address,
+        treasury_cap,
+    })
+}
+
+/// A cap of the coin and who owns it after the freeze.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapOwner {
+    pub name: String,
+    pub id: ObjectID,
+    pub owner: Owner,
+}
+
+/// What an executed freeze did, read back from the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmergencyReport {
+    pub plan: EmergencyPlan,
+    pub digest: TransactionDigest,
+    pub checkpoint: Option<u64>,
+    pub timestamp_ms: Option<u64>,
+    pub context: CommandContext,
+    pub paused: EpochStatus,
+    pub denied: Vec<(SuiAddress, EpochStatus)>,
+    pub caps: Vec<CapOwner>,
+}
+
+impl EmergencyReport {
+    /// Whether the chain shows everything the plan asked for.
+    pub fn complete(&self) -> bool {
+        let paused = !self.plan.pause() || self.paused.next_epoch;
+        let denied = self.denied.iter().all(|(_, status)| status.next_epoch);
+        let moved = match self.plan.cold_address {
+            Some(cold_address) => self
+                .caps
+                .iter()
+                .all(|cap| cap.owner == Owner::AddressOwner(cold_address)),
+            None => true,
+        };
+        paused && denied && moved
+    }
+}
+
+impl fmt::Display for EmergencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Emergency freeze of {}", self.plan.type_tag)?;
+        writeln!(f, "Transaction:     {}", self.digest)?;
+        if let Some(checkpoint) = self.checkpoint {
+            writeln!(f, "Checkpoint:      {checkpoint}")?;
+        }
+        if let Some(ticket) = &self.context.ticket {
+            writeln!(f, "Ticket:          {ticket}")?;
+        }
+        writeln!(f, "Global pause:    {}", epochs(self.paused, "enabled"))?;
+        if !self.plan.pause() {
+            writeln!(f, "  not changed: {}", self.plan.pause_state())?;
+        }
+        writeln!(f, "Denied:          {} addresses", self.denied.len())?;
+        for (address, status) in &self.denied {
+            writeln!(f, "  {address}  {}", epochs(*status, "denied"))?;
+        }
+        if !self.plan.already_denied.is_empty() {
+            writeln!(
+                f,
+                "Already denied:  {} addresses",
+                self.plan.already_denied.len()
+            )?;
+        }
+        for cap in &self.caps {
+            writeln!(f, "{:<16} {}  owned by {}", cap.name, cap.id, cap.owner)?;
+        }
+        if self.complete() {
+            writeln!(f, "Freeze complete")
+        } else {
+            writeln!(
+                f,
+                "WARNING: the chain does not show every change of the freeze"
+            )
+        }
+    }
+}
+
+fn epochs(status: EpochStatus, state: &str) -> String {
+    match (status.current_epoch, status.next_epoch) {
+        (true, true) => state.to_uppercase(),
+        (false, true) => format!(
+            "{} at once, for receiving from the next epoch",
+            state.to_uppercase()
+        ),
+        (true, false) => "lifted from the next epoch".to_string(),
+        (false, false) => format!("not {state}"),
+    }
+}
+
+/// Executes `plan` in one transaction with `context`, through the policy, the pre-flight checks
+/// and the audit log, and reads back the pause, the deny-list and the owners of the caps.
+pub async fn freeze<B: ChainBackend>(
+    config: &AppConfig<B>,
+    plan: EmergencyPlan,
+    context: &CommandContext,
+) -> Result<EmergencyReport> {
+    let resp = execute_command_with(plan.command(), context, config).await?;
+    if let Some(e) = execution_error(&resp) {
+        return Err(anyhow!("Emergency freeze {} failed: {e}", resp.digest));
+    }
+
+    let client = &config.client;
+    let type_tag = &config.type_tag;
+    let deny_list = get_deny_list(client).await?;
+    let paused = global_pause_status(client, type_tag.clone(), deny_list).await?;
+    let mut denied = vec![];
+    for &address in &plan.deny {
+        let status = deny_list_status(client, type_tag.clone(), deny_list, address).await?;
+        denied.push((address, status));
+    }
+    let mut caps = vec![];
+    let cap_ids = [
+        (plan.deny_cap_version.struct_name(), Some(plan.deny_cap)),
+        ("TreasuryCap", plan.treasury_cap),
+    ];
+    for (name, id) in cap_ids {
+        let Some(id) = id else {
+            continue;
+        };
+        let owner = client
+            .get_object_with_options(id, SuiObjectDataOptions::new().with_owner())
+            .await?
+            .data
+            .and_then(|data| data.owner)
+            .ok_or(anyhow!("Cannot read the owner of {name} {id}"))?;
+        caps.push(CapOwner {
+            name: name.to_string(),
+            id,
+            owner,
+        });
+    }
+    Ok(EmergencyReport {
+        plan,
+        digest: resp.digest,
+        checkpoint: resp.checkpoint,
+        timestamp_ms: resp.timestamp_ms,
+        context: context.clone(),
+        paused,
+        denied,
+        caps,
+    })
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use move_core_types::language_storage::TypeTag;
+use serde::{Deserialize, Serialize};
+use sui_sdk::rpc_types::{SuiObjectDataFilter, SuiObjectResponseQuery};
+use sui_sdk::types::base_types::{ObjectDigest, ObjectID, SequenceNumber, SuiAddress};
+use sui_sdk::types::coin::Coin;
+use sui_sdk::types::digests::TransactionDigest;
+
+use crate::backend::ChainBackend;
+use crate::history::format_time;
+use crate::tx_run::{
+    deny_list_status, execute_command_with, execution_error, get_deny_list, inspect_coin,
+    AppCommand, AppConfig, CommandContext,
+};
+
+/// A `Coin<T>` of the enforced address, as it was when read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrozenCoin {
+    pub id: ObjectID,
+    pub version: SequenceNumber,
+    pub digest: ObjectDigest,
+    pub value: u64,
+    /// The last transaction that changed the coin
+    pub previous_transaction: TransactionDigest,
+}
+
+/// What the address held when it was denied, for the records of a freeze.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvidenceReport {
+    pub address: SuiAddress,
+    pub coin_type: String,
+    /// The transaction adding the address to the deny-list
+    pub deny_digest: TransactionDigest,
+    /// The checkpoint and time of the deny transaction, when the fullnode reports them
+    pub checkpoint: Option<u64>,
+    pub timestamp_ms: Option<u64>,
+    /// When the coins were read, right after the deny transaction executed
+    pub snapshot_ms: u64,
+    /// The epoch the coins were read in
+    pub current_epoch: u64,
+    /// The first epoch the deny applies in. Denying an address for a coin of
+    /// `create_regulated_currency_v2` only applies from the next epoch, until when the address
+    /// can still spend the coins of the report.
+    pub effective_epoch: u64,
+    pub context: CommandContext,
+    /// The coins of the address after the freeze
+    pub coins: Vec<FrozenCoin>,
+    pub balance: u64,
+    /// The balance read just before the deny transaction was sent
+    pub balance_before: u64,
+    /// Coins that changed between the two reads, ie. moved while the deny was executing
+    pub changed: Vec<ObjectID>,
+}
+
+impl EvidenceReport {
+    /// Whether the deny does not apply yet, so that the balance may still change.
+    pub fn pending(&self) -> bool {
+        self.effective_epoch > self.current_epoch
+    }
+
+    /// Writes the report as pretty-printed JSON.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .map_err(|e| anyhow!("Cannot write evidence {}: {e}", path.display()))
+    }
+}
+
+impl fmt::Display for EvidenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Address:      {}", self.address)?;
+        writeln!(f, "Coin type:    {}", self.coin_type)?;
+        writeln!(f, "Denied in:    {}", self.deny_digest)?;
+        if let Some(checkpoint) = self.checkpoint {
+            writeln!(f, "Checkpoint:   {checkpoint}")?;
+        }
+        if let Some(ms) = self.timestamp_ms {
+            writeln!(f, "Executed at:  {}", format_time(ms))?;
+        }
+        writeln!(f, "Snapshot at:  {}", format_time(self.snapshot_ms))?;
+        writeln!(f, "Epoch:        {}", self.current_epoch)?;
+        writeln!(f, "Effective in: epoch {}", self.effective_epoch)?;
+        for coin in &self.coins {
+            writeln!(
+                f,
+                "  {} v{} {}  {}  last changed in {}",
+                coin.id, coin.version, coin.digest, coin.value, coin.previous_transaction
+            )?;
+        }
+        write!(
+            f,
+            "Frozen balance: {} in {} coins",
+            self.balance,
+            self.coins.len()
+        )?;
+        if self.pending() {
+            write!(f, ", pending until epoch {}", self.effective_epoch)?;
+        }
+        writeln!(f)?;
+        if !self.changed.is_empty() {
+            writeln!(
+                f,
+                "WARNING: {} coins changed while the deny executed, the balance before was {}",
+                self.changed.len(),
+                self.balance_before
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The `Coin<type_tag>` objects owned by `owner`, by id.
+pub async fn snapshot<B: ChainBackend>(
+    client: &B,
+    owner: SuiAddress,
+    type_tag: &TypeTag,
+) -> Result<BTreeMap<ObjectID, FrozenCoin>> {
+    let query = SuiObjectResponseQuery {
+        filter: Some(SuiObjectDataFilter::StructType(Coin::type_(
+            type_tag.clone(),
+        ))),
+        options: None,
+    };
+    let mut coins = BTreeMap::new();
+    let mut cursor = None;
+    loop {
+        let page = client
+            .get_owned_objects(owner, Some(query.clone()), cursor, None)
+            .await?;
+        for object in page.data {
+            let Some(data) = object.data else {
+                continue;
+            };
+            let coin = inspect_coin(client, data.object_id).await?;
+            let (id, version, digest) = coin.object_ref;
+            coins.insert(
+                id,
+                FrozenCoin {
+                    id,
+                    version,
+                    digest,
+                    value: coin.value,
+                    previous_transaction: coin.previous_transaction,
+                },
+            );
+        }
+        if !page.has_next_page {
+            break;
+        }
+        cursor = page.next_cursor;
+    }
+    Ok(coins)
+}
+
+/// Adds `address` to the deny-list of the coin and documents the coins it held when it was
+/// denied. Its balance is read before and after the deny transaction, so that coins moved in
+/// between are reported rather than missed. When the deny only applies from the next epoch, the
+/// report is marked pending until then.
+///
+/// Denying only freezes the coins: they stay owned by the address, and neither the
+/// `TreasuryCap` nor any package can burn them without its signature.
+pub async fn enforce<B: ChainBackend>(
+    config: &AppConfig<B>,
+    address: SuiAddress,
+    context: &CommandContext,
+) -> Result<EvidenceReport> {
+    let deny_list = get_deny_list(&config.client).await?;
+    let status = deny_list_status(&config.client, config.type_tag.clone(), deny_list, address);
+    if status.await?.next_epoch {
+        return Err(anyhow!(
+            "{address} is already denied for {}, its coins were frozen by an earlier transaction",
+            config.type_tag
+        ));
+    }
+    let before = snapshot(&config.client, address, &config.type_tag).await?;
+    let resp = execute_command_with(AppCommand::DenyListAdd(address), context, config).await?;
+    if let Some(e) = execution_error(&resp) {
+        return Err(anyhow!("Deny transaction {} failed: {e}", resp.digest));
+    }
+    let after = snapshot(&config.client, address, &config.type_tag).await?;
+    let snapshot_ms = Utc::now().timestamp_millis() as u64;
+    let current_epoch = config.client.get_epoch().await?;
+    let status = deny_list_status(&config.client, config.type_tag.clone(), deny_list, address);
+    let effective_epoch = match status.await?.current_epoch {
+        true => current_epoch,
+        false => current_epoch + 1,
+    };
+
+    let mut changed: Vec<ObjectID> = before
+        .iter()
+        .filter(|(id, coin)| after.get(id) != Some(coin))
+        .map(|(id, _)| *id)
+        .collect();
+    changed.extend(after.keys().filter(|id| !before.contains_key(id)));
+    Ok(EvidenceReport {
+        address,
+        coin_type: config.type_tag.to_string(),
+        deny_digest: resp.digest,
+        checkpoint: resp.checkpoint,
+        timestamp_ms: resp.timestamp_ms,
+        snapshot_ms,
+        current_epoch,
+        effective_epoch,
+        context: context.clone(),
+        balance: after.values().map(|coin| coin.value).sum(),
+        balance_before: before.values().map(|coin| coin.value).sum(),
+        coins: after.into_values().collect(),
+        changed,
+    })
+}
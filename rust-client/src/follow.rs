@@ -0,0 +1,60 @@
+use anyhow::{anyhow, Result};
+use sui_sdk::rpc_types::{SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions};
+
+use crate::backend::ChainBackend;
+
+const CHECKPOINT_PAGE_SIZE: usize = 50;
+/// Maximum number of transactions per `multi_get_transaction_blocks` call
+const TX_BATCH_SIZE: usize = 50;
+
+/// The transactions of a checkpoint, with their input, effects, balance and object changes.
+#[derive(Debug, Clone)]
+pub struct CheckpointTransactions {
+    pub sequence_number: u64,
+    pub timestamp_ms: u64,
+    pub transactions: Vec<SuiTransactionBlockResponse>,
+}
+
+/// The next page of checkpoints after `cursor`, oldest first, and whether more are available.
+pub async fn checkpoints_after<B: ChainBackend>(
+    client: &B,
+    cursor: Option<u64>,
+) -> Result<(Vec<CheckpointTransactions>, bool)> {
+    let page = client
+        .get_checkpoints(cursor, Some(CHECKPOINT_PAGE_SIZE), false)
+        .await?;
+    if page.has_next_page && page.data.is_empty() {
+        return Err(anyhow!("Checkpoint page is empty but has a next page"));
+    }
+    let options = SuiTransactionBlockResponseOptions::new()
+        .with_input()
+        .with_effects()
+        .with_balance_changes()
+        .with_object_changes();
+    let mut checkpoints = vec![];
+    for checkpoint in page.data {
+        let mut transactions = vec![];
+        for digests in checkpoint.transactions.chunks(TX_BATCH_SIZE) {
+            transactions.extend(
+                client
+                    .multi_get_transaction_blocks(digests.to_vec(), options.clone())
+                    .await?,
+            );
+        }
+        checkpoints.push(CheckpointTransactions {
+            sequence_number: checkpoint.sequence_number,
+            timestamp_ms: checkpoint.timestamp_ms,
+            transactions,
+        });
+    }
+    Ok((checkpoints, page.has_next_page))
+}
+
+/// The sequence number of the latest checkpoint, to start following from.
+pub async fn latest_checkpoint<B: ChainBackend>(client: &B) -> Result<Option<u64>> {
+    let page = client.get_checkpoints(None, Some(1), true).await?;
+    Ok(page
+        .data
+        .first()
+        .map(|checkpoint| checkpoint.sequence_number))
+}
@@ -6,7 +6,8 @@ use sui_sdk::rpc_types::{
 };
 use sui_sdk::types::base_types::{ObjectID, ObjectRef, SuiAddress};
 use sui_sdk::types::gas_coin::GasCoin;
-use sui_sdk::SuiClient;
+
+use crate::backend::ChainBackend;
 
 pub const DEFAULT_GAS_BUDGET: u64 = 10_000_000;
 
@@ -16,8 +17,8 @@ pub struct GasRet {
     pub price: u64,
 }
 
-pub async fn select_gas(
-    client: &SuiClient,
+pub async fn select_gas<B: ChainBackend>(
+    client: &B,
     signer_addr: SuiAddress,
     input_gas: Option<ObjectID>,
     budget: Option<u64>,
@@ -28,7 +29,7 @@ pub async fn select_gas(
         Some(p) => p,
         None => {
             debug!("No gas price given, fetching from fullnode");
-            client.read_api().get_reference_gas_price().await?
+            client.get_reference_gas_price().await?
         }
     };
     let budget = budget.unwrap_or_else(|| {
@@ -44,8 +45,7 @@ pub async fn select_gas(
     }
 
     if let Some(gas) = input_gas {
-        let read_api = client.read_api();
-        let object = read_api
+        let object = client
             .get_object_with_options(gas, SuiObjectDataOptions::new())
             .await?
             .object_ref_if_exists()
@@ -57,8 +57,7 @@ pub async fn select_gas(
         });
     }
 
-    let read_api = client.read_api();
-    let gas_objs = read_api
+    let gas_objs = client
         .get_owned_objects(
             signer_addr,
             Some(SuiObjectResponseQuery {
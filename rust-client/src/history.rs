@@ -0,0 +1,355 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use anyhow::Result;
+use chrono::{TimeZone, Utc};
+use move_core_types::language_storage::TypeTag;
+use serde::{Deserialize, Serialize};
+use sui_sdk::rpc_types::{
+    EventFilter, SuiArgument, SuiCallArg, SuiCommand, SuiEvent, SuiProgrammableMoveCall,
+    SuiProgrammableTransactionBlock, SuiTransactionBlockDataAPI, SuiTransactionBlockEffectsAPI,
+    SuiTransactionBlockKind, SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+    SuiTransactionBlockResponseQuery, TransactionFilter,
+};
+use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+use sui_sdk::types::coin::COIN_MODULE_NAME;
+use sui_sdk::types::digests::TransactionDigest;
+use sui_sdk::types::object::Owner;
+use sui_sdk::types::{parse_sui_type_tag, SUI_FRAMEWORK_PACKAGE_ID};
+
+use crate::backend::ChainBackend;
+use crate::publish::PackageIds;
+use crate::tx_run::get_coin_objects;
+
+/// The `0x2::coin` functions that change the supply or the deny-list of a coin
+pub const ADMIN_FUNCTIONS: [&str; 8] = [
+    "mint",
+    "burn",
+    "deny_list_add",
+    "deny_list_remove",
+    "deny_list_v2_add",
+    "deny_list_v2_remove",
+    "deny_list_v2_enable_global_pause",
+    "deny_list_v2_disable_global_pause",
+];
+const PAGE_SIZE: usize = 50;
+
+/// An administrative action on the coin, or an event emitted by its package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LedgerAction {
+    /// `recipient` is `None` if the minted coin was not transferred in the same transaction
+    Mint {
+        amount: u64,
+        recipient: Option<SuiAddress>,
+    },
+    Burn {
+        amount: u64,
+    },
+    DenyListAdd {
+        address: SuiAddress,
+    },
+    DenyListRemove {
+        address: SuiAddress,
+    },
+    /// The coin is denied to every address
+    Pause,
+    Unpause,
+    Event {
+        event_type: String,
+        fields: serde_json::Value,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub timestamp_ms: Option<u64>,
+    pub checkpoint: Option<u64>,
+    pub digest: TransactionDigest,
+    /// The sender of the transaction
+    pub actor: SuiAddress,
+    pub action: LedgerAction,
+}
+
+impl fmt::Display for LedgerAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LedgerAction::Mint {
+                amount,
+                recipient: Some(recipient),
+            } => write!(f, "MINT {amount} to {recipient}"),
+            LedgerAction::Mint {
+                amount,
+                recipient: None,
+            } => write!(f, "MINT {amount}"),
+            LedgerAction::Burn { amount } => write!(f, "BURN {amount}"),
+            LedgerAction::DenyListAdd { address } => write!(f, "DENY {address}"),
+            LedgerAction::DenyListRemove { address } => write!(f, "UNDENY {address}"),
+            LedgerAction::Pause => write!(f, "PAUSE"),
+            LedgerAction::Unpause => write!(f, "UNPAUSE"),
+            LedgerAction::Event { event_type, fields } => write!(f, "EVENT {event_type} {fields}"),
+        }
+    }
+}
+
+/// `ms` since the epoch in RFC 3339, or as is if out of range.
+pub fn format_time(ms: u64) -> String {
+    Utc.timestamp_millis_opt(ms as i64)
+        .single()
+        .map_or_else(|| ms.to_string(), |time| time.to_rfc3339())
+}
+
+impl fmt::Display for LedgerEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let time = self
+            .timestamp_ms
+            .map_or_else(|| "-".to_string(), format_time);
+        write!(
+            f,
+            "{time}  {}  {}  {}",
+            self.digest, self.actor, self.action
+        )
+    }
+}
+
+/// The full history of administrative actions on `type_tag`, oldest first: mints, burns and
+/// deny-list changes, and the events emitted by the coin's package.
+pub async fn history<B: ChainBackend>(
+    client: &B,
+    type_tag: &TypeTag,
+    packages: &PackageIds,
+) -> Result<Vec<LedgerEntry>> {
+    let mut entries = vec![];
+    for resp in coin_transactions(client, type_tag).await? {
+        entries.extend(ledger_entries(&resp, type_tag));
+    }
+    entries.extend(package_events(client, packages).await?);
+    // Stable, so entries of the same checkpoint stay in order
+    entries.sort_by_key(|entry| entry.timestamp_ms);
+    Ok(entries)
+}
+
+/// Every transaction taking the `TreasuryCap` or the deny-cap of `type_tag` as input, which
+/// every mint, burn and deny-list change does, oldest first.
+pub async fn coin_transactions<B: ChainBackend>(
+    client: &B,
+    type_tag: &TypeTag,
+) -> Result<Vec<SuiTransactionBlockResponse>> {
+    let objects = get_coin_objects(client, type_tag).await?;
+    let caps = objects
+        .treasury_cap
+        .into_iter()
+        .chain(objects.deny_cap.map(|(id, _)| id));
+    let mut transactions = vec![];
+    let mut seen = BTreeSet::new();
+    for cap in caps {
+        for resp in cap_transactions(client, cap).await? {
+            // A transaction may take both caps
+            if seen.insert(resp.digest) {
+                transactions.push(resp);
+            }
+        }
+    }
+    // Each query is in execution order, merge them by checkpoint
+    transactions.sort_by_key(|resp| resp.checkpoint);
+    Ok(transactions)
+}
+
+/// Every transaction taking `cap` as input, oldest first.
+async fn cap_transactions<B: ChainBackend>(
+    client: &B,
+    cap: ObjectID,
+) -> Result<Vec<SuiTransactionBlockResponse>> {
+    let query = SuiTransactionBlockResponseQuery::new(
+        Some(TransactionFilter::InputObject(cap)),
+        Some(
+            SuiTransactionBlockResponseOptions::new()
+                .with_input()
+                .with_effects()
+                .with_balance_changes(),
+        ),
+    );
+    let mut transactions = vec![];
+    let mut cursor = None;
+    loop {
+        let page = client
+            .query_transaction_blocks(query.clone(), cursor, Some(PAGE_SIZE), false)
+            .await?;
+        transactions.extend(page.data);
+        if !page.has_next_page {
+            break;
+        }
+        cursor = page.next_cursor;
+    }
+    Ok(transactions)
+}
+
+/// Events emitted by the original and the latest version of the coin's package.
+pub async fn package_events<B: ChainBackend>(
+    client: &B,
+    packages: &PackageIds,
+) -> Result<Vec<LedgerEntry>> {
+    let mut package_ids = vec![packages.original];
+    if packages.is_upgraded() {
+        package_ids.push(packages.latest);
+    }
+    let mut entries = vec![];
+    for package_id in package_ids {
+        let mut cursor = None;
+        loop {
+            let page = client
+                .query_events(
+                    EventFilter::Package(package_id),
+                    cursor,
+                    Some(PAGE_SIZE),
+                    false,
+                )
+                .await?;
+            entries.extend(page.data.into_iter().map(event_entry));
+            if !page.has_next_page {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+    }
+    Ok(entries)
+}
+
+fn event_entry(event: SuiEvent) -> LedgerEntry {
+    LedgerEntry {
+        timestamp_ms: event.timestamp_ms,
+        checkpoint: None,
+        digest: event.id.tx_digest,
+        actor: event.sender,
+        action: LedgerAction::Event {
+            event_type: event.type_.to_string(),
+            fields: event.parsed_json,
+        },
+    }
+}
+
+/// The administrative actions on `type_tag` in a transaction, read from its calls to
+/// `0x2::coin`. Failed transactions changed nothing and yield no entries.
+/// `resp` needs the input, effects and balance changes.
+pub fn ledger_entries(resp: &SuiTransactionBlockResponse, type_tag: &TypeTag) -> Vec<LedgerEntry> {
+    let Some(tx) = &resp.transaction else {
+        return vec![];
+    };
+    if let Some(effects) = &resp.effects {
+        if !effects.status().is_ok() {
+            return vec![];
+        }
+    }
+    let SuiTransactionBlockKind::ProgrammableTransaction(ptb) = tx.data.transaction() else {
+        return vec![];
+    };
+    let actor = *tx.data.sender();
+
+    let mut actions = vec![];
+    for (i, command) in ptb.commands.iter().enumerate() {
+        let SuiCommand::MoveCall(call) = command else {
+            continue;
+        };
+        if !is_admin_call(call, type_tag) {
+            continue;
+        }
+        let action = match (call.function.as_str(), call.arguments.as_slice()) {
+            ("mint", [_, amount]) => pure_u64(ptb, amount).map(|amount| LedgerAction::Mint {
+                amount,
+                recipient: transfer_recipient(ptb, i),
+            }),
+            ("burn", _) => {
+                burnt_amount(resp, type_tag, actor).map(|amount| LedgerAction::Burn { amount })
+            }
+            ("deny_list_add" | "deny_list_v2_add", [_, _, address]) => {
+                pure_address(ptb, address).map(|address| LedgerAction::DenyListAdd { address })
+            }
+            ("deny_list_remove" | "deny_list_v2_remove", [_, _, address]) => {
+                pure_address(ptb, address).map(|address| LedgerAction::DenyListRemove { address })
+            }
+            ("deny_list_v2_enable_global_pause", _) => Some(LedgerAction::Pause),
+            ("deny_list_v2_disable_global_pause", _) => Some(LedgerAction::Unpause),
+            _ => None,
+        };
+        actions.extend(action);
+    }
+
+    actions
+        .into_iter()
+        .map(|action| LedgerEntry {
+            timestamp_ms: resp.timestamp_ms,
+            checkpoint: resp.checkpoint,
+            digest: resp.digest,
+            actor,
+            action,
+        })
+        .collect()
+}
+
+fn is_admin_call(call: &SuiProgrammableMoveCall, type_tag: &TypeTag) -> bool {
+    call.package == SUI_FRAMEWORK_PACKAGE_ID
+        && call.module == COIN_MODULE_NAME.as_str()
+        && ADMIN_FUNCTIONS.contains(&call.function.as_str())
+        && call
+            .type_arguments
+            .first()
+            .and_then(|t| parse_sui_type_tag(t).ok())
+            .is_some_and(|t| &t == type_tag)
+}
+
+fn pure_input(
+    ptb: &SuiProgrammableTransactionBlock,
+    arg: &SuiArgument,
+) -> Option<serde_json::Value> {
+    let SuiArgument::Input(i) = arg else {
+        return None;
+    };
+    match ptb.inputs.get(*i as usize)? {
+        SuiCallArg::Pure(value) => Some(value.value().to_json_value()),
+        SuiCallArg::Object(_) => None,
+    }
+}
+
+fn pure_u64(ptb: &SuiProgrammableTransactionBlock, arg: &SuiArgument) -> Option<u64> {
+    match pure_input(ptb, arg)? {
+        serde_json::Value::String(s) => s.parse().ok(),
+        serde_json::Value::Number(n) => n.as_u64(),
+        _ => None,
+    }
+}
+
+fn pure_address(ptb: &SuiProgrammableTransactionBlock, arg: &SuiArgument) -> Option<SuiAddress> {
+    match pure_input(ptb, arg)? {
+        serde_json::Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// The recipient of the result of command `i`, if a later command transfers it.
+fn transfer_recipient(ptb: &SuiProgrammableTransactionBlock, i: usize) -> Option<SuiAddress> {
+    ptb.commands.iter().find_map(|command| match command {
+        SuiCommand::TransferObjects(objects, recipient)
+            if objects.iter().any(|object| match object {
+                SuiArgument::Result(r) | SuiArgument::NestedResult(r, 0) => *r as usize == i,
+                _ => false,
+            }) =>
+        {
+            pure_address(ptb, recipient)
+        }
+        _ => None,
+    })
+}
+
+/// Burnt coins are not arguments of the call, so read the amount from the balance changes.
+fn burnt_amount(
+    resp: &SuiTransactionBlockResponse,
+    type_tag: &TypeTag,
+    actor: SuiAddress,
+) -> Option<u64> {
+    resp.balance_changes.as_ref()?.iter().find_map(|change| {
+        (change.coin_type == *type_tag
+            && change.owner == Owner::AddressOwner(actor)
+            && change.amount < 0)
+            .then(|| change.amount.unsigned_abs() as u64)
+    })
+}
@@ -0,0 +1,148 @@
+mod db;
+
+pub use db::IndexerDb;
+
+use anyhow::{anyhow, Result};
+use move_core_types::language_storage::TypeTag;
+use serde::{Deserialize, Serialize};
+use sui_sdk::rpc_types::SuiTransactionBlockResponse;
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::digests::TransactionDigest;
+use tracing::{debug, info};
+
+use crate::backend::ChainBackend;
+use crate::follow::checkpoints_after;
+use crate::history::{ledger_entries, LedgerAction};
+use crate::tx_run::get_coin_objects;
+
+pub const DEFAULT_INDEXER_DB: &str = "indexer.db";
+
+/// A transaction touching the indexed coin, as stored in the indexer database.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedTx {
+    pub digest: TransactionDigest,
+    pub checkpoint: u64,
+    pub timestamp_ms: Option<u64>,
+    pub sender: SuiAddress,
+    /// Mints, burns and deny-list changes. Empty for plain transfers.
+    pub actions: Vec<LedgerAction>,
+    /// Net change of the coin's balance, per address
+    pub balance_changes: Vec<BalanceDelta>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceDelta {
+    pub owner: SuiAddress,
+    pub amount: i128,
+}
+
+/// Extracts what the indexer stores about `type_tag` from a transaction of `checkpoint`.
+/// Returns `None` if the transaction does not touch the coin.
+/// `resp` needs the input, effects and balance changes.
+pub fn index_tx(
+    resp: &SuiTransactionBlockResponse,
+    type_tag: &TypeTag,
+    checkpoint: u64,
+) -> Option<IndexedTx> {
+    let actions: Vec<LedgerAction> = ledger_entries(resp, type_tag)
+        .into_iter()
+        .map(|entry| entry.action)
+        .collect();
+    let balance_changes: Vec<BalanceDelta> = resp
+        .balance_changes
+        .iter()
+        .flatten()
+        .filter(|change| &change.coin_type == type_tag)
+        .filter_map(|change| {
+            Some(BalanceDelta {
+                owner: change.owner.get_owner_address().ok()?,
+                amount: change.amount,
+            })
+        })
+        .collect();
+    if actions.is_empty() && balance_changes.is_empty() {
+        return None;
+    }
+    Some(IndexedTx {
+        digest: resp.digest,
+        checkpoint,
+        timestamp_ms: resp.timestamp_ms,
+        sender: *resp.transaction.as_ref()?.data.sender(),
+        actions,
+        balance_changes,
+    })
+}
+
+/// Indexes every checkpoint after the database cursor, or on a new database from `start`,
+/// defaulting to the checkpoint the coin was published in.
+/// Each checkpoint is stored in one database transaction, so an interrupted sync resumes from
+/// the last complete checkpoint. Returns the number of indexed transactions.
+///
+/// The supply and balances are summed from the indexed transactions, so a new database must
+/// start at or before the checkpoint the coin was published in.
+pub async fn sync<B: ChainBackend>(
+    client: &B,
+    db: &mut IndexerDb,
+    type_tag: &TypeTag,
+    start: Option<u64>,
+) -> Result<usize> {
+    let mut cursor = match (db.cursor()?, start) {
+        (Some(cursor), _) => Some(cursor),
+        (None, start) => {
+            let published = publish_checkpoint(client, type_tag).await?;
+            let start = match start {
+                Some(start) => check_start(type_tag, start, published)?,
+                None => published,
+            };
+            // `get_checkpoints` returns the checkpoints after the cursor
+            start.checked_sub(1)
+        }
+    };
+    let mut indexed = 0;
+    loop {
+        let (checkpoints, has_next_page) = checkpoints_after(client, cursor).await?;
+        for checkpoint in checkpoints {
+            let txs: Vec<IndexedTx> = checkpoint
+                .transactions
+                .iter()
+                .filter_map(|resp| index_tx(resp, type_tag, checkpoint.sequence_number))
+                .collect();
+            debug!(
+                "Checkpoint {}: {} transactions of {type_tag}",
+                checkpoint.sequence_number,
+                txs.len()
+            );
+            indexed += txs.len();
+            db.apply_checkpoint(checkpoint.sequence_number, &txs)?;
+            cursor = Some(checkpoint.sequence_number);
+        }
+        if !has_next_page {
+            break;
+        }
+    }
+    info!("INDEXED {indexed} TRANSACTIONS UP TO CHECKPOINT {cursor:?}");
+    Ok(indexed)
+}
+
+/// The checkpoint of the transaction that published `type_tag`.
+async fn publish_checkpoint<B: ChainBackend>(client: &B, type_tag: &TypeTag) -> Result<u64> {
+    get_coin_objects(client, type_tag)
+        .await?
+        .checkpoint
+        .ok_or(anyhow!(
+            "The publish transaction of {type_tag} is not checkpointed yet"
+        ))
+}
+
+/// Refuses to start a new database after the coin was published, which would miss the mints
+/// and transfers before `start`. Returns `start`.
+fn check_start(type_tag: &TypeTag, start: u64, published: u64) -> Result<u64> {
+    if start > published {
+        return Err(anyhow!(
+            "Cannot start indexing at checkpoint {start}: {type_tag} was published in checkpoint \
+             {published}, and the supply and balances before {start} would be missing. Start at \
+             or before {published}"
+        ));
+    }
+    Ok(start)
+}
@@ -0,0 +1,279 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use move_core_types::language_storage::TypeTag;
+use rusqlite::{params, Connection, OptionalExtension};
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::digests::TransactionDigest;
+
+use super::IndexedTx;
+use crate::history::{LedgerAction, LedgerEntry};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS state (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS transactions (
+    digest TEXT PRIMARY KEY,
+    checkpoint INTEGER NOT NULL,
+    timestamp_ms INTEGER,
+    sender TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS actions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    digest TEXT NOT NULL REFERENCES transactions(digest),
+    kind TEXT NOT NULL,
+    action TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS balance_changes (
+    digest TEXT NOT NULL REFERENCES transactions(digest),
+    owner TEXT NOT NULL,
+    amount TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS balances (
+    owner TEXT PRIMARY KEY,
+    balance TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS denied (
+    address TEXT PRIMARY KEY
+);
+";
+
+/// SQLite mirror of one coin: its transactions, balances, supply and deny-list.
+/// Amounts are stored as text, as they do not fit SQLite integers.
+pub struct IndexerDb {
+    conn: Connection,
+}
+
+impl IndexerDb {
+    /// Opens or creates the database of `type_tag` at `path`. Fails if the database indexes
+    /// another coin.
+    pub fn open(path: &Path, type_tag: &TypeTag) -> Result<Self> {
+        Self::init(Connection::open(path)?, type_tag)
+    }
+
+    pub fn open_in_memory(type_tag: &TypeTag) -> Result<Self> {
+        Self::init(Connection::open_in_memory()?, type_tag)
+    }
+
+    fn init(conn: Connection, type_tag: &TypeTag) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        let db = IndexerDb { conn };
+        match db.state("coin_type")? {
+            Some(coin_type) if coin_type != type_tag.to_string() => {
+                return Err(anyhow!("Database indexes {coin_type}, not {type_tag}"));
+            }
+            Some(_) => {}
+            None => {
+                db.conn.execute(
+                    "INSERT INTO state (key, value) VALUES ('coin_type', ?1)",
+                    params![type_tag.to_string()],
+                )?;
+            }
+        }
+        Ok(db)
+    }
+
+    fn state(&self, key: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT value FROM state WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// The last indexed checkpoint.
+    pub fn cursor(&self) -> Result<Option<u64>> {
+        self.state("checkpoint")?
+            .map(|checkpoint| Ok(checkpoint.parse()?))
+            .transpose()
+    }
+
+    /// Stores the transactions of `checkpoint` and moves the cursor past it, atomically.
+    pub fn apply_checkpoint(&mut self, checkpoint: u64, txs: &[IndexedTx]) -> Result<()> {
+        if let Some(cursor) = self.cursor()? {
+            if checkpoint <= cursor {
+                return Err(anyhow!(
+                    "Checkpoint {checkpoint} is already indexed, cursor is at {cursor}"
+                ));
+            }
+        }
+        let db_tx = self.conn.transaction()?;
+        let mut supply = current_supply(&db_tx)?;
+        for tx in txs {
+            db_tx.execute(
+                "INSERT INTO transactions (digest, checkpoint, timestamp_ms, sender)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    tx.digest.to_string(),
+                    checkpoint,
+                    tx.timestamp_ms,
+                    tx.sender.to_string()
+                ],
+            )?;
+            for action in &tx.actions {
+                let kind = match action {
+                    LedgerAction::Mint { amount, .. } => {
+                        supply = supply
+                            .checked_add(*amount)
+                            .ok_or(anyhow!("Supply overflow in {}", tx.digest))?;
+                        "mint"
+                    }
+                    LedgerAction::Burn { amount } => {
+                        supply = supply
+                            .checked_sub(*amount)
+                            .ok_or(anyhow!("Supply underflow in {}", tx.digest))?;
+                        "burn"
+                    }
+                    LedgerAction::DenyListAdd { address } => {
+                        db_tx.execute(
+                            "INSERT OR IGNORE INTO denied (address) VALUES (?1)",
+                            params![address.to_string()],
+                        )?;
+                        "deny_list_add"
+                    }
+                    LedgerAction::DenyListRemove { address } => {
+                        db_tx.execute(
+                            "DELETE FROM denied WHERE address = ?1",
+                            params![address.to_string()],
+                        )?;
+                        "deny_list_remove"
+                    }
+                    LedgerAction::Pause => "pause",
+                    LedgerAction::Unpause => "unpause",
+                    LedgerAction::Event { .. } => "event",
+                };
+                db_tx.execute(
+                    "INSERT INTO actions (digest, kind, action) VALUES (?1, ?2, ?3)",
+                    params![tx.digest.to_string(), kind, serde_json::to_string(action)?],
+                )?;
+            }
+            for delta in &tx.balance_changes {
+                db_tx.execute(
+                    "INSERT INTO balance_changes (digest, owner, amount) VALUES (?1, ?2, ?3)",
+                    params![
+                        tx.digest.to_string(),
+                        delta.owner.to_string(),
+                        delta.amount.to_string()
+                    ],
+                )?;
+                let balance = balance_of(&db_tx, delta.owner)? as i128 + delta.amount;
+                if balance < 0 {
+                    return Err(anyhow!(
+                        "Negative balance for {} in {}",
+                        delta.owner,
+                        tx.digest
+                    ));
+                }
+                db_tx.execute(
+                    "INSERT OR REPLACE INTO balances (owner, balance) VALUES (?1, ?2)",
+                    params![delta.owner.to_string(), balance.to_string()],
+                )?;
+            }
+        }
+        db_tx.execute(
+            "INSERT OR REPLACE INTO state (key, value) VALUES ('supply', ?1)",
+            params![supply.to_string()],
+        )?;
+        db_tx.execute(
+            "INSERT OR REPLACE INTO state (key, value) VALUES ('checkpoint', ?1)",
+            params![checkpoint.to_string()],
+        )?;
+        db_tx.commit()?;
+        Ok(())
+    }
+
+    pub fn balance(&self, owner: SuiAddress) -> Result<u128> {
+        balance_of(&self.conn, owner)
+    }
+
+    /// Total minted minus burnt, since the first indexed checkpoint.
+    pub fn supply(&self) -> Result<u64> {
+        current_supply(&self.conn)
+    }
+
+    pub fn deny_list(&self) -> Result<Vec<SuiAddress>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT address FROM denied ORDER BY address")?;
+        let addresses = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        addresses
+            .map(|address| Ok(SuiAddress::from_str(&address?)?))
+            .collect()
+    }
+
+    /// The addresses whose balance of the coin changed since `since_ms`, with the time of their
+    /// last change.
+    pub fn counterparties(&self, since_ms: u64) -> Result<Vec<(SuiAddress, u64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT b.owner, MAX(t.timestamp_ms)
+             FROM balance_changes b JOIN transactions t ON b.digest = t.digest
+             WHERE t.timestamp_ms >= ?1
+             GROUP BY b.owner ORDER BY b.owner",
+        )?;
+        let rows = stmt.query_map(params![since_ms], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?))
+        })?;
+        let mut counterparties = vec![];
+        for row in rows {
+            let (owner, last_seen_ms) = row?;
+            counterparties.push((SuiAddress::from_str(&owner)?, last_seen_ms));
+        }
+        Ok(counterparties)
+    }
+
+    /// The indexed mints, burns and deny-list changes, oldest first.
+    pub fn history(&self) -> Result<Vec<LedgerEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT t.timestamp_ms, t.checkpoint, t.digest, t.sender, a.action
+             FROM actions a JOIN transactions t ON a.digest = t.digest
+             ORDER BY t.checkpoint, a.id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, Option<u64>>(0)?,
+                row.get::<_, u64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+        let mut entries = vec![];
+        for row in rows {
+            let (timestamp_ms, checkpoint, digest, sender, action) = row?;
+            entries.push(LedgerEntry {
+                timestamp_ms,
+                checkpoint: Some(checkpoint),
+                digest: TransactionDigest::from_str(&digest)?,
+                actor: SuiAddress::from_str(&sender)?,
+                action: serde_json::from_str(&action)?,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+fn balance_of(conn: &Connection, owner: SuiAddress) -> Result<u128> {
+    let balance: Option<String> = conn
+        .query_row(
+            "SELECT balance FROM balances WHERE owner = ?1",
+            params![owner.to_string()],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(balance.map(|b| b.parse()).transpose()?.unwrap_or(0))
+}
+
+fn current_supply(conn: &Connection) -> Result<u64> {
+    let supply: Option<String> = conn
+        .query_row("SELECT value FROM state WHERE key = 'supply'", [], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    Ok(supply.map(|s| s.parse()).transpose()?.unwrap_or(0))
+}
@@ -1,2 +1,21 @@
+pub mod audit;
+pub mod backend;
+pub mod caps;
+pub mod emergency;
+pub mod enforce;
+pub mod follow;
 pub mod gas;
+pub mod history;
+pub mod indexer;
+pub mod policy;
+pub mod proposal;
+pub mod publish;
+pub mod registry;
+pub mod report;
+pub mod scaffold;
+pub mod schedule;
+pub mod screen;
+pub mod server;
 pub mod tx_run;
+pub mod watch;
+pub mod webhook;
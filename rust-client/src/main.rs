@@ -1,22 +1,62 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand};
 use move_core_types::account_address::AccountAddress;
 use move_core_types::identifier::Identifier;
 use move_core_types::language_storage::{StructTag, TypeTag};
-use sui_config::{sui_config_dir, SUI_CLIENT_CONFIG};
+use sui_config::{sui_config_dir, Config, SUI_CLIENT_CONFIG};
+use sui_keys::keystore::{AccountKeystore, Keystore};
+use sui_sdk::sui_client_config::SuiClientConfig;
 use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+use sui_sdk::types::crypto::{EncodeDecodeBase64, Signature};
+use sui_sdk::types::parse_sui_type_tag;
 use sui_sdk::wallet_context::WalletContext;
-use tracing::debug;
+use sui_sdk::SuiClient;
+use tracing::{debug, error};
 
-use rust_client::tx_run;
-use rust_client::tx_run::{AppCommand, AppConfig};
+use rust_client::audit::{AuditLog, DEFAULT_AUDIT_LOG};
+use rust_client::indexer::{IndexerDb, DEFAULT_INDEXER_DB};
+use rust_client::policy::{
+    MintLedger, Policy, PolicyEngine, DEFAULT_POLICY_DB, DEFAULT_POLICY_FILE,
+};
+use rust_client::proposal::{
+    review_message, sign_review, Decision, ProposalDb, DEFAULT_PROPOSALS_DB,
+};
+use rust_client::publish::{PackageIds, DEFAULT_PACKAGE_PATH};
+use rust_client::registry::{CoinRegistry, ManagedCoin, DEFAULT_REGISTRY_FILE};
+use rust_client::scaffold::CoinParams;
+use rust_client::schedule::{ScheduleDb, Trigger, DEFAULT_MAX_ATTEMPTS, DEFAULT_SCHEDULE_DB};
+use rust_client::screen::{
+    BlocklistScreener, DenyListScreener, HttpScreener, ListFormat, ParsedList, ScreenReport,
+    Screener,
+};
+use rust_client::tx_run::{preflight, AppCommand, AppConfig, CommandContext};
+use rust_client::watch::{self, Watcher};
+use rust_client::webhook::{Notifier, PendingDeliveries, WebhookConfig};
+use rust_client::{
+    caps, emergency, enforce, history, indexer, proposal, publish, report, scaffold, schedule,
+    screen, server, tx_run, webhook,
+};
 
 /// Regulated coin command line interface
 #[derive(Parser, Debug)]
 #[command(name = "rust-client")]
 struct Cli {
+    #[command(flatten)]
+    global: GlobalArgs,
+    #[clap(subcommand)]
+    command: CliCommand,
+}
+
+/// The options before the command
+#[derive(Args, Debug)]
+struct GlobalArgs {
     /// The address of the contract the coin is issued.
     /// If none is passed, environment variable `PACKAGE_ID` will be used.
     #[arg(long = "package-id", short = 'p')]
@@ -26,18 +66,93 @@ struct Cli {
     /// Lastly defaults to "regulated_coin".
     #[arg(long = "module", short = 'm')]
     module: Option<String>,
-    #[clap(subcommand)]
-    command: CliCommand,
+    /// The full type of the coin, eg. `0x..::regulated_coin::REGULATED_COIN`.
+    /// Takes precedence over `--package-id` and `--module`, which assume that the one-time-witness
+    /// is the upper-cased module name.
+    /// If none is passed, environment variable `COIN_TYPE` will be used.
+    #[arg(long = "coin-type", short = 't', conflicts_with_all = ["package_id", "module"])]
+    coin_type: Option<String>,
+    /// The alias of a coin in the registry, eg. `USDX`.
+    #[arg(long = "coin", conflicts_with_all = ["package_id", "module", "coin_type"])]
+    coin: Option<String>,
+    /// The latest version of the coin's package, once it has been upgraded. Calls go to this
+    /// package, while the coin type keeps the original package id.
+    /// If none is passed, environment variable `LATEST_PACKAGE_ID` will be used for the coin in
+    /// `.env`, or `latest_package_id` for a coin in the registry.
+    #[arg(long = "latest-package-id")]
+    latest_package_id: Option<String>,
+    /// The registry of managed coins.
+    /// If none is passed, environment variable `COIN_REGISTRY` will be used.
+    /// Lastly defaults to "coins.json".
+    #[arg(long = "registry")]
+    registry: Option<PathBuf>,
+    /// Only run the read-only pre-flight checks of the command and print their report
+    #[arg(long = "preflight")]
+    preflight: bool,
+    /// Store the command as a proposal for checkers to approve, instead of executing it.
+    /// Proposals are stored in environment variable `PROPOSALS_DB`, lastly "proposals.db".
+    #[arg(long = "propose", conflicts_with = "preflight")]
+    propose: bool,
+    /// With `--propose`, the number of checkers who must approve the proposal
+    #[arg(long = "approvals", default_value_t = 1, requires = "propose")]
+    approvals: u32,
+    /// The policy checked before commands are signed.
+    /// If none is passed, environment variable `POLICY_FILE` will be used.
+    /// Lastly "policy.json" is used if it exists. Mints are counted against the caps of the
+    /// policy in environment variable `POLICY_DB`, lastly "policy.db".
+    #[arg(long = "policy")]
+    policy: Option<PathBuf>,
+    /// The change or incident ticket the command is made under, which the policy may require
+    #[arg(long = "ticket")]
+    ticket: Option<String>,
+    /// Addresses mints and transfers may not go to, in any format of `screen`.
+    /// If none is passed, environment variable `BLOCKLIST_FILE` will be used.
+    /// Recipients are also screened against the deny-list, and by the provider in environment
+    /// variable `SCREENING_URL` if set.
+    #[arg(long = "blocklist")]
+    blocklist: Option<PathBuf>,
+    /// Mint or transfer to a recipient flagged by screening anyway, for this justification
+    #[arg(long = "override-screening")]
+    override_screening: Option<String>,
+}
+
+/// Why a deny-list change is made, kept in the audit log
+#[derive(Args, Debug, Clone, Default)]
+struct ReasonArgs {
+    /// The reason code of the change, eg. `SANCTIONS`
+    #[arg(long = "reason")]
+    reason: Option<String>,
+    /// The compliance case the change belongs to
+    #[arg(long = "case")]
+    case: Option<String>,
+    /// Also emit the reason and case on chain, in the same transaction. Needs the `compliance`
+    /// module in the latest package of the coin
+    #[arg(long = "on-chain")]
+    on_chain: bool,
 }
 
 #[derive(Subcommand, Debug)]
 enum CliCommand {
+    #[command(flatten)]
+    Coin(CoinCommand),
+    #[command(flatten)]
+    Standalone(StandaloneCommand),
+}
+
+/// The commands on the managed coin, run through `cli_parse`
+#[derive(Subcommand, Debug)]
+enum CoinCommand {
     /// Add an address to allow-list
     #[command(name = "deny-list-add")]
     DenyListAdd {
         /// The address to insert to deny-list
         #[arg(value_parser)]
         address: String,
+        /// Add the address to the deny-list of every coin in the registry, in one transaction
+        #[arg(long = "all-coins")]
+        all_coins: bool,
+        #[command(flatten)]
+        reason: ReasonArgs,
     },
     /// Remove an address from deny-list
     #[clap(name = "deny-list-remove")]
@@ -45,6 +160,8 @@ enum CliCommand {
         /// The address to remove from deny-list
         #[arg(value_parser)]
         address: String,
+        #[command(flatten)]
+        reason: ReasonArgs,
     },
     /// Mint and transfer coin
     MintAndTransfer {
@@ -70,70 +187,1369 @@ enum CliCommand {
         #[arg(value_parser)]
         coin: String,
     },
+    /// Deny the coin to every address, with a DenyCapV2 that allows global pause
+    Pause,
+    /// Lift the global pause of the coin
+    Unpause,
+    /// Show value, owner, version and previous transaction of a coin
+    #[command(name = "inspect-coin")]
+    InspectCoin {
+        /// The Coin to inspect
+        #[arg(value_parser)]
+        coin: String,
+    },
+    /// Show the history of mints, burns and deny-list changes of the coin, oldest first
+    History,
+    /// Print mints, burns, deny-list changes, large transfers and transactions refused by the
+    /// deny-list as they are checkpointed
+    Watch {
+        /// Seconds between polls
+        #[arg(long = "interval", default_value_t = 2)]
+        interval: u64,
+        /// Also report transfers of at least this amount
+        #[arg(long = "large-transfer")]
+        large_transfer: Option<u64>,
+        /// Where to keep the last checkpoint looked at, to resume from after a restart.
+        /// Without it, watching starts from the latest checkpoint.
+        #[arg(long = "cursor-file")]
+        cursor_file: Option<PathBuf>,
+        /// Print events as JSON lines
+        #[arg(long = "json")]
+        json: bool,
+        /// Also post deny-list changes, mints, burns and supply changes to the webhooks
+        /// configured in this file
+        #[arg(long = "webhooks")]
+        webhooks: Option<PathBuf>,
+        /// Where to keep the webhook deliveries that failed, to retry them at the next polls, and
+        /// those a sink rejected
+        #[arg(long = "webhooks-pending", default_value = webhook::DEFAULT_PENDING_FILE)]
+        webhooks_pending: PathBuf,
+    },
+    /// Serve the deny-list, mint, burn and balance commands as an HTTP API, authenticated with the
+    /// bearer token in environment variable `API_TOKEN`
+    Serve {
+        /// The address to listen on
+        #[arg(long = "addr", default_value = "127.0.0.1:8080")]
+        addr: SocketAddr,
+    },
+    /// Review, approve, reject and execute the proposals made with `--propose`
+    Proposal {
+        /// The database.
+        /// If none is passed, environment variable `PROPOSALS_DB` will be used.
+        /// Lastly defaults to "proposals.db".
+        #[arg(long = "db")]
+        db: Option<PathBuf>,
+        #[clap(subcommand)]
+        command: ProposalCommand,
+    },
+    /// Compare a sanctions list with the deny-list and the recent counterparties of the coin, and
+    /// print the addresses to deny
+    Screen {
+        /// An OFAC `sdn.xml` or `sdn.csv`, or a CSV file with an `address` column
+        #[arg(value_parser)]
+        list: PathBuf,
+        /// The format of the list: sdn-xml, sdn-csv or csv. Guessed from the file if none is
+        /// passed
+        #[arg(long = "format")]
+        format: Option<String>,
+        /// Addresses whose balance of the coin changed in the last days, according to the
+        /// indexer database, are recent counterparties
+        #[arg(long = "days", default_value_t = 30)]
+        days: u64,
+        /// The indexer database.
+        /// If none is passed, environment variable `INDEXER_DB` will be used.
+        /// Lastly defaults to "indexer.db". Counterparties are not checked without it.
+        #[arg(long = "db")]
+        db: Option<PathBuf>,
+        /// Add the addresses to deny to the deny-list, in transactions of up to 250 addresses.
+        /// With `--propose`, propose the transactions instead
+        #[arg(long = "apply")]
+        apply: bool,
+        #[command(flatten)]
+        reason: ReasonArgs,
+    },
+    /// Add an address to the deny-list and report the coins it held when it was denied, with their
+    /// ids, balances and last transactions
+    Enforce {
+        /// The address to freeze
+        #[arg(value_parser)]
+        address: String,
+        /// Also write the evidence report to this file, as JSON
+        #[arg(long = "evidence")]
+        evidence: Option<PathBuf>,
+        #[command(flatten)]
+        reason: ReasonArgs,
+    },
+    /// Pause the coin where its DenyCapV2 allows it, deny a list of addresses and optionally move
+    /// the TreasuryCap and deny-cap to the cold address, in one transaction, after typed
+    /// confirmation
+    #[command(name = "emergency-freeze")]
+    EmergencyFreeze {
+        /// The addresses to deny
+        #[arg(value_parser)]
+        addresses: Vec<String>,
+        /// Also deny the addresses of this list: an OFAC `sdn.xml` or `sdn.csv`, or a CSV file
+        /// with an `address` column
+        #[arg(long = "list")]
+        list: Option<PathBuf>,
+        /// Move the TreasuryCap and the deny-cap to the cold address
+        #[arg(long = "move-caps")]
+        move_caps: bool,
+        /// The cold address the caps are moved to.
+        /// If none is passed, environment variable `COLD_ADDRESS` will be used.
+        #[arg(long = "cold-address", requires = "move_caps")]
+        cold_address: Option<String>,
+        #[command(flatten)]
+        reason: ReasonArgs,
+    },
+    /// Show who owns the TreasuryCap, deny-cap and CoinMetadata of the coin, and move the caps to
+    /// a known multisig or key
+    Caps {
+        #[clap(subcommand)]
+        command: CapsCommand,
+    },
+    /// Queue deny-list changes to execute at a later time or epoch, and execute them when due
+    Schedule {
+        /// The database.
+        /// If none is passed, environment variable `SCHEDULE_DB` will be used.
+        /// Lastly defaults to "schedule.db".
+        #[arg(long = "db")]
+        db: Option<PathBuf>,
+        #[clap(subcommand)]
+        command: ScheduleCommand,
+    },
 }
 
-async fn cli_parse() -> Result<(AppConfig, AppCommand)> {
-    let Cli {
-        package_id,
-        module,
-        command,
-    } = Cli::parse();
+/// The commands run on their own: there is no coin to resolve before publishing it, the audit
+/// log and indexer queries are read from disk, the indexer only needs the client, and the
+/// supply and deny status cover every coin in the registry
+#[derive(Subcommand, Debug)]
+enum StandaloneCommand {
+    /// Show the total supply of every coin in the registry
+    Supply,
+    /// Show whether an address is denied in the current and the next epoch, and whether the coin
+    /// is paused, for every coin in the registry
+    DenyStatus {
+        /// The address to look up
+        #[arg(value_parser)]
+        address: String,
+    },
+    /// Check the local audit log of executed commands
+    Audit {
+        /// The log.
+        /// If none is passed, environment variable `AUDIT_LOG` will be used.
+        /// Lastly defaults to "audit.log".
+        #[arg(long = "log")]
+        log: Option<PathBuf>,
+        #[clap(subcommand)]
+        command: AuditCommand,
+    },
+    /// Mirror the coin's transactions, balances and deny-list into a local SQLite database
+    Indexer {
+        /// The database.
+        /// If none is passed, environment variable `INDEXER_DB` will be used.
+        /// Lastly defaults to "indexer.db".
+        #[arg(long = "db")]
+        db: Option<PathBuf>,
+        #[clap(subcommand)]
+        command: IndexerCommand,
+    },
+    /// Publish the regulated coin package and write its ids to `.env`
+    Publish {
+        /// The Move package to publish
+        #[arg(long = "path", default_value = DEFAULT_PACKAGE_PATH)]
+        path: PathBuf,
+        /// The file to write `PACKAGE_ID`, `COIN_TYPE` and the cap ids to
+        #[arg(long = "env-file", default_value = ".env")]
+        env_file: PathBuf,
+        /// Also register the new coin under this alias
+        #[arg(long = "alias")]
+        alias: Option<String>,
+    },
+    /// Upgrade the coin's package using its UpgradeCap, and write the new package id to `.env`
+    Upgrade {
+        /// The new version of the Move package
+        #[arg(long = "path", default_value = DEFAULT_PACKAGE_PATH)]
+        path: PathBuf,
+        /// The UpgradeCap of the package.
+        /// If none is passed, environment variable `UPGRADE_CAP_ID` will be used.
+        #[arg(long = "upgrade-cap")]
+        upgrade_cap: Option<String>,
+        /// The file to write `LATEST_PACKAGE_ID` to
+        #[arg(long = "env-file", default_value = ".env")]
+        env_file: PathBuf,
+        /// Also record the new package id for this coin in the registry
+        #[arg(long = "alias")]
+        alias: Option<String>,
+    },
+    /// Generate the Move package of a new regulated coin, and optionally publish it
+    #[command(name = "new-coin")]
+    NewCoin {
+        /// The module of the coin, eg. `usdx`. Also names the package.
+        #[arg(value_parser)]
+        module: String,
+        #[arg(long = "decimals", default_value_t = 6)]
+        decimals: u8,
+        #[arg(long = "symbol")]
+        symbol: String,
+        #[arg(long = "name")]
+        name: String,
+        #[arg(long = "description", default_value = "")]
+        description: String,
+        #[arg(long = "icon-url")]
+        icon_url: Option<String>,
+        /// Let the DenyCapV2 of the coin pause it for every address, see `pause`
+        #[arg(long = "allow-global-pause")]
+        allow_global_pause: bool,
+        /// Where to generate the package. Defaults to `../move/<module>`.
+        #[arg(long = "output", short = 'o')]
+        output: Option<PathBuf>,
+        /// Publish the package after generating it, as `publish` does
+        #[arg(long = "publish")]
+        publish: bool,
+        /// With `--publish`, the file to write the ids to
+        #[arg(long = "env-file", default_value = ".env")]
+        env_file: PathBuf,
+        /// With `--publish`, also register the new coin under this alias
+        #[arg(long = "alias")]
+        alias: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum IndexerCommand {
+    /// Index new checkpoints, resuming after the last indexed one
+    Sync {
+        /// Where a new database starts indexing, by default the checkpoint the coin was published
+        /// in. Later checkpoints are refused, as the supply and balances before them would be
+        /// missing
+        #[arg(long = "from-checkpoint")]
+        from_checkpoint: Option<u64>,
+        /// Keep indexing new checkpoints
+        #[arg(long = "follow")]
+        follow: bool,
+        /// With `--follow`, seconds between polls
+        #[arg(long = "interval", default_value_t = 5)]
+        interval: u64,
+    },
+    #[command(flatten)]
+    Query(IndexerQuery),
+}
+
+/// Indexer commands answered from the database alone
+#[derive(Subcommand, Debug)]
+enum IndexerQuery {
+    /// Show the indexed balance of an address
+    Balance {
+        #[arg(value_parser)]
+        address: String,
+    },
+    /// Show the indexed deny-list
+    #[command(name = "deny-list-show")]
+    DenyListShow,
+    /// Show the indexed history of mints, burns and deny-list changes
+    History,
+    /// Show the supply minted and burnt since the first indexed checkpoint
+    Supply,
+}
+
+/// When a scheduled change is due: exactly one of the three
+#[derive(Args, Debug, Clone)]
+#[group(required = true, multiple = false)]
+struct TriggerArgs {
+    /// The time, eg. `2026-11-18T09:00:00Z`
+    #[arg(long = "at")]
+    at: Option<String>,
+    /// The delay from now, eg. `30d`, `12h` or `15m`
+    #[arg(long = "after")]
+    after: Option<String>,
+    /// The epoch of the network
+    #[arg(long = "epoch")]
+    epoch: Option<u64>,
+}
+
+#[derive(Subcommand, Debug)]
+enum ScheduleCommand {
+    /// Schedule adding an address to the deny-list
+    #[command(name = "deny-list-add")]
+    DenyListAdd {
+        #[arg(value_parser)]
+        address: String,
+        #[command(flatten)]
+        trigger: TriggerArgs,
+        /// Attempts before the change is given up as failed
+        #[arg(long = "max-attempts", default_value_t = DEFAULT_MAX_ATTEMPTS)]
+        max_attempts: u32,
+        #[command(flatten)]
+        reason: ReasonArgs,
+    },
+    /// Schedule removing an address from the deny-list, eg. to lift a temporary freeze
+    #[command(name = "deny-list-remove")]
+    DenyListRemove {
+        #[arg(value_parser)]
+        address: String,
+        #[command(flatten)]
+        trigger: TriggerArgs,
+        /// Attempts before the change is given up as failed
+        #[arg(long = "max-attempts", default_value_t = DEFAULT_MAX_ATTEMPTS)]
+        max_attempts: u32,
+        #[command(flatten)]
+        reason: ReasonArgs,
+    },
+    /// List the scheduled changes, oldest first
+    List {
+        /// Only list changes with this status: pending, executed, failed or cancelled
+        #[arg(long = "status")]
+        status: Option<String>,
+    },
+    /// Show a scheduled change and its attempts
+    Show {
+        #[arg(value_parser)]
+        id: u64,
+    },
+    /// Cancel a pending change
+    Cancel {
+        #[arg(value_parser)]
+        id: u64,
+    },
+    /// Execute the changes of the coin that are due, with the active address, and print a report
+    Run {
+        /// Keep running, executing changes as they become due
+        #[arg(long = "follow")]
+        follow: bool,
+        /// With `--follow`, seconds between runs
+        #[arg(long = "interval", default_value_t = 60)]
+        interval: u64,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CapsCommand {
+    /// Show the owners of the TreasuryCap, the deny-cap and the CoinMetadata, found by their ids
+    /// in the transaction that created the coin
+    Show,
+    /// Move the TreasuryCap and the deny-cap from the active address to a multisig or key of the
+    /// registry, once the pre-flight checks and a dry-run pass
+    Transfer {
+        /// The recipient
+        #[arg(long = "to")]
+        to: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum AuditCommand {
+    /// Check that no record was edited, removed or inserted, and that each is signed by its
+    /// operator
+    Verify,
+}
+
+#[derive(Subcommand, Debug)]
+enum ProposalCommand {
+    /// List the proposals, oldest first
+    List {
+        /// Only list proposals with this status: pending, approved, rejected, executed or failed
+        #[arg(long = "status")]
+        status: Option<String>,
+    },
+    /// Show a proposal, its reviews, and its dry-run when proposed and now
+    Show {
+        #[arg(value_parser)]
+        id: u64,
+    },
+    /// Print the message a checker signs to approve a proposal, or to reject it with `--reject`,
+    /// for a wallet to sign as a personal message
+    Message {
+        #[arg(value_parser)]
+        id: u64,
+        #[arg(long = "reject")]
+        reject: bool,
+    },
+    /// Sign the approval of a proposal, or its rejection with `--reject`, with the key of the
+    /// active address, and print the signature for `approve` or `reject`. Run by the checker
+    Sign {
+        #[arg(value_parser)]
+        id: u64,
+        #[arg(long = "reject")]
+        reject: bool,
+    },
+    /// Approve a proposal with the checker's signature of the approval
+    Approve {
+        #[arg(value_parser)]
+        id: u64,
+        #[arg(long = "checker")]
+        checker: String,
+        /// The signature printed by `sign`, or by the checker's wallet, in base64
+        #[arg(long = "signature")]
+        signature: String,
+        #[arg(long = "comment")]
+        comment: Option<String>,
+    },
+    /// Reject a proposal with the checker's signature of the rejection
+    Reject {
+        #[arg(value_parser)]
+        id: u64,
+        #[arg(long = "checker")]
+        checker: String,
+        /// The signature printed by `sign --reject`, or by the checker's wallet, in base64
+        #[arg(long = "signature")]
+        signature: String,
+        #[arg(long = "comment")]
+        comment: Option<String>,
+    },
+    /// Verify the approvals of a proposal, build its transaction again and execute it as its maker
+    Execute {
+        #[arg(value_parser)]
+        id: u64,
+    },
+    /// Show the audit trail of a proposal, or of every proposal
+    Audit {
+        #[arg(value_parser)]
+        id: Option<u64>,
+    },
+}
+
+/// What `main` runs: either a transaction or a read-only report
+enum Action {
+    Execute(AppCommand),
+    Preflight(AppCommand),
+    Propose {
+        command: AppCommand,
+        approvals: u32,
+        db: PathBuf,
+    },
+    Proposal {
+        db: PathBuf,
+        command: ProposalCommand,
+    },
+    Schedule {
+        db: PathBuf,
+        command: ScheduleCommand,
+    },
+    InspectCoin(ObjectID),
+    History,
+    Watch {
+        interval: Duration,
+        large_transfer: Option<u64>,
+        cursor_file: Option<PathBuf>,
+        json: bool,
+        webhooks: Option<WebhookConfig>,
+        webhooks_pending: PathBuf,
+    },
+    Serve {
+        addr: SocketAddr,
+        token: String,
+    },
+    Screen {
+        list: ParsedList,
+        /// The indexer database to look up counterparties in, if it exists
+        indexer_db: Option<PathBuf>,
+        since_ms: u64,
+        apply: bool,
+        /// The approvals of the proposals, if the additions are proposed instead of executed
+        propose: Option<u32>,
+    },
+    Enforce {
+        address: SuiAddress,
+        evidence: Option<PathBuf>,
+    },
+    EmergencyFreeze {
+        addresses: Vec<SuiAddress>,
+        cold_address: Option<SuiAddress>,
+        /// Only run the pre-flight checks of the planned freeze
+        preflight: bool,
+    },
+    CapsShow,
+    CapsTransfer {
+        to: SuiAddress,
+        /// Only run the pre-flight checks and the dry-run
+        preflight: bool,
+        /// The registry to record the new owners of the caps in
+        registry_path: PathBuf,
+    },
+}
+
+/// Builds the type of the coin assuming the sample layout, where the one-time-witness is the
+/// upper-cased module name: `package::module::MODULE`.
+fn otw_type_tag(package_id: Option<String>, module: Option<String>) -> Result<TypeTag> {
     let package_id_str = match package_id {
         Some(package_id) => package_id,
-        None => {
-            dotenvy::dotenv().ok();
-            std::env::var("PACKAGE_ID")?
-        }
+        None => std::env::var("PACKAGE_ID").map_err(|_| {
+            anyhow!(
+                "No coin selected: pass --coin, --coin-type or --package-id, or set COIN_TYPE or \
+                 PACKAGE_ID in .env"
+            )
+        })?,
     };
     let package_id = ObjectID::from_hex_literal(&package_id_str)?;
     let module = match module {
         Some(module) => module,
-        None => {
-            dotenvy::dotenv().ok();
-            match std::env::var("MODULE_NAME") {
-                Ok(module) => module,
-                Err(_) => "regulated_coin".to_string()
-            }
-        }
+        None => match std::env::var("MODULE_NAME") {
+            Ok(module) => module,
+            Err(_) => "regulated_coin".to_string(),
+        },
     };
     let otw = module.to_uppercase();
-    let type_tag = TypeTag::Struct(Box::new(StructTag {
+    Ok(TypeTag::Struct(Box::new(StructTag {
         address: AccountAddress::new(package_id.as_ref().try_into()?),
         module: Identifier::from_str(&module)?,
         name: Identifier::from_str(&otw)?,
         type_params: vec![],
-    }));
-    let wallet_context =
-        WalletContext::new(&sui_config_dir()?.join(SUI_CLIENT_CONFIG), None, None).await?;
+    })))
+}
 
-    let command = match command {
-        CliCommand::DenyListAdd { address } => {
-            AppCommand::DenyListAdd(SuiAddress::from_str(&address)?)
+fn parse_package_id(package_id: Option<String>) -> Result<Option<ObjectID>> {
+    Ok(match package_id {
+        Some(package_id) => Some(ObjectID::from_hex_literal(&package_id)?),
+        None => None,
+    })
+}
+
+fn indexer_db_path(db: Option<PathBuf>) -> PathBuf {
+    match db {
+        Some(db) => db,
+        None => std::env::var("INDEXER_DB")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_INDEXER_DB)),
+    }
+}
+
+/// Answers an indexer query from the database alone, without reaching the chain.
+fn indexer_query(global: &GlobalArgs, db: Option<PathBuf>, query: &IndexerQuery) -> Result<()> {
+    let registry = CoinRegistry::load_or_default(&registry_path(global.registry.clone()))?;
+    let (type_tag, _) = resolve_coin(global, &registry)?;
+    let db = IndexerDb::open(&indexer_db_path(db), &type_tag)?;
+    match query {
+        IndexerQuery::Balance { address } => {
+            println!("{}", db.balance(SuiAddress::from_str(address)?)?);
         }
-        CliCommand::DenyListRemove { address } => {
-            AppCommand::DenyListRemove(SuiAddress::from_str(&address)?)
+        IndexerQuery::DenyListShow => {
+            for address in db.deny_list()? {
+                println!("{address}");
+            }
+        }
+        IndexerQuery::History => {
+            for entry in db.history()? {
+                println!("{entry}");
+            }
+        }
+        IndexerQuery::Supply => println!("{}", db.supply()?),
+    }
+    match db.cursor()? {
+        Some(cursor) => println!("(indexed up to checkpoint {cursor})"),
+        None => println!("(nothing indexed yet)"),
+    }
+    Ok(())
+}
+
+/// Indexes the checkpoints of the coin into `db`, which only needs the client of the wallet.
+async fn indexer_sync(
+    global: &GlobalArgs,
+    db: Option<PathBuf>,
+    from_checkpoint: Option<u64>,
+    follow: bool,
+    interval: Duration,
+) -> Result<()> {
+    let registry = CoinRegistry::load_or_default(&registry_path(global.registry.clone()))?;
+    let (type_tag, _) = resolve_coin(global, &registry)?;
+    let (client, _, _) = load_wallet().await?;
+    tx_run::verify_coin_type(&client, &type_tag).await?;
+    let mut db = IndexerDb::open(&indexer_db_path(db), &type_tag)?;
+    loop {
+        let synced = indexer::sync(&client, &mut db, &type_tag, from_checkpoint).await;
+        match synced.and_then(|indexed| Ok((indexed, db.cursor()?))) {
+            Ok((indexed, cursor)) => println!(
+                "Indexed {indexed} transactions, up to checkpoint {}",
+                cursor.map_or("-".to_string(), |cursor| cursor.to_string())
+            ),
+            // Each checkpoint is committed on its own, so the next sync resumes after the last one
+            Err(e) if follow => error!("Sync failed, retrying in {}s: {e:#}", interval.as_secs()),
+            Err(e) => return Err(e),
+        }
+        if !follow {
+            return Ok(());
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+fn schedule_db_path(db: Option<PathBuf>) -> PathBuf {
+    match db {
+        Some(db) => db,
+        None => std::env::var("SCHEDULE_DB")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_SCHEDULE_DB)),
+    }
+}
+
+/// The trigger of `--at`, `--after` or `--epoch`.
+fn trigger(args: &TriggerArgs) -> Result<Trigger> {
+    match (&args.at, &args.after, args.epoch) {
+        (Some(at), None, None) => {
+            let at = chrono::DateTime::parse_from_rfc3339(at)
+                .map_err(|e| anyhow!("Invalid time {at}: {e}"))?;
+            Ok(Trigger::At(at.timestamp_millis().max(0) as u64))
+        }
+        (None, Some(after), None) => Ok(Trigger::At(
+            Utc::now().timestamp_millis() as u64 + schedule::parse_delay(after)?,
+        )),
+        (None, None, Some(epoch)) => Ok(Trigger::Epoch(epoch)),
+        _ => Err(anyhow!("Pass exactly one of --at, --after and --epoch")),
+    }
+}
+
+fn proposals_db_path(db: Option<PathBuf>) -> PathBuf {
+    match db {
+        Some(db) => db,
+        None => std::env::var("PROPOSALS_DB")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_PROPOSALS_DB)),
+    }
+}
+
+/// The policy of `--policy`, `POLICY_FILE` or "policy.json", with its mint ledger.
+/// Without any of them, commands are not checked.
+fn load_policy(policy: Option<PathBuf>) -> Result<Option<PolicyEngine>> {
+    let path = match policy.or_else(|| std::env::var("POLICY_FILE").ok().map(PathBuf::from)) {
+        Some(path) => path,
+        None if Path::new(DEFAULT_POLICY_FILE).exists() => PathBuf::from(DEFAULT_POLICY_FILE),
+        None => return Ok(None),
+    };
+    let ledger = std::env::var("POLICY_DB")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_POLICY_DB));
+    Ok(Some(PolicyEngine::new(
+        Policy::load(&path)?,
+        MintLedger::open(&ledger)?,
+    )))
+}
+
+/// Screeners of mint and transfer recipients: the deny-list, the blocklist of `--blocklist` or
+/// `BLOCKLIST_FILE`, and the provider at `SCREENING_URL` with the bearer token
+/// `SCREENING_TOKEN`.
+fn load_screeners(
+    client: &SuiClient,
+    blocklist: Option<PathBuf>,
+) -> Result<Vec<Box<dyn Screener>>> {
+    let mut screeners: Vec<Box<dyn Screener>> =
+        vec![Box::new(DenyListScreener::new(client.clone()))];
+    if let Some(path) =
+        blocklist.or_else(|| std::env::var("BLOCKLIST_FILE").ok().map(PathBuf::from))
+    {
+        screeners.push(Box::new(BlocklistScreener::load(&path)?));
+    }
+    if let Ok(url) = std::env::var("SCREENING_URL") {
+        let token = std::env::var("SCREENING_TOKEN").ok();
+        screeners.push(Box::new(HttpScreener::new(url, token)?));
+    }
+    Ok(screeners)
+}
+
+fn audit_log_path(log: Option<PathBuf>) -> PathBuf {
+    match log {
+        Some(log) => log,
+        None => std::env::var("AUDIT_LOG")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_AUDIT_LOG)),
+    }
+}
+
+/// Verifies the audit log, from the file alone. Exits with 1 if it was tampered with.
+fn verify_audit_log(log: Option<PathBuf>) -> Result<()> {
+    let log = AuditLog::new(audit_log_path(log));
+    let report = log.verify()?;
+    for problem in &report.problems {
+        println!("[FAIL] {problem}");
+    }
+    println!("Records: {}", report.records);
+    println!("Head:    {}", report.head.as_deref().unwrap_or("-"));
+    if !report.problems.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn registry_path(registry: Option<PathBuf>) -> PathBuf {
+    match registry {
+        Some(registry) => registry,
+        None => std::env::var("COIN_REGISTRY")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_REGISTRY_FILE)),
+    }
+}
+
+/// Loads the sui client's environment. Returns the client, the keystore and the active address.
+async fn load_wallet() -> Result<(SuiClient, Keystore, SuiAddress)> {
+    let config_path = sui_config_dir()?.join(SUI_CLIENT_CONFIG);
+    let mut wallet_context = WalletContext::new(&config_path, None, None).await?;
+    let client = wallet_context.get_client().await?;
+    let active_addr = wallet_context.active_address()?;
+    // The keystore `client.yaml` points to, which need not be in the config directory
+    let keystore = SuiClientConfig::load(&config_path)?.keystore;
+    Ok((client, keystore, active_addr))
+}
+
+/// Resolves the type of the coin and its packages from the options, `.env` and the registry,
+/// without reaching the chain.
+fn resolve_coin(global: &GlobalArgs, registry: &CoinRegistry) -> Result<(TypeTag, PackageIds)> {
+    let GlobalArgs {
+        package_id,
+        module,
+        coin_type,
+        coin,
+        latest_package_id,
+        ..
+    } = global;
+    let (package_id, module) = (package_id.clone(), module.clone());
+    // An explicit `--package-id`/`--module` should not be overridden by `COIN_TYPE` in `.env`
+    let explicit = package_id.is_some() || module.is_some();
+    // `LATEST_PACKAGE_ID` belongs to the coin in `.env`
+    let env_latest = || std::env::var("LATEST_PACKAGE_ID").ok();
+    let package_from_env = package_id.is_none();
+    let (type_tag, latest) = if let Some(alias) = coin {
+        let coin = registry.get(alias)?;
+        (coin.type_tag.clone(), coin.latest_package_id)
+    } else if let Some(coin_type) = coin_type {
+        (parse_sui_type_tag(coin_type)?, None)
+    } else if let Some(coin_type) = std::env::var("COIN_TYPE").ok().filter(|_| !explicit) {
+        (
+            parse_sui_type_tag(&coin_type)?,
+            parse_package_id(env_latest())?,
+        )
+    } else {
+        let type_tag = otw_type_tag(package_id, module)?;
+        let latest = if package_from_env {
+            parse_package_id(env_latest())?
+        } else {
+            None
+        };
+        (type_tag, latest)
+    };
+    let latest = match parse_package_id(latest_package_id.clone())? {
+        Some(latest) => Some(latest),
+        None => latest,
+    };
+    let packages = PackageIds::new(&type_tag, latest)?;
+    Ok((type_tag, packages))
+}
+
+/// Why the command is run, from the global `--ticket` and the reason of deny-list changes.
+fn command_context(global: &GlobalArgs, command: &CoinCommand) -> CommandContext {
+    let reason = match command {
+        CoinCommand::DenyListAdd { reason, .. }
+        | CoinCommand::DenyListRemove { reason, .. }
+        | CoinCommand::Screen { reason, .. }
+        | CoinCommand::Enforce { reason, .. }
+        | CoinCommand::EmergencyFreeze { reason, .. }
+        | CoinCommand::Schedule {
+            command:
+                ScheduleCommand::DenyListAdd { reason, .. }
+                | ScheduleCommand::DenyListRemove { reason, .. },
+            ..
+        } => reason.clone(),
+        _ => ReasonArgs::default(),
+    };
+    CommandContext {
+        ticket: global.ticket.clone(),
+        reason: reason.reason,
+        case: reason.case,
+        on_chain: reason.on_chain,
+        override_screening: global.override_screening.clone(),
+    }
+}
+
+async fn cli_parse(global: GlobalArgs, command: CoinCommand) -> Result<(AppConfig, Action)> {
+    let registry = CoinRegistry::load_or_default(&registry_path(global.registry.clone()))?;
+    let (type_tag, packages) = resolve_coin(&global, &registry)?;
+    let GlobalArgs {
+        preflight,
+        propose,
+        approvals,
+        policy,
+        blocklist,
+        registry: registry_file,
+        ..
+    } = global;
+    let policy = load_policy(policy)?;
+
+    let action = match command {
+        CoinCommand::DenyListAdd {
+            address, all_coins, ..
+        } => {
+            let address = SuiAddress::from_str(&address)?;
+            if all_coins {
+                Action::Execute(AppCommand::DenyListAddAll(address))
+            } else {
+                Action::Execute(AppCommand::DenyListAdd(address))
+            }
         }
-        CliCommand::MintAndTransfer { balance, address } => {
-            AppCommand::MintAndTransfer(balance, SuiAddress::from_str(&address)?)
+        CoinCommand::DenyListRemove { address, .. } => {
+            Action::Execute(AppCommand::DenyListRemove(SuiAddress::from_str(&address)?))
         }
-        CliCommand::Transfer { coin, address } => AppCommand::Transfer(
+        CoinCommand::MintAndTransfer { balance, address } => Action::Execute(
+            AppCommand::MintAndTransfer(balance, SuiAddress::from_str(&address)?),
+        ),
+        CoinCommand::Transfer { coin, address } => Action::Execute(AppCommand::Transfer(
             ObjectID::from_hex_literal(&coin)?,
             SuiAddress::from_str(&address)?,
-        ),
-        CliCommand::Burn { coin } => AppCommand::Burn(ObjectID::from_hex_literal(&coin)?),
+        )),
+        CoinCommand::Burn { coin } => {
+            Action::Execute(AppCommand::Burn(ObjectID::from_hex_literal(&coin)?))
+        }
+        CoinCommand::Pause => Action::Execute(AppCommand::Pause),
+        CoinCommand::Unpause => Action::Execute(AppCommand::Unpause),
+        CoinCommand::InspectCoin { coin } => {
+            Action::InspectCoin(ObjectID::from_hex_literal(&coin)?)
+        }
+        CoinCommand::History => Action::History,
+        CoinCommand::Watch {
+            interval,
+            large_transfer,
+            cursor_file,
+            json,
+            webhooks,
+            webhooks_pending,
+        } => Action::Watch {
+            interval: Duration::from_secs(interval),
+            large_transfer,
+            cursor_file,
+            json,
+            webhooks: webhooks
+                .map(|path| WebhookConfig::load(&path))
+                .transpose()?,
+            webhooks_pending,
+        },
+        CoinCommand::Serve { addr } => Action::Serve {
+            addr,
+            token: match std::env::var("API_TOKEN") {
+                Ok(token) if !token.is_empty() => token,
+                _ => return Err(anyhow!("serve needs a bearer token in API_TOKEN")),
+            },
+        },
+        CoinCommand::Proposal { db, command } => Action::Proposal {
+            db: proposals_db_path(db),
+            command,
+        },
+        CoinCommand::Schedule { db, command } => {
+            if preflight || propose {
+                return Err(anyhow!(
+                    "--preflight and --propose are not supported by schedule"
+                ));
+            }
+            Action::Schedule {
+                db: schedule_db_path(db),
+                command,
+            }
+        }
+        CoinCommand::Screen {
+            list,
+            format,
+            days,
+            db,
+            apply,
+            ..
+        } => {
+            if apply && preflight {
+                return Err(anyhow!("--preflight is not supported by screen --apply"));
+            }
+            let contents = std::fs::read_to_string(&list)
+                .map_err(|e| anyhow!("Cannot read {}: {e}", list.display()))?;
+            let format = match format {
+                Some(format) => format.parse()?,
+                None => ListFormat::detect(&list, &contents),
+            };
+            let indexer_db = indexer_db_path(db);
+            let since = Utc::now() - chrono::Duration::days(days as i64);
+            Action::Screen {
+                list: screen::parse_list(format, &contents)?,
+                indexer_db: indexer_db.exists().then_some(indexer_db),
+                since_ms: since.timestamp_millis().max(0) as u64,
+                apply,
+                propose: propose.then_some(approvals),
+            }
+        }
+        CoinCommand::Enforce {
+            address, evidence, ..
+        } => {
+            let address = SuiAddress::from_str(&address)?;
+            if propose {
+                // The balance is documented when the address is denied, not when it is proposed
+                return Err(anyhow!("--propose is not supported by enforce"));
+            }
+            if preflight {
+                Action::Preflight(AppCommand::DenyListAdd(address))
+            } else {
+                Action::Enforce { address, evidence }
+            }
+        }
+        CoinCommand::EmergencyFreeze {
+            addresses,
+            list,
+            move_caps,
+            cold_address,
+            ..
+        } => {
+            if propose {
+                // An incident does not wait for checkers
+                return Err(anyhow!("--propose is not supported by emergency-freeze"));
+            }
+            let mut addresses = addresses
+                .iter()
+                .map(|address| SuiAddress::from_str(address))
+                .collect::<Result<Vec<_>, _>>()?;
+            if let Some(list) = list {
+                let contents = std::fs::read_to_string(&list)
+                    .map_err(|e| anyhow!("Cannot read {}: {e}", list.display()))?;
+                let format = ListFormat::detect(&list, &contents);
+                let list = screen::parse_list(format, &contents)?;
+                addresses.extend(list.addresses.iter().map(|listed| listed.address));
+            }
+            let cold_address = match cold_address {
+                Some(address) => Some(address),
+                None if move_caps => Some(
+                    std::env::var("COLD_ADDRESS")
+                        .map_err(|_| anyhow!("--move-caps needs --cold-address or COLD_ADDRESS"))?,
+                ),
+                None => None,
+            };
+            Action::EmergencyFreeze {
+                addresses,
+                cold_address: cold_address
+                    .map(|address| SuiAddress::from_str(&address))
+                    .transpose()?,
+                preflight,
+            }
+        }
+        CoinCommand::Caps {
+            command: CapsCommand::Show,
+        } => Action::CapsShow,
+        CoinCommand::Caps {
+            command: CapsCommand::Transfer { to },
+        } => {
+            let to = SuiAddress::from_str(&to)?;
+            if propose {
+                Action::Execute(AppCommand::TransferCaps(to))
+            } else {
+                Action::CapsTransfer {
+                    to,
+                    preflight,
+                    registry_path: registry_path(registry_file),
+                }
+            }
+        }
+    };
+    let action = match action {
+        Action::Execute(command) if preflight => Action::Preflight(command),
+        Action::Execute(command) if propose => Action::Propose {
+            command,
+            approvals,
+            db: proposals_db_path(None),
+        },
+        action => action,
     };
 
-    let client = wallet_context.get_client().await?;
+    let (client, keystore, active_addr) = load_wallet().await?;
+    let metadata = tx_run::verify_coin_type(&client, &type_tag).await?;
+    debug!("Managing {} ({})", metadata.name, metadata.symbol);
+    publish::verify_package_ids(&client, &packages, &type_tag).await?;
     Ok((
         AppConfig {
             client,
-            wallet_context,
+            keystore,
+            active_address: active_addr,
             type_tag,
+            packages,
+            registry,
+            policy,
+            audit_log: Some(AuditLog::new(audit_log_path(None))),
+            screeners: load_screeners(&client, blocklist)?,
         },
-        command,
+        action,
     ))
 }
 
+/// Publishes the package at `path`, writes its ids to `env_file` and, given an alias, registers
+/// the new coin with the active address as owner of both caps.
+async fn publish_package(
+    path: PathBuf,
+    env_file: PathBuf,
+    alias: Option<String>,
+    registry_path: PathBuf,
+) -> Result<()> {
+    let (client, keystore, active_addr) = load_wallet().await?;
+    let signer = keystore.get_key(&active_addr)?;
+    let published = publish::publish(&client, signer, &path).await?;
+    publish::write_env(&env_file, &published)?;
+    if let Some(alias) = alias {
+        let mut registry = CoinRegistry::load_or_default(&registry_path)?;
+        registry.insert(ManagedCoin {
+            alias,
+            type_tag: published.type_tag.clone(),
+            treasury_cap_owner: active_addr,
+            deny_cap_owner: active_addr,
+            latest_package_id: None,
+        });
+        registry.save(&registry_path)?;
+    }
+
+    println!("Transaction:   {}", published.digest);
+    println!("Package:       {}", published.package_id);
+    println!("Coin type:     {}", published.type_tag);
+    println!("TreasuryCap:   {}", published.treasury_cap);
+    println!("DenyCap:       {}", published.deny_cap);
+    println!("CoinMetadata:  {}", published.coin_metadata);
+    println!("UpgradeCap:    {}", published.upgrade_cap);
+    println!("Written to {}", env_file.display());
+    Ok(())
+}
+
+/// Upgrades the package of `upgrade_cap` to the one at `path` and records the new package id.
+async fn upgrade_package(
+    path: PathBuf,
+    upgrade_cap: Option<String>,
+    env_file: PathBuf,
+    alias: Option<String>,
+    registry_path: PathBuf,
+) -> Result<()> {
+    let upgrade_cap = match upgrade_cap {
+        Some(upgrade_cap) => upgrade_cap,
+        None => std::env::var("UPGRADE_CAP_ID")?,
+    };
+    let upgrade_cap = ObjectID::from_hex_literal(&upgrade_cap)?;
+    let (client, keystore, active_addr) = load_wallet().await?;
+    let signer = keystore.get_key(&active_addr)?;
+    let upgraded = publish::upgrade(&client, signer, &path, upgrade_cap).await?;
+    publish::update_env_file(
+        &env_file,
+        &[("LATEST_PACKAGE_ID", upgraded.package_id.to_string())],
+    )?;
+    if let Some(alias) = alias {
+        let mut registry = CoinRegistry::load_or_default(&registry_path)?;
+        let mut coin = registry.get(&alias)?.clone();
+        coin.latest_package_id = Some(upgraded.package_id);
+        registry.insert(coin);
+        registry.save(&registry_path)?;
+    }
+
+    println!("Transaction:       {}", upgraded.digest);
+    println!("Previous package:  {}", upgraded.previous_package_id);
+    println!("Latest package:    {}", upgraded.package_id);
+    println!("Version:           {}", upgraded.version);
+    println!("Written to {}", env_file.display());
+    Ok(())
+}
+
+async fn run_proposal_command(
+    config: &AppConfig,
+    db: &mut ProposalDb,
+    command: ProposalCommand,
+) -> Result<()> {
+    let decision = |reject: bool| {
+        if reject {
+            Decision::Reject
+        } else {
+            Decision::Approve
+        }
+    };
+    let parse_signature = |signature: String| {
+        Signature::decode_base64(&signature).map_err(|e| anyhow!("Invalid signature: {e}"))
+    };
+    match command {
+        ProposalCommand::List { status } => {
+            let status = status.map(|status| status.parse()).transpose()?;
+            for proposal in db.list(status)? {
+                println!(
+                    "{}  {}  {}/{}  {:?}  {}",
+                    proposal.id,
+                    proposal.status,
+                    proposal.approvals(),
+                    proposal.required_approvals,
+                    proposal.command,
+                    proposal.maker
+                );
+            }
+        }
+        ProposalCommand::Show { id } => {
+            let proposal = db.get(id)?;
+            print!("{proposal}");
+            println!("Dry-run now:");
+            print!("{}", proposal::dry_run(&config.client, &proposal).await?);
+        }
+        ProposalCommand::Message { id, reject } => {
+            println!("{}", review_message(&db.get(id)?, decision(reject))?);
+        }
+        ProposalCommand::Sign { id, reject } => {
+            let message = review_message(&db.get(id)?, decision(reject))?;
+            let key = config.keystore.get_key(&config.active_address)?;
+            println!("{}", sign_review(&message, key).encode_base64());
+        }
+        ProposalCommand::Approve {
+            id,
+            checker,
+            signature,
+            comment,
+        } => {
+            let checker = SuiAddress::from_str(&checker)?;
+            let proposal = proposal::review(
+                config,
+                db,
+                id,
+                checker,
+                Decision::Approve,
+                comment,
+                &parse_signature(signature)?,
+            )?;
+            print!("{proposal}");
+        }
+        ProposalCommand::Reject {
+            id,
+            checker,
+            signature,
+            comment,
+        } => {
+            let checker = SuiAddress::from_str(&checker)?;
+            let proposal = proposal::review(
+                config,
+                db,
+                id,
+                checker,
+                Decision::Reject,
+                comment,
+                &parse_signature(signature)?,
+            )?;
+            print!("{proposal}");
+        }
+        ProposalCommand::Execute { id } => {
+            let resp = proposal::execute(config, db, id).await?;
+            print!("{}", db.get(id)?);
+            debug!("{:?}", resp);
+        }
+        ProposalCommand::Audit { id } => {
+            for entry in db.audit_trail(id)? {
+                println!("{entry}");
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_schedule_command(
+    config: &AppConfig,
+    db: &mut ScheduleDb,
+    command: ScheduleCommand,
+    context: &CommandContext,
+) -> Result<()> {
+    match command {
+        ScheduleCommand::DenyListAdd {
+            address,
+            trigger: trigger_args,
+            max_attempts,
+            ..
+        } => {
+            let command = AppCommand::DenyListAdd(SuiAddress::from_str(&address)?);
+            let trigger = trigger(&trigger_args)?;
+            print!(
+                "{}",
+                schedule::schedule(config, db, command, context, trigger, max_attempts)?
+            );
+        }
+        ScheduleCommand::DenyListRemove {
+            address,
+            trigger: trigger_args,
+            max_attempts,
+            ..
+        } => {
+            let command = AppCommand::DenyListRemove(SuiAddress::from_str(&address)?);
+            let trigger = trigger(&trigger_args)?;
+            print!(
+                "{}",
+                schedule::schedule(config, db, command, context, trigger, max_attempts)?
+            );
+        }
+        ScheduleCommand::List { status } => {
+            let status = status.map(|status| status.parse()).transpose()?;
+            for change in db.list(status)? {
+                println!(
+                    "{}  {}  {}  {:?}  {}/{} attempts",
+                    change.id,
+                    change.status,
+                    change.trigger,
+                    change.command,
+                    change.attempts.len(),
+                    change.max_attempts
+                );
+            }
+        }
+        ScheduleCommand::Show { id } => print!("{}", db.get(id)?),
+        ScheduleCommand::Cancel { id } => print!("{}", schedule::cancel(db, id)?),
+        ScheduleCommand::Run { follow, interval } => loop {
+            let now_ms = Utc::now().timestamp_millis() as u64;
+            match schedule::run_due(config, db, now_ms).await {
+                Ok(report) => print!("{report}"),
+                Err(e) if follow => error!("Run failed, retrying in {interval}s: {e:#}"),
+                Err(e) => return Err(e),
+            }
+            if !follow {
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        },
+    }
+    Ok(())
+}
+
+/// Adds the addresses a screening proposes to deny to the deny-list, or proposes the additions
+/// with `approvals` if given.
+async fn apply_screening(
+    config: &AppConfig,
+    report: &ScreenReport,
+    context: &CommandContext,
+    approvals: Option<u32>,
+) -> Result<()> {
+    let mut db = match approvals {
+        Some(approvals) => Some((ProposalDb::open(&proposals_db_path(None))?, approvals)),
+        None => None,
+    };
+    for addresses in report.deny_list_batches() {
+        let count = addresses.len();
+        let command = AppCommand::DenyListAddMany(addresses);
+        match &mut db {
+            Some((db, approvals)) => {
+                let proposal = proposal::propose(config, db, command, context, *approvals).await?;
+                print!("{proposal}");
+            }
+            None => {
+                let resp = tx_run::execute_command_with(command, context, config).await?;
+                println!("Denied {count} addresses in {}", resp.digest);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs a command that does not go through `cli_parse`.
+async fn run_standalone(global: GlobalArgs, command: StandaloneCommand) -> Result<()> {
+    match command {
+        StandaloneCommand::Supply => {
+            let registry = CoinRegistry::load_or_default(&registry_path(global.registry))?;
+            let (client, _, _) = load_wallet().await?;
+            for (alias, supply) in report::total_supply(&client, &registry).await? {
+                println!("{alias}: {supply}");
+            }
+            Ok(())
+        }
+        StandaloneCommand::DenyStatus { address } => {
+            let address = SuiAddress::from_str(&address)?;
+            let registry = CoinRegistry::load_or_default(&registry_path(global.registry))?;
+            let (client, _, _) = load_wallet().await?;
+            for status in report::deny_status(&client, &registry, address).await? {
+                println!("{status}");
+            }
+            Ok(())
+        }
+        StandaloneCommand::Audit {
+            log,
+            command: AuditCommand::Verify,
+        } => verify_audit_log(log),
+        StandaloneCommand::Indexer {
+            db,
+            command:
+                IndexerCommand::Sync {
+                    from_checkpoint,
+                    follow,
+                    interval,
+                },
+        } => {
+            let interval = Duration::from_secs(interval);
+            indexer_sync(&global, db, from_checkpoint, follow, interval).await
+        }
+        StandaloneCommand::Indexer {
+            db,
+            command: IndexerCommand::Query(query),
+        } => indexer_query(&global, db, &query),
+        StandaloneCommand::Publish {
+            path,
+            env_file,
+            alias,
+        } => publish_package(path, env_file, alias, registry_path(global.registry)).await,
+        StandaloneCommand::Upgrade {
+            path,
+            upgrade_cap,
+            env_file,
+            alias,
+        } => {
+            let registry_path = registry_path(global.registry);
+            upgrade_package(path, upgrade_cap, env_file, alias, registry_path).await
+        }
+        StandaloneCommand::NewCoin {
+            module,
+            decimals,
+            symbol,
+            name,
+            description,
+            icon_url,
+            allow_global_pause,
+            output,
+            publish,
+            env_file,
+            alias,
+        } => {
+            let params = CoinParams {
+                decimals,
+                symbol,
+                name,
+                description,
+                icon_url,
+                allow_global_pause,
+                module,
+            };
+            let output = output.unwrap_or_else(|| PathBuf::from("../move").join(&params.module));
+            let module_path = scaffold::new_coin_package(&params, &output)?;
+            println!("Generated {}", module_path.display());
+            if publish {
+                publish_package(output, env_file, alias, registry_path(global.registry)).await?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Polls the new checkpoints, prints their events, delivers their notifications and saves the
+/// pending deliveries and the cursor. `watcher` and `pending` are only updated once all of it
+/// succeeded, so a failed poll is retried from the same checkpoint.
+async fn watch_once(
+    config: &AppConfig,
+    watcher: &mut Watcher,
+    json: bool,
+    notifier: Option<&Notifier>,
+    pending: &mut PendingDeliveries,
+    pending_path: &Path,
+    cursor_file: Option<&Path>,
+) -> Result<()> {
+    let mut next = watcher.clone();
+    let mut next_pending = pending.clone();
+    let events = next.poll(&config.client).await?;
+    for event in &events {
+        if json {
+            println!("{}", serde_json::to_string(event)?);
+        } else {
+            println!("{event}");
+        }
+    }
+    if let Some(notifier) = notifier {
+        let notifications = webhook::notifications(&config.type_tag, &events);
+        notifier.deliver(&mut next_pending, notifications).await?;
+        // Saved before the cursor, so no undelivered event is skipped
+        next_pending.save(pending_path)?;
+    }
+    if let (Some(cursor_file), Some(cursor)) = (cursor_file, next.cursor) {
+        watch::save_cursor(cursor_file, cursor)?;
+    }
+    *watcher = next;
+    *pending = next_pending;
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
@@ -141,10 +1557,201 @@ async fn main() -> Result<()> {
         .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
         .init();
 
-    let (config, command) = cli_parse().await?;
-    let resp = tx_run::execute_command(command, config).await?;
-
-    debug!("{:?}", resp);
+    let Cli { global, command } = Cli::parse();
+    let command = match command {
+        CliCommand::Coin(command) => command,
+        CliCommand::Standalone(command) => return run_standalone(global, command).await,
+    };
+    let context = command_context(&global, &command);
+    let (config, action) = cli_parse(global, command).await?;
+    match action {
+        Action::Execute(command) => {
+            let resp = tx_run::execute_command_with(command, &context, &config).await?;
+            debug!("{:?}", resp);
+        }
+        Action::Preflight(command) => {
+            let report = preflight::run(
+                &config.client,
+                config.active_address,
+                &config.type_tag,
+                &config.registry,
+                &command,
+            )
+            .await?;
+            print!("{report}");
+            if !report.passed() {
+                std::process::exit(1);
+            }
+        }
+        Action::InspectCoin(coin_id) => {
+            let coin = tx_run::inspect_coin(&config.client, coin_id).await?;
+            if coin.coin_type != config.type_tag {
+                println!("WARNING: not a coin of {}", config.type_tag);
+            }
+            println!("Coin:                 {}", coin.object_ref.0);
+            println!("Type:                 {}", coin.coin_type);
+            println!("Value:                {}", coin.value);
+            println!("Owner:                {}", coin.owner);
+            println!("Version:              {}", coin.object_ref.1);
+            println!("Previous transaction: {}", coin.previous_transaction);
+        }
+        Action::History => {
+            for entry in
+                history::history(&config.client, &config.type_tag, &config.packages).await?
+            {
+                println!("{entry}");
+            }
+        }
+        Action::Watch {
+            interval,
+            large_transfer,
+            cursor_file,
+            json,
+            webhooks,
+            webhooks_pending,
+        } => {
+            let mut watcher = Watcher::new(config.type_tag.clone(), large_transfer);
+            if let Some(cursor_file) = &cursor_file {
+                watcher.cursor = watch::load_cursor(cursor_file)?;
+            }
+            let notifier = webhooks.map(Notifier::new);
+            let mut pending = PendingDeliveries::load_or_default(&webhooks_pending)?;
+            loop {
+                if let Err(e) = watch_once(
+                    &config,
+                    &mut watcher,
+                    json,
+                    notifier.as_ref(),
+                    &mut pending,
+                    &webhooks_pending,
+                    cursor_file.as_deref(),
+                )
+                .await
+                {
+                    error!("Watch failed, retrying in {}s: {e:#}", interval.as_secs());
+                }
+                tokio::time::sleep(interval).await;
+            }
+        }
+        Action::Serve { addr, token } => server::serve(config, token, addr).await?,
+        Action::Propose {
+            command,
+            approvals,
+            db,
+        } => {
+            let mut db = ProposalDb::open(&db)?;
+            let proposal =
+                proposal::propose(&config, &mut db, command, &context, approvals).await?;
+            print!("{proposal}");
+        }
+        Action::Proposal { db, command } => {
+            let mut db = ProposalDb::open(&db)?;
+            run_proposal_command(&config, &mut db, command).await?;
+        }
+        Action::Schedule { db, command } => {
+            let mut db = ScheduleDb::open(&db)?;
+            run_schedule_command(&config, &mut db, command, &context).await?;
+        }
+        Action::Screen {
+            list,
+            indexer_db,
+            since_ms,
+            apply,
+            propose,
+        } => {
+            let counterparties: Option<BTreeMap<SuiAddress, u64>> = match indexer_db {
+                Some(db) => {
+                    let db = IndexerDb::open(&db, &config.type_tag)?;
+                    Some(db.counterparties(since_ms)?.into_iter().collect())
+                }
+                None => None,
+            };
+            let report = screen::screen(
+                &config.client,
+                &config.type_tag,
+                list,
+                counterparties.as_ref(),
+            )
+            .await?;
+            print!("{report}");
+            if apply {
+                apply_screening(&config, &report, &context, propose).await?;
+            }
+        }
+        Action::Enforce { address, evidence } => {
+            let report = enforce::enforce(&config, address, &context).await?;
+            print!("{report}");
+            if let Some(path) = evidence {
+                report.save(&path)?;
+                println!("Evidence written to {}", path.display());
+            }
+        }
+        Action::CapsShow => {
+            let report = caps::show(&config.client, &config.type_tag, &config.registry).await?;
+            print!("{report}");
+        }
+        Action::CapsTransfer {
+            to,
+            preflight,
+            registry_path,
+        } => {
+            if preflight {
+                let report = preflight::run(
+                    &config.client,
+                    config.active_address,
+                    &config.type_tag,
+                    &config.registry,
+                    &AppCommand::TransferCaps(to),
+                )
+                .await?;
+                print!("{report}");
+                if !report.passed() {
+                    std::process::exit(1);
+                }
+                let dry_run = caps::dry_run(&config, to, &context).await?;
+                print!("{dry_run}");
+                if dry_run.error.is_some() {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+            let transfer = caps::transfer(&config, to, &context, &registry_path).await?;
+            print!("{transfer}");
+            if transfer.recorded {
+                println!("Cap owners recorded in {}", registry_path.display());
+            }
+        }
+        Action::EmergencyFreeze {
+            addresses,
+            cold_address,
+            preflight,
+        } => {
+            let plan = emergency::plan(&config, &addresses, cold_address).await?;
+            print!("{plan}");
+            if preflight {
+                let report = preflight::run(
+                    &config.client,
+                    config.active_address,
+                    &config.type_tag,
+                    &config.registry,
+                    &plan.command(),
+                )
+                .await?;
+                print!("{report}");
+                if !report.passed() {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+            print!("Type `{}` to execute: ", plan.confirmation_phrase());
+            std::io::Write::flush(&mut std::io::stdout())?;
+            let mut typed = String::new();
+            std::io::stdin().read_line(&mut typed)?;
+            plan.confirm(&typed)?;
+            let report = emergency::freeze(&config, plan, &context).await?;
+            print!("{report}");
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,418 @@
+mod ledger;
+
+pub use ledger::MintLedger;
+
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeZone, Utc};
+use move_core_types::language_storage::TypeTag;
+use serde::{Deserialize, Serialize};
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::digests::TransactionDigest;
+
+use crate::tx_run::{AppCommand, CommandContext};
+
+pub const DEFAULT_POLICY_FILE: &str = "policy.json";
+pub const DEFAULT_POLICY_DB: &str = "policy.db";
+
+/// A time of day in UTC, written `HH:MM`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay(pub NaiveTime);
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        NaiveTime::parse_from_str(&s, "%H:%M")
+            .map(TimeOfDay)
+            .map_err(|e| anyhow!("Invalid time of day {s}, expected HH:MM: {e}"))
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(time: TimeOfDay) -> Self {
+        time.0.format("%H:%M").to_string()
+    }
+}
+
+/// The hours commands may be signed in, from `start` included to `end` excluded.
+/// A window whose end is before its start spans midnight.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimeWindow {
+    pub start: TimeOfDay,
+    pub end: TimeOfDay,
+}
+
+impl TimeWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        let (start, end) = (self.start.0, self.end.0);
+        if start <= end {
+            start <= time && time < end
+        } else {
+            start <= time || time < end
+        }
+    }
+}
+
+/// Mint caps of one operator, replacing the default caps of the policy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OperatorCaps {
+    pub operator: SuiAddress,
+    #[serde(default)]
+    pub daily_mint_cap: Option<u64>,
+    #[serde(default)]
+    pub monthly_mint_cap: Option<u64>,
+}
+
+/// The kinds of command a policy rule may name, as written in the policy file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CommandKind {
+    /// Adding addresses to the deny-list, of the coin or of every coin in the registry
+    DenyListAdd,
+    DenyListRemove,
+    Mint,
+    Transfer,
+    Burn,
+    Pause,
+    Unpause,
+    EmergencyFreeze,
+    TransferCaps,
+}
+
+impl CommandKind {
+    pub fn of(command: &AppCommand) -> Self {
+        match command {
+            AppCommand::DenyListAdd(_)
+            | AppCommand::DenyListAddAll(_)
+            | AppCommand::DenyListAddMany(_) => CommandKind::DenyListAdd,
+            AppCommand::DenyListRemove(_) => CommandKind::DenyListRemove,
+            AppCommand::MintAndTransfer(..) => CommandKind::Mint,
+            AppCommand::Transfer(..) => CommandKind::Transfer,
+            AppCommand::Burn(_) => CommandKind::Burn,
+            AppCommand::Pause => CommandKind::Pause,
+            AppCommand::Unpause => CommandKind::Unpause,
+            AppCommand::EmergencyFreeze { .. } => CommandKind::EmergencyFreeze,
+            AppCommand::TransferCaps(_) => CommandKind::TransferCaps,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CommandKind::DenyListAdd => "deny-list-add",
+            CommandKind::DenyListRemove => "deny-list-remove",
+            CommandKind::Mint => "mint",
+            CommandKind::Transfer => "transfer",
+            CommandKind::Burn => "burn",
+            CommandKind::Pause => "pause",
+            CommandKind::Unpause => "unpause",
+            CommandKind::EmergencyFreeze => "emergency-freeze",
+            CommandKind::TransferCaps => "transfer-caps",
+        }
+    }
+}
+
+impl fmt::Display for CommandKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Rules checked before a command is signed. Every rule is optional; the default policy allows
+/// everything. Unknown fields are rejected, so a misspelled rule is not silently ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// The largest amount a single transaction may mint
+    #[serde(default)]
+    pub max_mint: Option<u64>,
+    /// What each operator may mint per UTC day
+    #[serde(default)]
+    pub daily_mint_cap: Option<u64>,
+    /// What each operator may mint per UTC month
+    #[serde(default)]
+    pub monthly_mint_cap: Option<u64>,
+    /// Caps of specific operators, instead of the ones above
+    #[serde(default)]
+    pub operators: Vec<OperatorCaps>,
+    /// If set, mints may only go to these addresses
+    #[serde(default)]
+    pub mint_recipients: Option<Vec<SuiAddress>>,
+    /// Removing an address from the deny-list needs `--ticket`
+    #[serde(default)]
+    pub deny_list_remove_requires_ticket: bool,
+    /// If not empty, commands may only be signed within one of these windows, except the
+    /// emergency freeze and the global pause an incident may need at any hour
+    #[serde(default)]
+    pub windows: Vec<TimeWindow>,
+    /// The only addresses that may review proposals. Without them nothing can be proposed
+    #[serde(default)]
+    pub checkers: Vec<SuiAddress>,
+    /// Commands that may only be executed as approved proposals
+    #[serde(default)]
+    pub require_proposal: Vec<CommandKind>,
+}
+
+impl Policy {
+    pub fn load(path: &Path) -> Result<Self> {
+        let policy: Policy = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| anyhow!("Invalid policy {}: {e}", path.display()))?;
+        for (i, caps) in policy.operators.iter().enumerate() {
+            if policy.operators[..i]
+                .iter()
+                .any(|other| other.operator == caps.operator)
+            {
+                return Err(anyhow!(
+                    "Invalid policy {}: operator {} is listed twice",
+                    path.display(),
+                    caps.operator
+                ));
+            }
+        }
+        if !policy.require_proposal.is_empty() && policy.checkers.is_empty() {
+            return Err(anyhow!(
+                "Invalid policy {}: require_proposal needs checkers to approve the proposals",
+                path.display()
+            ));
+        }
+        Ok(policy)
+    }
+
+    /// The daily and monthly mint caps of `operator`.
+    pub fn mint_caps(&self, operator: SuiAddress) -> (Option<u64>, Option<u64>) {
+        match self.operators.iter().find(|caps| caps.operator == operator) {
+            Some(caps) => (caps.daily_mint_cap, caps.monthly_mint_cap),
+            None => (self.daily_mint_cap, self.monthly_mint_cap),
+        }
+    }
+}
+
+/// A command the policy does not allow. Returned wrapped in `anyhow::Error`, so callers can
+/// tell it apart from other failures with `downcast_ref`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    OutsideWindow {
+        time: NaiveTime,
+    },
+    MintTooLarge {
+        amount: u64,
+        max: u64,
+    },
+    RecipientNotAllowed {
+        recipient: SuiAddress,
+    },
+    DailyMintCap {
+        operator: SuiAddress,
+        minted: u128,
+        amount: u64,
+        cap: u64,
+    },
+    MonthlyMintCap {
+        operator: SuiAddress,
+        minted: u128,
+        amount: u64,
+        cap: u64,
+    },
+    TicketRequired,
+    ProposalRequired {
+        kind: CommandKind,
+    },
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::OutsideWindow { time } => write!(
+                f,
+                "Commands may not be signed at {} UTC, outside the policy's windows",
+                time.format("%H:%M")
+            ),
+            PolicyViolation::MintTooLarge { amount, max } => {
+                write!(f, "Minting {amount} exceeds the maximum of {max} per transaction")
+            }
+            PolicyViolation::RecipientNotAllowed { recipient } => {
+                write!(f, "{recipient} is not an allowed mint recipient")
+            }
+            PolicyViolation::DailyMintCap {
+                operator,
+                minted,
+                amount,
+                cap,
+            } => write!(
+                f,
+                "{operator} minted {minted} today, minting {amount} more exceeds the daily cap of {cap}"
+            ),
+            PolicyViolation::MonthlyMintCap {
+                operator,
+                minted,
+                amount,
+                cap,
+            } => write!(
+                f,
+                "{operator} minted {minted} this month, minting {amount} more exceeds the monthly cap of {cap}"
+            ),
+            PolicyViolation::TicketRequired => {
+                write!(f, "Removing an address from the deny-list needs a ticket")
+            }
+            PolicyViolation::ProposalRequired { kind } => write!(
+                f,
+                "The policy requires {kind} commands to be proposed and approved, use --propose"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+/// A policy with the ledger of past mints its caps are checked against.
+pub struct PolicyEngine {
+    pub policy: Policy,
+    ledger: MintLedger,
+}
+
+impl PolicyEngine {
+    pub fn new(policy: Policy, ledger: MintLedger) -> Self {
+        PolicyEngine { policy, ledger }
+    }
+
+    /// Checks that `operator` may sign `command` at `now`. Fails with a [`PolicyViolation`]
+    /// for the first rule broken.
+    pub fn check(
+        &self,
+        type_tag: &TypeTag,
+        operator: SuiAddress,
+        command: &AppCommand,
+        context: &CommandContext,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let policy = &self.policy;
+        let time = now.time();
+        let emergency = matches!(
+            command,
+            AppCommand::EmergencyFreeze { .. } | AppCommand::Pause
+        );
+        if !emergency
+            && !policy.windows.is_empty()
+            && !policy.windows.iter().any(|w| w.contains(time))
+        {
+            return Err(PolicyViolation::OutsideWindow { time }.into());
+        }
+        match *command {
+            AppCommand::MintAndTransfer(amount, recipient) => {
+                self.check_mint(type_tag, operator, amount, recipient, now)
+            }
+            AppCommand::DenyListRemove(_)
+                if policy.deny_list_remove_requires_ticket
+                    && context
+                        .ticket
+                        .as_deref()
+                        .map_or(true, |t| t.trim().is_empty()) =>
+            {
+                Err(PolicyViolation::TicketRequired.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that `command` may be executed without a proposal. Fails with
+    /// [`PolicyViolation::ProposalRequired`] if the policy requires one.
+    pub fn check_unproposed(&self, command: &AppCommand) -> Result<()> {
+        let kind = CommandKind::of(command);
+        if self.policy.require_proposal.contains(&kind) {
+            return Err(PolicyViolation::ProposalRequired { kind }.into());
+        }
+        Ok(())
+    }
+
+    fn check_mint(
+        &self,
+        type_tag: &TypeTag,
+        operator: SuiAddress,
+        amount: u64,
+        recipient: SuiAddress,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let policy = &self.policy;
+        if let Some(max) = policy.max_mint {
+            if amount > max {
+                return Err(PolicyViolation::MintTooLarge { amount, max }.into());
+            }
+        }
+        if let Some(recipients) = &policy.mint_recipients {
+            if !recipients.contains(&recipient) {
+                return Err(PolicyViolation::RecipientNotAllowed { recipient }.into());
+            }
+        }
+        let coin_type = type_tag.to_string();
+        let (daily, monthly) = policy.mint_caps(operator);
+        if let Some(cap) = daily {
+            let minted = self
+                .ledger
+                .minted_since(&coin_type, operator, start_of_day(now))?;
+            if minted + amount as u128 > cap as u128 {
+                return Err(PolicyViolation::DailyMintCap {
+                    operator,
+                    minted,
+                    amount,
+                    cap,
+                }
+                .into());
+            }
+        }
+        if let Some(cap) = monthly {
+            let minted = self
+                .ledger
+                .minted_since(&coin_type, operator, start_of_month(now))?;
+            if minted + amount as u128 > cap as u128 {
+                return Err(PolicyViolation::MonthlyMintCap {
+                    operator,
+                    minted,
+                    amount,
+                    cap,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Records what an executed `command` counts against the caps of `operator`.
+    pub fn record(
+        &self,
+        type_tag: &TypeTag,
+        operator: SuiAddress,
+        command: &AppCommand,
+        digest: TransactionDigest,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        if let AppCommand::MintAndTransfer(amount, _) = *command {
+            self.ledger.record(
+                &type_tag.to_string(),
+                operator,
+                amount,
+                digest,
+                now.timestamp_millis() as u64,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn start_of_day(now: DateTime<Utc>) -> u64 {
+    start_of(now.date_naive())
+}
+
+fn start_of_month(now: DateTime<Utc>) -> u64 {
+    let first = NaiveDate::from_ymd_opt(now.year(), now.month(), 1).expect("valid date");
+    start_of(first)
+}
+
+fn start_of(date: NaiveDate) -> u64 {
+    Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN))
+        .timestamp_millis() as u64
+}
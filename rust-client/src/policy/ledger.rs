@@ -0,0 +1,87 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::digests::TransactionDigest;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS mints (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    coin_type TEXT NOT NULL,
+    operator TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    digest TEXT NOT NULL,
+    timestamp_ms INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS mints_by_operator ON mints (coin_type, operator, timestamp_ms);
+";
+
+/// SQLite record of the mints executed by each operator, for the caps of the policy.
+/// Amounts are stored as text, as they do not fit SQLite integers.
+pub struct MintLedger {
+    // Shared by the handlers of `serve`, which need the ledger to be `Sync`
+    conn: Mutex<Connection>,
+}
+
+impl MintLedger {
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(MintLedger {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    pub fn record(
+        &self,
+        coin_type: &str,
+        operator: SuiAddress,
+        amount: u64,
+        digest: TransactionDigest,
+        timestamp_ms: u64,
+    ) -> Result<()> {
+        self.conn.lock().unwrap().execute(
+            "INSERT INTO mints (coin_type, operator, amount, digest, timestamp_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                coin_type,
+                operator.to_string(),
+                amount.to_string(),
+                digest.to_string(),
+                timestamp_ms
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The total `operator` minted of `coin_type` since `since_ms`.
+    pub fn minted_since(
+        &self,
+        coin_type: &str,
+        operator: SuiAddress,
+        since_ms: u64,
+    ) -> Result<u128> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT amount FROM mints
+             WHERE coin_type = ?1 AND operator = ?2 AND timestamp_ms >= ?3",
+        )?;
+        let rows = stmt.query_map(params![coin_type, operator.to_string(), since_ms], |row| {
+            row.get::<_, String>(0)
+        })?;
+        let mut minted = 0;
+        for amount in rows {
+            minted += amount?.parse::<u64>()? as u128;
+        }
+        Ok(minted)
+    }
+}
@@ -0,0 +1,574 @@
+mod db;
+
+pub use db::ProposalDb;
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use shared_crypto::intent::{Intent, IntentMessage, PersonalMessage};
+use sui_keys::keystore::AccountKeystore;
+use sui_sdk::rpc_types::{BalanceChange, ObjectChange, SuiTransactionBlockResponse};
+use sui_sdk::types::base_types::{SequenceNumber, SuiAddress};
+use sui_sdk::types::crypto::{Signature, SuiKeyPair, SuiSignature, ToFromBytes};
+use sui_sdk::types::digests::{ObjectDigest, TransactionDigest};
+use sui_sdk::types::transaction::{
+    CallArg, ObjectArg, ProgrammableTransaction, TransactionData, TransactionDataAPI,
+    TransactionKind,
+};
+
+use crate::backend::{ChainBackend, DryRunOutcome};
+use crate::tx_run::{
+    audit_command, build_command, check_policy, execution_error, record_policy,
+    sign_and_execute_data, transaction_data, AppCommand, AppConfig, CommandContext,
+};
+
+pub const DEFAULT_PROPOSALS_DB: &str = "proposals.db";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalStatus {
+    /// Waiting for approvals
+    Pending,
+    /// Approved by enough checkers, ready to execute
+    Approved,
+    Rejected,
+    Executed,
+    /// Executed, but the transaction failed
+    Failed,
+}
+
+impl ProposalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProposalStatus::Pending => "pending",
+            ProposalStatus::Approved => "approved",
+            ProposalStatus::Rejected => "rejected",
+            ProposalStatus::Executed => "executed",
+            ProposalStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for ProposalStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "pending" => ProposalStatus::Pending,
+            "approved" => ProposalStatus::Approved,
+            "rejected" => ProposalStatus::Rejected,
+            "executed" => ProposalStatus::Executed,
+            "failed" => ProposalStatus::Failed,
+            _ => return Err(anyhow!("Unknown proposal status {s}")),
+        })
+    }
+}
+
+impl fmt::Display for ProposalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Approve,
+    Reject,
+}
+
+impl Decision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Decision::Approve => "approve",
+            Decision::Reject => "reject",
+        }
+    }
+}
+
+impl FromStr for Decision {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "approve" => Ok(Decision::Approve),
+            "reject" => Ok(Decision::Reject),
+            _ => Err(anyhow!("Unknown decision {s}")),
+        }
+    }
+}
+
+/// What the dry-run of a proposal's transaction would change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DryRunSummary {
+    pub error: Option<String>,
+    pub gas_used: i64,
+    pub balance_changes: Vec<BalanceChange>,
+    pub object_changes: Vec<ObjectChange>,
+}
+
+impl From<DryRunOutcome> for DryRunSummary {
+    fn from(outcome: DryRunOutcome) -> Self {
+        DryRunSummary {
+            error: outcome.status.err(),
+            gas_used: outcome.gas_used,
+            balance_changes: outcome.balance_changes,
+            object_changes: outcome.object_changes,
+        }
+    }
+}
+
+impl fmt::Display for DryRunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            Some(error) => writeln!(f, "Status:  FAILED {error}")?,
+            None => writeln!(f, "Status:  success")?,
+        }
+        writeln!(f, "Gas:     {}", self.gas_used)?;
+        for change in &self.balance_changes {
+            writeln!(
+                f,
+                "Balance: {} {:+} {}",
+                change.owner, change.amount, change.coin_type
+            )?;
+        }
+        for change in &self.object_changes {
+            let (kind, object_id, object_type) = match change {
+                ObjectChange::Created {
+                    object_id,
+                    object_type,
+                    ..
+                } => ("created", object_id, object_type.to_string()),
+                ObjectChange::Mutated {
+                    object_id,
+                    object_type,
+                    ..
+                } => ("mutated", object_id, object_type.to_string()),
+                ObjectChange::Transferred {
+                    object_id,
+                    object_type,
+                    ..
+                } => ("transferred", object_id, object_type.to_string()),
+                ObjectChange::Deleted {
+                    object_id,
+                    object_type,
+                    ..
+                } => ("deleted", object_id, object_type.to_string()),
+                ObjectChange::Wrapped {
+                    object_id,
+                    object_type,
+                    ..
+                } => ("wrapped", object_id, object_type.to_string()),
+                ObjectChange::Published { package_id, .. } => {
+                    ("published", package_id, "package".to_string())
+                }
+            };
+            writeln!(f, "Object:  {kind} {object_id} {object_type}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A checker's decision, signed with the checker's key.
+#[derive(Debug, Clone)]
+pub struct Review {
+    pub checker: SuiAddress,
+    pub decision: Decision,
+    pub comment: Option<String>,
+    /// Signature of [`review_message`] as a personal message
+    pub signature: Vec<u8>,
+    pub timestamp_ms: u64,
+}
+
+/// A privileged command waiting for, or done with, four-eyes review.
+#[derive(Debug, Clone)]
+pub struct Proposal {
+    pub id: u64,
+    pub coin_type: String,
+    pub command: AppCommand,
+    /// The ticket, reason and case the command was proposed with, used again to execute it
+    pub context: CommandContext,
+    /// The transaction dry-run when proposed. Its gas coin and caps may be used by other
+    /// transactions before the proposal is approved, so it is built again to execute, and must
+    /// then repeat its inputs and commands.
+    pub tx_data: TransactionData,
+    pub maker: SuiAddress,
+    pub required_approvals: u32,
+    /// The dry-run made when the proposal was created
+    pub dry_run: DryRunSummary,
+    pub status: ProposalStatus,
+    pub executed_digest: Option<TransactionDigest>,
+    pub created_ms: u64,
+    pub reviews: Vec<Review>,
+}
+
+impl Proposal {
+    pub fn tx_digest(&self) -> TransactionDigest {
+        self.tx_data.digest()
+    }
+
+    /// What the checkers sign: the hex SHA-256 of the coin, the maker, the command, its context
+    /// and the dry-run they reviewed.
+    pub fn review_digest(&self) -> Result<String> {
+        let reviewed = (
+            &self.coin_type,
+            self.maker,
+            &self.command,
+            &self.context,
+            &self.dry_run,
+        );
+        Ok(hex::encode(Sha256::digest(serde_json::to_vec(&reviewed)?)))
+    }
+
+    pub fn approvals(&self) -> usize {
+        self.reviews
+            .iter()
+            .filter(|review| review.decision == Decision::Approve)
+            .count()
+    }
+}
+
+impl fmt::Display for Proposal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Proposal {}  {}", self.id, self.status)?;
+        writeln!(f, "Coin:        {}", self.coin_type)?;
+        writeln!(f, "Command:     {:?}", self.command)?;
+        for (label, value) in [
+            ("Ticket:", &self.context.ticket),
+            ("Reason:", &self.context.reason),
+            ("Case:", &self.context.case),
+        ] {
+            if let Some(value) = value {
+                writeln!(f, "{label:<12} {value}")?;
+            }
+        }
+        writeln!(f, "Maker:       {}", self.maker)?;
+        writeln!(f, "Transaction: {}", self.tx_digest())?;
+        writeln!(
+            f,
+            "Approvals:   {}/{}",
+            self.approvals(),
+            self.required_approvals
+        )?;
+        for review in &self.reviews {
+            writeln!(
+                f,
+                "  {} {}{}",
+                review.decision.as_str(),
+                review.checker,
+                review
+                    .comment
+                    .as_ref()
+                    .map(|comment| format!(": {comment}"))
+                    .unwrap_or_default()
+            )?;
+        }
+        if let Some(digest) = self.executed_digest {
+            writeln!(f, "Executed:    {digest}")?;
+        }
+        write!(f, "{}", self.dry_run)
+    }
+}
+
+/// An entry of the audit trail: a proposal was created, reviewed, executed or failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    pub timestamp_ms: u64,
+    pub proposal_id: u64,
+    pub actor: SuiAddress,
+    pub event: String,
+    pub detail: Option<String>,
+}
+
+impl fmt::Display for AuditEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}  proposal {}  {}  {}",
+            self.timestamp_ms, self.proposal_id, self.actor, self.event
+        )?;
+        if let Some(detail) = &self.detail {
+            write!(f, "  {detail}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The message a checker signs: the decision, the proposal and its [`Proposal::review_digest`].
+pub fn review_message(proposal: &Proposal, decision: Decision) -> Result<String> {
+    Ok(format!(
+        "{} proposal {} reviewed {}",
+        decision.as_str(),
+        proposal.id,
+        proposal.review_digest()?
+    ))
+}
+
+fn personal_message(message: String) -> IntentMessage<PersonalMessage> {
+    IntentMessage::new(
+        Intent::personal_message(),
+        PersonalMessage {
+            message: message.into_bytes(),
+        },
+    )
+}
+
+/// The addresses the policy allows to review proposals.
+fn checkers<B: ChainBackend>(config: &AppConfig<B>) -> Result<&[SuiAddress]> {
+    config
+        .policy
+        .as_ref()
+        .map(|engine| engine.policy.checkers.as_slice())
+        .filter(|checkers| !checkers.is_empty())
+        .ok_or(anyhow!(
+            "No checkers may review proposals: list them in the `checkers` of the policy"
+        ))
+}
+
+/// Checks `command` against the policy, runs its pre-flight checks, builds and dry-runs its
+/// transaction, and stores it as a pending proposal of the active address.
+pub async fn propose<B: ChainBackend>(
+    config: &AppConfig<B>,
+    db: &mut ProposalDb,
+    command: AppCommand,
+    context: &CommandContext,
+    required_approvals: u32,
+) -> Result<Proposal> {
+    if required_approvals == 0 {
+        return Err(anyhow!("A proposal needs at least one approval"));
+    }
+    let checkers = checkers(config)?;
+    let reviewers = checkers
+        .iter()
+        .filter(|checker| **checker != config.active_address)
+        .count();
+    if required_approvals as usize > reviewers {
+        return Err(anyhow!(
+            "{required_approvals} approvals required, but the policy lists {reviewers} checkers \
+             other than {}",
+            config.active_address
+        ));
+    }
+    check_policy(&command, context, config)?;
+    let pt = build_command(&command, context, config).await?;
+    let tx_data = transaction_data(&config.client, config.active_address, pt, None).await?;
+    let dry_run = DryRunSummary::from(config.client.dry_run(tx_data.clone()).await?);
+    if let Some(error) = &dry_run.error {
+        return Err(anyhow!("Dry-run failed: {error}"));
+    }
+    let id = db.insert(
+        &config.type_tag.to_string(),
+        &command,
+        context,
+        &tx_data,
+        required_approvals,
+        &dry_run,
+    )?;
+    db.get(id)
+}
+
+/// Dry-runs the transaction of a proposal again, to review its effects on the current state.
+pub async fn dry_run<B: ChainBackend>(client: &B, proposal: &Proposal) -> Result<DryRunSummary> {
+    Ok(client.dry_run(proposal.tx_data.clone()).await?.into())
+}
+
+/// Signs the [`review_message`] of a decision as a personal message, as the checker's wallet
+/// would.
+pub fn sign_review(message: &str, key: &SuiKeyPair) -> Signature {
+    Signature::new_secure(&personal_message(message.to_string()), key)
+}
+
+/// Checks that `signature` is the signature by `checker` of its `decision` on `proposal`.
+fn verify_review(
+    proposal: &Proposal,
+    checker: SuiAddress,
+    decision: Decision,
+    signature: &[u8],
+) -> Result<()> {
+    let message = personal_message(review_message(proposal, decision)?);
+    let signature = Signature::from_bytes(signature).map_err(|e| anyhow!(e))?;
+    signature
+        .verify_secure(&message, checker, signature.scheme())
+        .map_err(|e| anyhow!(e))
+}
+
+/// Records the decision of `checker`, with its signature of the [`review_message`] made by the
+/// checker on its own machine, which is verified first. Only the checkers of the policy review,
+/// the maker cannot review its own proposal, and each checker reviews once. A single rejection
+/// rejects the proposal.
+pub fn review<B: ChainBackend>(
+    config: &AppConfig<B>,
+    db: &mut ProposalDb,
+    id: u64,
+    checker: SuiAddress,
+    decision: Decision,
+    comment: Option<String>,
+    signature: &Signature,
+) -> Result<Proposal> {
+    if !checkers(config)?.contains(&checker) {
+        return Err(anyhow!("{checker} is not a checker of the policy"));
+    }
+    let proposal = db.get(id)?;
+    if proposal.status != ProposalStatus::Pending {
+        return Err(anyhow!("Proposal {id} is {}, not pending", proposal.status));
+    }
+    if checker == proposal.maker {
+        return Err(anyhow!("{checker} made proposal {id} and cannot review it"));
+    }
+    if proposal
+        .reviews
+        .iter()
+        .any(|review| review.checker == checker)
+    {
+        return Err(anyhow!("{checker} already reviewed proposal {id}"));
+    }
+    verify_review(&proposal, checker, decision, signature.as_ref()).map_err(|e| {
+        anyhow!(
+            "The signature is not {checker}'s to {} proposal {id}: {e}",
+            decision.as_str()
+        )
+    })?;
+    db.insert_review(
+        id,
+        checker,
+        decision,
+        comment.as_deref(),
+        signature.as_ref(),
+    )?;
+    db.get(id)
+}
+
+/// The approvals of `proposal` by checkers of the policy other than the maker, each checked
+/// against its signature. A signature that does not verify is an error, as the database was
+/// tampered with.
+pub fn verified_approvals(proposal: &Proposal, checkers: &[SuiAddress]) -> Result<usize> {
+    let mut approvals = 0;
+    for review in &proposal.reviews {
+        if review.decision != Decision::Approve
+            || review.checker == proposal.maker
+            || !checkers.contains(&review.checker)
+        {
+            continue;
+        }
+        if let Err(e) = verify_review(proposal, review.checker, review.decision, &review.signature)
+        {
+            return Err(anyhow!(
+                "The approval of proposal {} by {} does not verify: {e}",
+                proposal.id,
+                review.checker
+            ));
+        }
+        approvals += 1;
+    }
+    Ok(approvals)
+}
+
+/// The inputs and commands of the programmable transaction of `tx_data`, which the transaction
+/// built to execute must repeat. Owned objects are kept by ID only: their version changes when
+/// another transaction uses them.
+fn reviewed_transaction(tx_data: &TransactionData) -> Result<ProgrammableTransaction> {
+    let TransactionKind::ProgrammableTransaction(pt) = tx_data.kind() else {
+        return Err(anyhow!("Only programmable transactions are proposed"));
+    };
+    let mut pt = pt.clone();
+    for input in &mut pt.inputs {
+        if let CallArg::Object(ObjectArg::ImmOrOwnedObject((_, version, digest))) = input {
+            *version = SequenceNumber::new();
+            *digest = ObjectDigest::MIN;
+        }
+    }
+    Ok(pt)
+}
+
+/// Checks the approvals of `proposal`, checks its command against the policy again and builds
+/// it again with its context, and with the current gas coin and caps of the maker. It must call
+/// the same functions with the same arguments and objects as the reviewed transaction, and its
+/// dry-run must succeed.
+async fn execution_data<B: ChainBackend>(
+    config: &AppConfig<B>,
+    proposal: &Proposal,
+) -> Result<TransactionData> {
+    let context = &proposal.context;
+    let id = proposal.id;
+    let approvals = verified_approvals(proposal, checkers(config)?)?;
+    if approvals < proposal.required_approvals as usize {
+        return Err(anyhow!(
+            "Proposal {id} has {approvals} verified approvals, {} required",
+            proposal.required_approvals
+        ));
+    }
+    // The maker may have minted since, or the window closed
+    check_policy(&proposal.command, context, config)?;
+    let pt = build_command(&proposal.command, context, config).await?;
+    let tx_data = transaction_data(&config.client, proposal.maker, pt, None).await?;
+    // Eg. another coin, or the coin's package upgraded since
+    if reviewed_transaction(&tx_data)? != reviewed_transaction(&proposal.tx_data)? {
+        return Err(anyhow!(
+            "Proposal {id} no longer builds the reviewed transaction, propose it again"
+        ));
+    }
+    let dry_run = DryRunSummary::from(config.client.dry_run(tx_data.clone()).await?);
+    if let Some(error) = &dry_run.error {
+        return Err(anyhow!("Dry-run failed: {error}"));
+    }
+    Ok(tx_data)
+}
+
+/// Signs and executes an approved proposal with the key of its maker, which must be the active
+/// address. The approvals are verified and the transaction is built again, so a gas coin or cap
+/// used since the proposal does not fail it. An executed mint counts against the policy caps of
+/// the maker.
+pub async fn execute<B: ChainBackend>(
+    config: &AppConfig<B>,
+    db: &mut ProposalDb,
+    id: u64,
+) -> Result<SuiTransactionBlockResponse> {
+    let proposal = db.get(id)?;
+    if proposal.status != ProposalStatus::Approved {
+        return Err(anyhow!(
+            "Proposal {id} is {}, not approved",
+            proposal.status
+        ));
+    }
+    if config.active_address != proposal.maker {
+        return Err(anyhow!(
+            "Proposal {id} is executed by its maker {}, not {}",
+            proposal.maker,
+            config.active_address
+        ));
+    }
+    let tx_data = match execution_data(config, &proposal).await {
+        Ok(tx_data) => tx_data,
+        Err(e) => {
+            db.record(
+                id,
+                proposal.maker,
+                "execution_refused",
+                Some(&e.to_string()),
+            )?;
+            return Err(e);
+        }
+    };
+    let signer = config.keystore.get_key(&proposal.maker)?;
+    let result = sign_and_execute_data(&config.client, signer, tx_data).await;
+    match &result {
+        Ok(resp) => {
+            let error = execution_error(resp);
+            db.set_executed(id, resp.digest, error.as_deref())?;
+            if error.is_none() {
+                record_policy(&proposal.command, proposal.maker, resp.digest, config)?;
+            }
+        }
+        // Not executed, eg. because an object of the transaction changed since it was built
+        Err(e) => db.record(id, proposal.maker, "execution_error", Some(&e.to_string()))?,
+    }
+    audit_command(
+        &proposal.command,
+        &proposal.context,
+        proposal.maker,
+        &result,
+        config,
+    )?;
+    result
+}
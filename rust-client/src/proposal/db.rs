@@ -0,0 +1,353 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::digests::TransactionDigest;
+use sui_sdk::types::transaction::{TransactionData, TransactionDataAPI};
+
+use super::{AuditEntry, Decision, DryRunSummary, Proposal, ProposalStatus, Review};
+use crate::tx_run::{AppCommand, CommandContext};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS proposals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    coin_type TEXT NOT NULL,
+    command TEXT NOT NULL,
+    context TEXT NOT NULL,
+    tx_data BLOB NOT NULL,
+    maker TEXT NOT NULL,
+    required_approvals INTEGER NOT NULL,
+    dry_run TEXT NOT NULL,
+    status TEXT NOT NULL,
+    executed_digest TEXT,
+    created_ms INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS reviews (
+    proposal_id INTEGER NOT NULL REFERENCES proposals(id),
+    checker TEXT NOT NULL,
+    decision TEXT NOT NULL,
+    comment TEXT,
+    signature BLOB NOT NULL,
+    timestamp_ms INTEGER NOT NULL,
+    PRIMARY KEY (proposal_id, checker)
+);
+CREATE TABLE IF NOT EXISTS audit (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    proposal_id INTEGER NOT NULL REFERENCES proposals(id),
+    timestamp_ms INTEGER NOT NULL,
+    actor TEXT NOT NULL,
+    event TEXT NOT NULL,
+    detail TEXT
+);
+";
+
+/// SQLite store of proposals, their reviews and the audit trail of both.
+/// Every change of a proposal is written in the same database transaction as its audit entry.
+pub struct ProposalDb {
+    conn: Connection,
+}
+
+impl ProposalDb {
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(ProposalDb { conn })
+    }
+
+    /// Stores a pending proposal made by the sender of `tx_data`, returning its id.
+    pub fn insert(
+        &mut self,
+        coin_type: &str,
+        command: &AppCommand,
+        context: &CommandContext,
+        tx_data: &TransactionData,
+        required_approvals: u32,
+        dry_run: &DryRunSummary,
+    ) -> Result<u64> {
+        let maker = tx_data.sender();
+        let db_tx = self.conn.transaction()?;
+        db_tx.execute(
+            "INSERT INTO proposals (coin_type, command, context, tx_data, maker,
+                                    required_approvals, dry_run, status, created_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                coin_type,
+                serde_json::to_string(command)?,
+                serde_json::to_string(context)?,
+                bcs::to_bytes(tx_data)?,
+                maker.to_string(),
+                required_approvals,
+                serde_json::to_string(dry_run)?,
+                ProposalStatus::Pending.as_str(),
+                now_ms(),
+            ],
+        )?;
+        let id = db_tx.last_insert_rowid() as u64;
+        audit(
+            &db_tx,
+            id,
+            maker,
+            "created",
+            Some(&format!(
+                "{command:?}, {required_approvals} approvals required"
+            )),
+        )?;
+        db_tx.commit()?;
+        Ok(id)
+    }
+
+    pub fn get(&self, id: u64) -> Result<Proposal> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT coin_type, command, tx_data, maker, required_approvals, dry_run, status,
+                        executed_digest, created_ms, context
+                 FROM proposals WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Vec<u8>>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, u32>(4)?,
+                        row.get::<_, String>(5)?,
+                        row.get::<_, String>(6)?,
+                        row.get::<_, Option<String>>(7)?,
+                        row.get::<_, u64>(8)?,
+                        row.get::<_, String>(9)?,
+                    ))
+                },
+            )
+            .optional()?
+            .ok_or(anyhow!("No proposal {id}"))?;
+        let (
+            coin_type,
+            command,
+            tx_data,
+            maker,
+            required_approvals,
+            dry_run,
+            status,
+            executed_digest,
+            created_ms,
+            context,
+        ) = row;
+        Ok(Proposal {
+            id,
+            coin_type,
+            command: serde_json::from_str(&command)?,
+            context: serde_json::from_str(&context)?,
+            tx_data: bcs::from_bytes(&tx_data)?,
+            maker: SuiAddress::from_str(&maker)?,
+            required_approvals,
+            dry_run: serde_json::from_str(&dry_run)?,
+            status: status.parse()?,
+            executed_digest: executed_digest
+                .map(|digest| TransactionDigest::from_str(&digest))
+                .transpose()?,
+            created_ms,
+            reviews: self.reviews(id)?,
+        })
+    }
+
+    /// Proposals with `status`, or all of them, oldest first.
+    pub fn list(&self, status: Option<ProposalStatus>) -> Result<Vec<Proposal>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, status FROM proposals ORDER BY id")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, u64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut proposals = vec![];
+        for row in rows {
+            let (id, row_status) = row?;
+            if status.map_or(true, |status| status.as_str() == row_status) {
+                proposals.push(self.get(id)?);
+            }
+        }
+        Ok(proposals)
+    }
+
+    fn reviews(&self, id: u64) -> Result<Vec<Review>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT checker, decision, comment, signature, timestamp_ms
+             FROM reviews WHERE proposal_id = ?1 ORDER BY timestamp_ms, checker",
+        )?;
+        let rows = stmt.query_map(params![id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Vec<u8>>(3)?,
+                row.get::<_, u64>(4)?,
+            ))
+        })?;
+        let mut reviews = vec![];
+        for row in rows {
+            let (checker, decision, comment, signature, timestamp_ms) = row?;
+            reviews.push(Review {
+                checker: SuiAddress::from_str(&checker)?,
+                decision: decision.parse()?,
+                comment,
+                signature,
+                timestamp_ms,
+            });
+        }
+        Ok(reviews)
+    }
+
+    /// Stores a review, and approves or rejects the proposal once decided.
+    pub fn insert_review(
+        &mut self,
+        id: u64,
+        checker: SuiAddress,
+        decision: Decision,
+        comment: Option<&str>,
+        signature: &[u8],
+    ) -> Result<()> {
+        let db_tx = self.conn.transaction()?;
+        db_tx.execute(
+            "INSERT INTO reviews (proposal_id, checker, decision, comment, signature, timestamp_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                id,
+                checker.to_string(),
+                decision.as_str(),
+                comment,
+                signature,
+                now_ms()
+            ],
+        )?;
+        let event = match decision {
+            Decision::Approve => "approved",
+            Decision::Reject => "rejected",
+        };
+        audit(&db_tx, id, checker, event, comment)?;
+
+        let (approvals, required): (u32, u32) = db_tx.query_row(
+            "SELECT (SELECT COUNT(*) FROM reviews WHERE proposal_id = ?1 AND decision = 'approve'),
+                    required_approvals
+             FROM proposals WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        let status = match decision {
+            Decision::Reject => Some(ProposalStatus::Rejected),
+            Decision::Approve if approvals >= required => Some(ProposalStatus::Approved),
+            Decision::Approve => None,
+        };
+        if let Some(status) = status {
+            set_status(&db_tx, id, status)?;
+        }
+        db_tx.commit()?;
+        Ok(())
+    }
+
+    /// Records the execution of an approved proposal: executed, or failed with `error`.
+    pub fn set_executed(
+        &mut self,
+        id: u64,
+        digest: TransactionDigest,
+        error: Option<&str>,
+    ) -> Result<()> {
+        let maker = self.get(id)?.maker;
+        let db_tx = self.conn.transaction()?;
+        let status = match error {
+            Some(_) => ProposalStatus::Failed,
+            None => ProposalStatus::Executed,
+        };
+        set_status(&db_tx, id, status)?;
+        db_tx.execute(
+            "UPDATE proposals SET executed_digest = ?1 WHERE id = ?2",
+            params![digest.to_string(), id],
+        )?;
+        let detail = match error {
+            Some(error) => format!("{digest}: {error}"),
+            None => digest.to_string(),
+        };
+        audit(&db_tx, id, maker, status.as_str(), Some(&detail))?;
+        db_tx.commit()?;
+        Ok(())
+    }
+
+    /// Appends an entry to the audit trail without changing the proposal.
+    pub fn record(
+        &mut self,
+        id: u64,
+        actor: SuiAddress,
+        event: &str,
+        detail: Option<&str>,
+    ) -> Result<()> {
+        let db_tx = self.conn.transaction()?;
+        audit(&db_tx, id, actor, event, detail)?;
+        db_tx.commit()?;
+        Ok(())
+    }
+
+    /// The audit trail of proposal `id`, or of every proposal, oldest first.
+    pub fn audit_trail(&self, id: Option<u64>) -> Result<Vec<AuditEntry>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp_ms, proposal_id, actor, event, detail FROM audit
+             WHERE ?1 IS NULL OR proposal_id = ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![id], |row| {
+            Ok((
+                row.get::<_, u64>(0)?,
+                row.get::<_, u64>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<String>>(4)?,
+            ))
+        })?;
+        let mut entries = vec![];
+        for row in rows {
+            let (timestamp_ms, proposal_id, actor, event, detail) = row?;
+            entries.push(AuditEntry {
+                timestamp_ms,
+                proposal_id,
+                actor: SuiAddress::from_str(&actor)?,
+                event,
+                detail,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+fn set_status(db_tx: &Transaction, id: u64, status: ProposalStatus) -> Result<()> {
+    db_tx.execute(
+        "UPDATE proposals SET status = ?1 WHERE id = ?2",
+        params![status.as_str(), id],
+    )?;
+    Ok(())
+}
+
+fn audit(
+    db_tx: &Transaction,
+    id: u64,
+    actor: SuiAddress,
+    event: &str,
+    detail: Option<&str>,
+) -> Result<()> {
+    db_tx.execute(
+        "INSERT INTO audit (proposal_id, timestamp_ms, actor, event, detail)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![id, now_ms(), actor.to_string(), event, detail],
+    )?;
+    Ok(())
+}
+
+fn now_ms() -> u64 {
+    Utc::now().timestamp_millis() as u64
+}
@@ -0,0 +1,348 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use move_core_types::identifier::Identifier;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use sui_move_build::{BuildConfig, CompiledPackage};
+use sui_sdk::rpc_types::{
+    ObjectChange, SuiObjectDataOptions, SuiRawData, SuiTransactionBlockResponse,
+};
+use sui_sdk::types::base_types::{ObjectID, ObjectRef, SuiAddress};
+use sui_sdk::types::coin::{COIN_METADATA_STRUCT_NAME, COIN_MODULE_NAME, COIN_TREASURE_CAP_NAME};
+use sui_sdk::types::crypto::SuiKeyPair;
+use sui_sdk::types::digests::TransactionDigest;
+use sui_sdk::types::move_package::{UpgradeCap, UpgradePolicy};
+use sui_sdk::types::object::Owner;
+use sui_sdk::types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_sdk::types::transaction::{Command, ObjectArg};
+use sui_sdk::types::{SUI_FRAMEWORK_ADDRESS, SUI_FRAMEWORK_PACKAGE_ID};
+use tracing::info;
+
+use crate::backend::ChainBackend;
+use crate::tx_run::sign_and_execute_with_budget;
+
+/// Path of the sample package, relative to `rust-client`
+pub const DEFAULT_PACKAGE_PATH: &str = "../move/regulated_coin_example";
+pub const PUBLISH_GAS_BUDGET: u64 = 2_000_000_000;
+
+/// The objects created by publishing a regulated coin package.
+#[derive(Debug, Clone)]
+pub struct PublishedCoin {
+    pub digest: TransactionDigest,
+    pub package_id: ObjectID,
+    pub type_tag: TypeTag,
+    pub treasury_cap: ObjectID,
+    pub deny_cap: ObjectID,
+    pub coin_metadata: ObjectID,
+    pub upgrade_cap: ObjectID,
+}
+
+/// Builds the Move package at `path` and publishes it, keeping the `UpgradeCap` with the signer.
+pub async fn publish<B: ChainBackend>(
+    client: &B,
+    signer: &SuiKeyPair,
+    path: &Path,
+) -> Result<PublishedCoin> {
+    info!("PUBLISHING PACKAGE {}", path.display());
+    let compiled = build(path)?;
+    let modules = compiled.get_package_bytes(false);
+    let dependencies = compiled.get_dependency_original_package_ids();
+
+    let mut ptb = ProgrammableTransactionBuilder::new();
+    let upgrade_cap = ptb.publish_upgradeable(modules, dependencies);
+    ptb.transfer_arg(SuiAddress::from(&signer.public()), upgrade_cap);
+
+    let resp = sign_and_execute_with_budget(client, signer, ptb.finish(), Some(PUBLISH_GAS_BUDGET))
+        .await?;
+    parse_published(&resp)
+}
+
+fn build(path: &Path) -> Result<CompiledPackage> {
+    Ok(BuildConfig::default().build(path.to_path_buf())?)
+}
+
+/// Finds the package, caps and metadata in the object changes of a publish transaction.
+pub fn parse_published(resp: &SuiTransactionBlockResponse) -> Result<PublishedCoin> {
+    if !resp.errors.is_empty() {
+        return Err(anyhow!("Publishing failed: {}", resp.errors.join(", ")));
+    }
+    let changes = resp
+        .object_changes
+        .as_ref()
+        .ok_or(anyhow!("No object changes in response"))?;
+    let package_id = changes
+        .iter()
+        .find_map(|change| match change {
+            ObjectChange::Published { package_id, .. } => Some(*package_id),
+            _ => None,
+        })
+        .ok_or(anyhow!("No package published"))?;
+    let created = |module: &str, name: &str| -> Option<(ObjectID, &StructTag)> {
+        changes.iter().find_map(|change| match change {
+            ObjectChange::Created {
+                object_type,
+                object_id,
+                ..
+            } if object_type.address == SUI_FRAMEWORK_ADDRESS
+                && object_type.module.as_str() == module
+                && object_type.name.as_str() == name =>
+            {
+                Some((*object_id, object_type))
+            }
+            _ => None,
+        })
+    };
+
+    let (treasury_cap, treasury_cap_type) =
+        created(COIN_MODULE_NAME.as_str(), COIN_TREASURE_CAP_NAME.as_str())
+            .ok_or(anyhow!("No TreasuryCap created"))?;
+    let type_tag = treasury_cap_type
+        .type_params
+        .first()
+        .cloned()
+        .ok_or(anyhow!("TreasuryCap has no type parameter"))?;
+    // Coins of `create_regulated_currency_v2` come with a `DenyCapV2`
+    let (deny_cap, _) = created(COIN_MODULE_NAME.as_str(), "DenyCapV2")
+        .or_else(|| created(COIN_MODULE_NAME.as_str(), "DenyCap"))
+        .ok_or(anyhow!("No DenyCap created"))?;
+    let (coin_metadata, _) = created(
+        COIN_MODULE_NAME.as_str(),
+        COIN_METADATA_STRUCT_NAME.as_str(),
+    )
+    .ok_or(anyhow!("No CoinMetadata created"))?;
+    let (upgrade_cap, _) =
+        created("package", "UpgradeCap").ok_or(anyhow!("No UpgradeCap created"))?;
+
+    Ok(PublishedCoin {
+        digest: resp.digest,
+        package_id,
+        type_tag,
+        treasury_cap,
+        deny_cap,
+        coin_metadata,
+        upgrade_cap,
+    })
+}
+
+/// Writes the published ids into the `.env` file at `path`, keeping any other variables but the
+/// `LATEST_PACKAGE_ID` of a previously published coin.
+pub fn write_env(path: &Path, published: &PublishedCoin) -> Result<()> {
+    let module = match &published.type_tag {
+        TypeTag::Struct(tag) => tag.module.to_string(),
+        other => return Err(anyhow!("Unexpected coin type {other}")),
+    };
+    edit_env_file(
+        path,
+        &[
+            ("PACKAGE_ID", published.package_id.to_string()),
+            ("MODULE_NAME", module),
+            ("COIN_TYPE", published.type_tag.to_string()),
+            ("TREASURY_CAP_ID", published.treasury_cap.to_string()),
+            ("DENY_CAP_ID", published.deny_cap.to_string()),
+            ("COIN_METADATA_ID", published.coin_metadata.to_string()),
+            ("UPGRADE_CAP_ID", published.upgrade_cap.to_string()),
+        ],
+        &["LATEST_PACKAGE_ID"],
+    )
+}
+
+/// Sets `vars` in the `.env` file at `path`, replacing existing values in place.
+pub fn update_env_file(path: &Path, vars: &[(&str, String)]) -> Result<()> {
+    edit_env_file(path, vars, &[])
+}
+
+/// Sets `vars` and removes `removed` in the `.env` file at `path`.
+fn edit_env_file(path: &Path, vars: &[(&str, String)], removed: &[&str]) -> Result<()> {
+    let existing = if path.exists() {
+        std::fs::read_to_string(path)?
+    } else {
+        String::new()
+    };
+    let mut lines: Vec<String> = existing
+        .lines()
+        .filter(|line| {
+            !removed
+                .iter()
+                .any(|key| line.starts_with(&format!("{key}=")))
+        })
+        .map(str::to_string)
+        .collect();
+    for (key, value) in vars {
+        let entry = format!("{key}={value}");
+        let prefix = format!("{key}=");
+        match lines.iter_mut().find(|line| line.starts_with(&prefix)) {
+            Some(line) => *line = entry,
+            None => lines.push(entry),
+        }
+    }
+    std::fs::write(path, lines.join("\n") + "\n")?;
+    Ok(())
+}
+
+/// The package of a coin. Types keep the id of the package that first defined them, while calls
+/// to functions added by an upgrade need to target the latest version of the package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PackageIds {
+    /// The package that defines the coin type
+    pub original: ObjectID,
+    /// The latest version of the package, to call into
+    pub latest: ObjectID,
+}
+
+impl PackageIds {
+    /// The package of `type_tag`, upgraded to `latest` if given.
+    pub fn new(type_tag: &TypeTag, latest: Option<ObjectID>) -> Result<Self> {
+        let TypeTag::Struct(tag) = type_tag else {
+            return Err(anyhow!("{type_tag} is not a struct type"));
+        };
+        let original = ObjectID::from(tag.address);
+        Ok(PackageIds {
+            original,
+            latest: latest.unwrap_or(original),
+        })
+    }
+
+    pub fn is_upgraded(&self) -> bool {
+        self.original != self.latest
+    }
+}
+
+/// Checks that `packages.latest` is an upgrade of the package defining `type_tag`.
+pub async fn verify_package_ids<B: ChainBackend>(
+    client: &B,
+    packages: &PackageIds,
+    type_tag: &TypeTag,
+) -> Result<()> {
+    if !packages.is_upgraded() {
+        return Ok(());
+    }
+    let TypeTag::Struct(tag) = type_tag else {
+        return Err(anyhow!("{type_tag} is not a struct type"));
+    };
+    let resp = client
+        .get_object_with_options(packages.latest, SuiObjectDataOptions::new().with_bcs())
+        .await?;
+    let data = resp
+        .data
+        .ok_or(anyhow!("No package {} found", packages.latest))?;
+    let Some(SuiRawData::Package(package)) = data.bcs else {
+        return Err(anyhow!("Object {} is not a package", packages.latest));
+    };
+    let defined_in_original = package.type_origin_table.iter().any(|origin| {
+        origin.module_name == tag.module.as_str()
+            && origin.struct_name == tag.name.as_str()
+            && origin.package == packages.original
+    });
+    if !defined_in_original {
+        return Err(anyhow!(
+            "Package {} is not an upgrade of {}, which defines {type_tag}",
+            packages.latest,
+            packages.original
+        ));
+    }
+    Ok(())
+}
+
+/// The outcome of upgrading a package.
+#[derive(Debug, Clone)]
+pub struct UpgradedPackage {
+    pub digest: TransactionDigest,
+    /// The id of the new version of the package
+    pub package_id: ObjectID,
+    /// The id of the version that was upgraded
+    pub previous_package_id: ObjectID,
+    pub version: u64,
+}
+
+/// Reads an `UpgradeCap` owned by `owner_addr`.
+pub async fn get_upgrade_cap<B: ChainBackend>(
+    client: &B,
+    id: ObjectID,
+    owner_addr: SuiAddress,
+) -> Result<(ObjectRef, UpgradeCap)> {
+    let resp = client
+        .get_object_with_options(id, SuiObjectDataOptions::new().with_owner().with_bcs())
+        .await?;
+    let data = resp.data.ok_or(anyhow!("No object {id} found"))?;
+    let object_ref = data.object_ref();
+
+    let Some(SuiRawData::MoveObject(raw_obj)) = &data.bcs else {
+        return Err(anyhow!("Object {id} is not a Move object"));
+    };
+    if raw_obj.type_ != UpgradeCap::type_() {
+        return Err(anyhow!(
+            "Object {id} is a {}, not an UpgradeCap",
+            raw_obj.type_
+        ));
+    }
+    if data.owner != Some(Owner::AddressOwner(owner_addr)) {
+        return Err(anyhow!("UpgradeCap {id} is not owned by {owner_addr}"));
+    }
+    Ok((object_ref, bcs::from_bytes(&raw_obj.bcs_bytes)?))
+}
+
+/// Builds the Move package at `path` and upgrades the package of `upgrade_cap` to it, with the
+/// compatible policy. The signer must own the `UpgradeCap`.
+pub async fn upgrade<B: ChainBackend>(
+    client: &B,
+    signer: &SuiKeyPair,
+    path: &Path,
+    upgrade_cap: ObjectID,
+) -> Result<UpgradedPackage> {
+    let signer_addr = SuiAddress::from(&signer.public());
+    let (cap_ref, cap) = get_upgrade_cap(client, upgrade_cap, signer_addr).await?;
+    let previous_package_id = cap.package.bytes;
+    info!(
+        "UPGRADING PACKAGE {previous_package_id} WITH {}",
+        path.display()
+    );
+
+    let compiled = build(path)?;
+    let digest = compiled.get_package_digest(false);
+    let modules = compiled.get_package_bytes(false);
+    let dependencies = compiled.get_dependency_original_package_ids();
+
+    let mut ptb = ProgrammableTransactionBuilder::new();
+    let cap_arg = ptb.obj(ObjectArg::ImmOrOwnedObject(cap_ref))?;
+    let policy = ptb.pure(UpgradePolicy::Compatible as u8)?;
+    let digest = ptb.pure(digest.to_vec())?;
+    let ticket = ptb.command(Command::move_call(
+        SUI_FRAMEWORK_PACKAGE_ID,
+        Identifier::from_str("package")?,
+        Identifier::from_str("authorize_upgrade")?,
+        vec![],
+        vec![cap_arg, policy, digest],
+    ));
+    let receipt = ptb.upgrade(previous_package_id, ticket, dependencies, modules);
+    ptb.command(Command::move_call(
+        SUI_FRAMEWORK_PACKAGE_ID,
+        Identifier::from_str("package")?,
+        Identifier::from_str("commit_upgrade")?,
+        vec![],
+        vec![cap_arg, receipt],
+    ));
+
+    let resp = sign_and_execute_with_budget(client, signer, ptb.finish(), Some(PUBLISH_GAS_BUDGET))
+        .await?;
+    if !resp.errors.is_empty() {
+        return Err(anyhow!("Upgrade failed: {}", resp.errors.join(", ")));
+    }
+    let package_id = resp
+        .object_changes
+        .as_ref()
+        .ok_or(anyhow!("No object changes in response"))?
+        .iter()
+        .find_map(|change| match change {
+            ObjectChange::Published { package_id, .. } => Some(*package_id),
+            _ => None,
+        })
+        .ok_or(anyhow!("No package published"))?;
+
+    Ok(UpgradedPackage {
+        digest: resp.digest,
+        package_id,
+        previous_package_id,
+        version: cap.version + 1,
+    })
+}
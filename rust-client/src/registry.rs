@@ -0,0 +1,201 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use move_core_types::language_storage::TypeTag;
+use serde::{Deserialize, Serialize};
+use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+use sui_sdk::types::crypto::{EncodeDecodeBase64, PublicKey};
+use sui_sdk::types::multisig::MultiSigPublicKey;
+
+pub const DEFAULT_REGISTRY_FILE: &str = "coins.json";
+
+/// A regulated coin managed by this client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedCoin {
+    /// Short name used in the command line, eg. `USDX`
+    pub alias: String,
+    #[serde(rename = "coin_type", with = "type_tag_str")]
+    pub type_tag: TypeTag,
+    pub treasury_cap_owner: SuiAddress,
+    pub deny_cap_owner: SuiAddress,
+    /// The latest version of the package, once it has been upgraded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latest_package_id: Option<ObjectID>,
+}
+
+/// An address the caps of the coins may be handed to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownAddress {
+    /// Who or what holds the address, eg. `alice` or `treasury-2-of-3`
+    pub name: String,
+    pub address: SuiAddress,
+}
+
+/// A multisig the caps of the coins may be handed to, with the keys its address derives from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownMultisig {
+    pub name: String,
+    pub address: SuiAddress,
+    /// The public keys of the members in base64, prefixed with their scheme flag as printed by
+    /// `sui keytool list`
+    pub public_keys: Vec<String>,
+    /// The weight of each member, in the order of `public_keys`
+    pub weights: Vec<u8>,
+    pub threshold: u16,
+}
+
+impl KnownMultisig {
+    /// The multisig public key of the members, failing unless it derives `address`.
+    pub fn public_key(&self) -> Result<MultiSigPublicKey> {
+        let name = &self.name;
+        let public_keys = self
+            .public_keys
+            .iter()
+            .map(|key| {
+                PublicKey::decode_base64(key)
+                    .map_err(|e| anyhow!("Invalid public key {key} of multisig {name}: {e}"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let multisig = MultiSigPublicKey::new(public_keys, self.weights.clone(), self.threshold)
+            .map_err(|e| anyhow!("Invalid multisig {name}: {e}"))?;
+        let derived = SuiAddress::from(&multisig);
+        if derived != self.address {
+            return Err(anyhow!(
+                "Multisig {name} is {}, but its keys derive {derived}",
+                self.address
+            ));
+        }
+        Ok(multisig)
+    }
+}
+
+/// The regulated coins managed by this client, and the keys and multisigs that may hold their
+/// caps, stored as JSON:
+///
+/// ```json
+/// {
+///   "coins": [
+///     {
+///       "alias": "USDX",
+///       "coin_type": "0x..::usdx::USDX",
+///       "treasury_cap_owner": "0x..",
+///       "deny_cap_owner": "0x.."
+///     }
+///   ],
+///   "keys": [{ "name": "alice", "address": "0x.." }],
+///   "multisigs": [
+///     {
+///       "name": "treasury-2-of-3",
+///       "address": "0x..",
+///       "public_keys": ["AC1s..", "AQI..", "AgO.."],
+///       "weights": [1, 1, 1],
+///       "threshold": 2
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CoinRegistry {
+    pub coins: Vec<ManagedCoin>,
+    /// Addresses of single keys held by the team
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<KnownAddress>,
+    /// Multisigs, whose addresses are checked against their keys when loaded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub multisigs: Vec<KnownMultisig>,
+}
+
+impl CoinRegistry {
+    pub fn load(path: &Path) -> Result<Self> {
+        let registry: CoinRegistry = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| anyhow!("Invalid coin registry {}: {e}", path.display()))?;
+        for (i, coin) in registry.coins.iter().enumerate() {
+            if registry.coins[..i].iter().any(|c| c.alias == coin.alias) {
+                return Err(anyhow!("Duplicate coin alias {} in registry", coin.alias));
+            }
+        }
+        for multisig in &registry.multisigs {
+            multisig
+                .public_key()
+                .map_err(|e| anyhow!("Invalid coin registry {}: {e}", path.display()))?;
+        }
+        Ok(registry)
+    }
+
+    /// Loads the registry if `path` exists, otherwise returns an empty one.
+    pub fn load_or_default(path: &Path) -> Result<Self> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Adds `coin`, replacing any coin registered under the same alias.
+    pub fn insert(&mut self, coin: ManagedCoin) {
+        self.coins.retain(|c| c.alias != coin.alias);
+        self.coins.push(coin);
+    }
+
+    pub fn get(&self, alias: &str) -> Result<&ManagedCoin> {
+        self.coins
+            .iter()
+            .find(|coin| coin.alias == alias)
+            .ok_or(anyhow!("Coin {alias} not found in registry"))
+    }
+
+    /// Describes `address` if it is a known key or multisig, eg. `multisig treasury-2-of-3`.
+    pub fn describe(&self, address: SuiAddress) -> Option<String> {
+        let multisig = self
+            .multisigs
+            .iter()
+            .find(|multisig| multisig.address == address)
+            .map(|multisig| format!("multisig {}", multisig.name));
+        multisig.or_else(|| {
+            self.keys
+                .iter()
+                .find(|key| key.address == address)
+                .map(|key| format!("key {}", key.name))
+        })
+    }
+
+    /// Records the owners of the caps of the coin `type_tag`, returning whether the coin is
+    /// registered.
+    pub fn set_cap_owners(
+        &mut self,
+        type_tag: &TypeTag,
+        treasury_cap_owner: SuiAddress,
+        deny_cap_owner: SuiAddress,
+    ) -> bool {
+        let Some(coin) = self
+            .coins
+            .iter_mut()
+            .find(|coin| coin.type_tag == *type_tag)
+        else {
+            return false;
+        };
+        coin.treasury_cap_owner = treasury_cap_owner;
+        coin.deny_cap_owner = deny_cap_owner;
+        true
+    }
+}
+
+mod type_tag_str {
+    use move_core_types::language_storage::TypeTag;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use sui_sdk::types::parse_sui_type_tag;
+
+    pub fn serialize<S: Serializer>(type_tag: &TypeTag, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&type_tag.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<TypeTag, D::Error> {
+        let s = String::deserialize(d)?;
+        parse_sui_type_tag(&s).map_err(serde::de::Error::custom)
+    }
+}
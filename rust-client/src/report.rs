@@ -0,0 +1,77 @@
+use std::fmt;
+
+use anyhow::Result;
+use sui_sdk::types::base_types::SuiAddress;
+
+use crate::backend::ChainBackend;
+use crate::registry::CoinRegistry;
+use crate::tx_run::{deny_list_status, get_deny_list, global_pause_status, EpochStatus};
+
+/// Total supply of every coin in the registry, by alias.
+pub async fn total_supply<B: ChainBackend>(
+    client: &B,
+    registry: &CoinRegistry,
+) -> Result<Vec<(String, u64)>> {
+    let mut supplies = vec![];
+    for coin in &registry.coins {
+        let supply = client.get_total_supply(coin.type_tag.to_string()).await?;
+        supplies.push((coin.alias.clone(), supply.value));
+    }
+    Ok(supplies)
+}
+
+/// Whether an address is denied a coin, on its own or by a global pause of the coin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DenyStatus {
+    pub alias: String,
+    pub denied: EpochStatus,
+    pub paused: EpochStatus,
+}
+
+impl fmt::Display for DenyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}",
+            self.alias,
+            epochs(self.denied, "DENIED", "allowed")
+        )?;
+        if self.paused.any() {
+            write!(f, ", coin {}", epochs(self.paused, "PAUSED", "unpaused"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Describes `status`, by epoch if the current and the next epoch differ.
+fn epochs(status: EpochStatus, yes: &str, no: &str) -> String {
+    let word = |value| if value { yes } else { no };
+    if status.current_epoch == status.next_epoch {
+        word(status.current_epoch).to_string()
+    } else {
+        format!(
+            "{} in this epoch, {} from the next",
+            word(status.current_epoch),
+            word(status.next_epoch)
+        )
+    }
+}
+
+/// Whether `addr` is denied in the current and the next epoch, and whether the coin is paused,
+/// for every coin in the registry.
+pub async fn deny_status<B: ChainBackend>(
+    client: &B,
+    registry: &CoinRegistry,
+    addr: SuiAddress,
+) -> Result<Vec<DenyStatus>> {
+    let deny_list = get_deny_list(client).await?;
+    let mut statuses = vec![];
+    for coin in &registry.coins {
+        statuses.push(DenyStatus {
+            alias: coin.alias.clone(),
+            denied: deny_list_status(client, coin.type_tag.clone(), deny_list, addr).await?,
+            paused: global_pause_status(client, coin.type_tag.clone(), deny_list).await?,
+        });
+    }
+    Ok(statuses)
+}
@@ -0,0 +1,143 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use move_core_types::identifier::Identifier;
+use tracing::info;
+
+use crate::tx_run::COMPLIANCE_MODULE;
+
+const MOVE_TOML_TEMPLATE: &str = include_str!("../templates/coin/Move.toml");
+const COIN_MODULE_TEMPLATE: &str = include_str!("../templates/coin/sources/coin.move");
+/// The `compliance` module of the example package, generated packages get a copy under their
+/// own named address
+const COMPLIANCE_MODULE_SOURCE: &str =
+    include_str!("../../move/regulated_coin_example/sources/compliance.move");
+const COMPLIANCE_MODULE_ADDRESS: &str = "regulated_coin_example";
+
+/// Parameters of a new regulated coin, filled into `templates/coin`.
+#[derive(Debug, Clone)]
+pub struct CoinParams {
+    /// Module name, also used as package name and named address, eg. `usdx`. The
+    /// one-time-witness is the upper-cased module name, as Move requires.
+    pub module: String,
+    pub decimals: u8,
+    pub symbol: String,
+    pub name: String,
+    pub description: String,
+    pub icon_url: Option<String>,
+    /// Whether the `DenyCapV2` may pause the coin for every address
+    pub allow_global_pause: bool,
+}
+
+impl CoinParams {
+    pub fn new(module: &str, decimals: u8, symbol: &str, name: &str) -> Self {
+        CoinParams {
+            module: module.to_string(),
+            decimals,
+            symbol: symbol.to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            icon_url: None,
+            allow_global_pause: false,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if !Identifier::is_valid(&self.module) || self.module != self.module.to_lowercase() {
+            return Err(anyhow!(
+                "Invalid module name {}, expected a lower-case identifier",
+                self.module
+            ));
+        }
+        if self.module == COMPLIANCE_MODULE {
+            return Err(anyhow!(
+                "Module name {COMPLIANCE_MODULE} is taken by the generated compliance module"
+            ));
+        }
+        if self.symbol.is_empty() || self.name.is_empty() {
+            return Err(anyhow!("Symbol and name cannot be empty"));
+        }
+        let mut fields = vec![
+            ("symbol", &self.symbol),
+            ("name", &self.name),
+            ("description", &self.description),
+        ];
+        if let Some(icon_url) = &self.icon_url {
+            fields.push(("icon URL", icon_url));
+        }
+        for (field, value) in fields {
+            // Written as Move byte-string literals
+            if !value
+                .chars()
+                .all(|c| c == ' ' || (c.is_ascii_graphic() && c != '"' && c != '\\'))
+            {
+                return Err(anyhow!(
+                    "Coin {field} must be printable ASCII without quotes or backslashes"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The one-time-witness of the coin.
+    pub fn otw(&self) -> String {
+        self.module.to_uppercase()
+    }
+
+    fn render(&self, template: &str) -> String {
+        let icon_url = match &self.icon_url {
+            Some(url) => format!("option::some(sui::url::new_unsafe_from_bytes(b\"{url}\"))"),
+            None => "option::none()".to_string(),
+        };
+        template
+            .replace("{{package}}", &self.module)
+            .replace("{{module}}", &self.module)
+            .replace("{{otw}}", &self.otw())
+            .replace("{{decimals}}", &self.decimals.to_string())
+            .replace("{{symbol}}", &self.symbol)
+            .replace("{{name}}", &self.name)
+            .replace("{{description}}", &self.description)
+            .replace("{{icon_url}}", &icon_url)
+            .replace(
+                "{{allow_global_pause}}",
+                &self.allow_global_pause.to_string(),
+            )
+    }
+}
+
+/// Writes a Move package for the coin into `dir`, which must not exist or be empty, with the
+/// `compliance` module recording deny-list changes on chain.
+/// Returns the path of the generated coin module.
+pub fn new_coin_package(params: &CoinParams, dir: &Path) -> Result<PathBuf> {
+    params.validate()?;
+    if dir.exists() && dir.read_dir()?.next().is_some() {
+        return Err(anyhow!("{} already exists and is not empty", dir.display()));
+    }
+    info!(
+        "GENERATING PACKAGE FOR {} IN {}",
+        params.otw(),
+        dir.display()
+    );
+
+    let sources = dir.join("sources");
+    std::fs::create_dir_all(&sources)?;
+    std::fs::write(dir.join("Move.toml"), params.render(MOVE_TOML_TEMPLATE))?;
+    let module_path = sources.join(format!("{}.move", params.module));
+    std::fs::write(&module_path, params.render(COIN_MODULE_TEMPLATE))?;
+    std::fs::write(
+        sources.join(format!("{COMPLIANCE_MODULE}.move")),
+        compliance_module(&params.module)?,
+    )?;
+    Ok(module_path)
+}
+
+/// The `compliance` module of the example package, declared under the named address `package`.
+fn compliance_module(package: &str) -> Result<String> {
+    let declaration = format!("module {COMPLIANCE_MODULE_ADDRESS}::{COMPLIANCE_MODULE} {{");
+    let body = COMPLIANCE_MODULE_SOURCE
+        .strip_prefix(&declaration)
+        .ok_or(anyhow!(
+            "{COMPLIANCE_MODULE}.move does not start with {declaration}"
+        ))?;
+    Ok(format!("module {package}::{COMPLIANCE_MODULE} {{{body}"))
+}
@@ -0,0 +1,327 @@
+mod db;
+
+pub use db::ScheduleDb;
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::digests::TransactionDigest;
+
+use crate::backend::ChainBackend;
+use crate::history::format_time;
+use crate::tx_run::{
+    deny_list_status, execute_command_with, execution_error, get_deny_list, AppCommand, AppConfig,
+    CommandContext,
+};
+
+pub const DEFAULT_SCHEDULE_DB: &str = "schedule.db";
+/// Attempts before a scheduled change is given up as failed
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+/// The wait after the first failed attempt, doubled after each further one
+const RETRY_DELAY_MS: u64 = 60_000;
+const MAX_RETRY_DELAY_MS: u64 = 3_600_000;
+
+/// When a scheduled change becomes due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trigger {
+    /// At or after this time, in milliseconds since the epoch
+    At(u64),
+    /// Once the network reaches this epoch
+    Epoch(u64),
+}
+
+impl Trigger {
+    pub fn is_due(&self, now_ms: u64, epoch: u64) -> bool {
+        match *self {
+            Trigger::At(ms) => now_ms >= ms,
+            Trigger::Epoch(due) => epoch >= due,
+        }
+    }
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Trigger::At(ms) => write!(f, "at {}", format_time(ms)),
+            Trigger::Epoch(epoch) => write!(f, "at epoch {epoch}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleStatus {
+    /// Waiting to be due, or for its next attempt
+    Pending,
+    Executed,
+    /// Every attempt failed
+    Failed,
+    Cancelled,
+}
+
+impl ScheduleStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduleStatus::Pending => "pending",
+            ScheduleStatus::Executed => "executed",
+            ScheduleStatus::Failed => "failed",
+            ScheduleStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl FromStr for ScheduleStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "pending" => ScheduleStatus::Pending,
+            "executed" => ScheduleStatus::Executed,
+            "failed" => ScheduleStatus::Failed,
+            "cancelled" => ScheduleStatus::Cancelled,
+            _ => return Err(anyhow!("Unknown schedule status {s}")),
+        })
+    }
+}
+
+impl fmt::Display for ScheduleStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An attempt at executing a scheduled change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    pub timestamp_ms: u64,
+    /// The transaction, if one was executed
+    pub digest: Option<TransactionDigest>,
+    pub error: Option<String>,
+}
+
+/// A deny-list change waiting for, or done with, its time.
+#[derive(Debug, Clone)]
+pub struct ScheduledChange {
+    pub id: u64,
+    pub coin_type: String,
+    pub command: AppCommand,
+    /// The ticket and reason the change is executed with
+    pub context: CommandContext,
+    pub trigger: Trigger,
+    pub status: ScheduleStatus,
+    /// Who scheduled the change
+    pub scheduled_by: SuiAddress,
+    pub max_attempts: u32,
+    /// No attempt is made before this time, once an attempt failed
+    pub next_attempt_ms: u64,
+    pub created_ms: u64,
+    pub attempts: Vec<Attempt>,
+}
+
+impl ScheduledChange {
+    pub fn executed_digest(&self) -> Option<TransactionDigest> {
+        match self.status {
+            ScheduleStatus::Executed => self.attempts.last().and_then(|attempt| attempt.digest),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ScheduledChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Scheduled change {}  {}", self.id, self.status)?;
+        writeln!(f, "Coin:       {}", self.coin_type)?;
+        writeln!(f, "Command:    {:?}", self.command)?;
+        writeln!(f, "Due:        {}", self.trigger)?;
+        writeln!(f, "By:         {}", self.scheduled_by)?;
+        if let Some(ticket) = &self.context.ticket {
+            writeln!(f, "Ticket:     {ticket}")?;
+        }
+        writeln!(
+            f,
+            "Attempts:   {}/{}",
+            self.attempts.len(),
+            self.max_attempts
+        )?;
+        for attempt in &self.attempts {
+            write!(f, "  {}", format_time(attempt.timestamp_ms))?;
+            if let Some(digest) = attempt.digest {
+                write!(f, "  {digest}")?;
+            }
+            match &attempt.error {
+                Some(error) => writeln!(f, "  FAILED {error}")?,
+                None => writeln!(f, "  executed")?,
+            }
+        }
+        if self.status == ScheduleStatus::Pending && !self.attempts.is_empty() {
+            writeln!(f, "Next attempt: {}", format_time(self.next_attempt_ms))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a delay such as `30d`, `12h` or `15m` into milliseconds.
+pub fn parse_delay(s: &str) -> Result<u64> {
+    let invalid = || anyhow!("Invalid delay {s}, expected eg. 30d, 12h, 15m or 30s");
+    let (i, unit) = s.char_indices().last().ok_or_else(invalid)?;
+    let unit_ms = match unit {
+        'd' => 86_400_000,
+        'h' => 3_600_000,
+        'm' => 60_000,
+        's' => 1_000,
+        _ => return Err(invalid()),
+    };
+    let amount: u64 = s[..i].parse().map_err(|_| invalid())?;
+    amount
+        .checked_mul(unit_ms)
+        .ok_or(anyhow!("Delay {s} is too long"))
+}
+
+/// Queues `command`, a deny-list addition or removal of the coin, to execute with `context` once
+/// `trigger` is due. Policy and pre-flight checks run when it executes, not now: a freeze may be
+/// lifted on a schedule before it is even in place.
+pub fn schedule<B: ChainBackend>(
+    config: &AppConfig<B>,
+    db: &mut ScheduleDb,
+    command: AppCommand,
+    context: &CommandContext,
+    trigger: Trigger,
+    max_attempts: u32,
+) -> Result<ScheduledChange> {
+    if !matches!(
+        command,
+        AppCommand::DenyListAdd(_) | AppCommand::DenyListRemove(_)
+    ) {
+        return Err(anyhow!(
+            "Only deny-list additions and removals can be scheduled"
+        ));
+    }
+    if max_attempts == 0 {
+        return Err(anyhow!("A scheduled change needs at least one attempt"));
+    }
+    let id = db.insert(
+        &config.type_tag.to_string(),
+        &command,
+        context,
+        trigger,
+        config.active_address,
+        max_attempts,
+    )?;
+    db.get(id)
+}
+
+/// What a run of the due changes did.
+#[derive(Debug, Clone, Default)]
+pub struct RunReport {
+    pub epoch: u64,
+    /// The changes attempted, as they are after the attempt
+    pub attempted: Vec<ScheduledChange>,
+}
+
+impl fmt::Display for RunReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.attempted {
+            let attempt = change.attempts.last();
+            write!(f, "{}  {}  {:?}", change.id, change.status, change.command)?;
+            if let Some(digest) = attempt.and_then(|attempt| attempt.digest) {
+                write!(f, "  {digest}")?;
+            }
+            if let Some(error) = attempt.and_then(|attempt| attempt.error.as_ref()) {
+                write!(f, "  {error}")?;
+            }
+            if change.status == ScheduleStatus::Pending {
+                write!(
+                    f,
+                    "  retrying after {}",
+                    format_time(change.next_attempt_ms)
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Executes the pending changes of the coin that are due at `now_ms`, oldest first, with the
+/// active address. A failed change is retried with a growing delay until it runs out of
+/// attempts, unless the deny-list already holds its change: an earlier attempt may have been
+/// executed without its response reaching us.
+pub async fn run_due<B: ChainBackend>(
+    config: &AppConfig<B>,
+    db: &mut ScheduleDb,
+    now_ms: u64,
+) -> Result<RunReport> {
+    let epoch = config.client.get_epoch().await?;
+    let coin_type = config.type_tag.to_string();
+    let mut report = RunReport {
+        epoch,
+        attempted: vec![],
+    };
+    for change in db.list(Some(ScheduleStatus::Pending))? {
+        if change.coin_type != coin_type
+            || !change.trigger.is_due(now_ms, epoch)
+            || change.next_attempt_ms > now_ms
+        {
+            continue;
+        }
+        let result = execute_command_with(change.command.clone(), &change.context, config).await;
+        let (digest, error) = match &result {
+            Ok(resp) => (Some(resp.digest), execution_error(resp)),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        // If the deny-list cannot be read, the change is retried as if not in effect
+        let (digest, error) = match error {
+            Some(_) if in_effect(config, &change.command).await.unwrap_or(false) => (None, None),
+            _ => (digest, error),
+        };
+        let attempts = change.attempts.len() as u32 + 1;
+        let status = match error {
+            None => ScheduleStatus::Executed,
+            Some(_) if attempts >= change.max_attempts => ScheduleStatus::Failed,
+            Some(_) => ScheduleStatus::Pending,
+        };
+        let next_attempt_ms = now_ms + retry_delay(attempts);
+        db.record_attempt(
+            change.id,
+            now_ms,
+            digest,
+            error.as_deref(),
+            status,
+            next_attempt_ms,
+        )?;
+        report.attempted.push(db.get(change.id)?);
+    }
+    Ok(report)
+}
+
+/// Whether the deny-list of the coin already holds the change of `command`, from the next epoch
+/// on for the coins whose deny-list changes wait for it.
+async fn in_effect<B: ChainBackend>(config: &AppConfig<B>, command: &AppCommand) -> Result<bool> {
+    let (address, denied) = match command {
+        AppCommand::DenyListAdd(address) => (*address, true),
+        AppCommand::DenyListRemove(address) => (*address, false),
+        _ => return Ok(false),
+    };
+    let deny_list = get_deny_list(&config.client).await?;
+    let status = deny_list_status(&config.client, config.type_tag.clone(), deny_list, address);
+    Ok(status.await?.next_epoch == denied)
+}
+
+fn retry_delay(attempts: u32) -> u64 {
+    let doublings = attempts.saturating_sub(1).min(16);
+    (RETRY_DELAY_MS << doublings).min(MAX_RETRY_DELAY_MS)
+}
+
+/// Cancels a pending change.
+pub fn cancel(db: &mut ScheduleDb, id: u64) -> Result<ScheduledChange> {
+    let change = db.get(id)?;
+    if change.status != ScheduleStatus::Pending {
+        return Err(anyhow!(
+            "Scheduled change {id} is {}, not pending",
+            change.status
+        ));
+    }
+    db.set_status(id, ScheduleStatus::Cancelled)?;
+    db.get(id)
+}
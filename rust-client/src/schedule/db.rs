@@ -0,0 +1,219 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use rusqlite::{params, Connection, OptionalExtension};
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::digests::TransactionDigest;
+
+use super::{Attempt, ScheduleStatus, ScheduledChange, Trigger};
+use crate::tx_run::{AppCommand, CommandContext};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scheduled (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    coin_type TEXT NOT NULL,
+    command TEXT NOT NULL,
+    context TEXT NOT NULL,
+    due_ms INTEGER,
+    due_epoch INTEGER,
+    status TEXT NOT NULL,
+    scheduled_by TEXT NOT NULL,
+    max_attempts INTEGER NOT NULL,
+    next_attempt_ms INTEGER NOT NULL,
+    created_ms INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS attempts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    scheduled_id INTEGER NOT NULL REFERENCES scheduled(id),
+    timestamp_ms INTEGER NOT NULL,
+    digest TEXT,
+    error TEXT
+);
+";
+
+/// SQLite queue of scheduled deny-list changes and their attempts.
+pub struct ScheduleDb {
+    conn: Connection,
+}
+
+impl ScheduleDb {
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(ScheduleDb { conn })
+    }
+
+    /// Stores a pending change, returning its id.
+    pub fn insert(
+        &mut self,
+        coin_type: &str,
+        command: &AppCommand,
+        context: &CommandContext,
+        trigger: Trigger,
+        scheduled_by: SuiAddress,
+        max_attempts: u32,
+    ) -> Result<u64> {
+        let (due_ms, due_epoch) = match trigger {
+            Trigger::At(ms) => (Some(ms), None),
+            Trigger::Epoch(epoch) => (None, Some(epoch)),
+        };
+        self.conn.execute(
+            "INSERT INTO scheduled (coin_type, command, context, due_ms, due_epoch, status,
+                                    scheduled_by, max_attempts, next_attempt_ms, created_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, ?9)",
+            params![
+                coin_type,
+                serde_json::to_string(command)?,
+                serde_json::to_string(context)?,
+                due_ms,
+                due_epoch,
+                ScheduleStatus::Pending.as_str(),
+                scheduled_by.to_string(),
+                max_attempts,
+                Utc::now().timestamp_millis() as u64,
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid() as u64)
+    }
+
+    pub fn get(&self, id: u64) -> Result<ScheduledChange> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT coin_type, command, context, due_ms, due_epoch, status, scheduled_by,
+                        max_attempts, next_attempt_ms, created_ms
+                 FROM scheduled WHERE id = ?1",
+                params![id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, Option<u64>>(3)?,
+                        row.get::<_, Option<u64>>(4)?,
+                        row.get::<_, String>(5)?,
+                        row.get::<_, String>(6)?,
+                        row.get::<_, u32>(7)?,
+                        row.get::<_, u64>(8)?,
+                        row.get::<_, u64>(9)?,
+                    ))
+                },
+            )
+            .optional()?
+            .ok_or(anyhow!("No scheduled change {id}"))?;
+        let (
+            coin_type,
+            command,
+            context,
+            due_ms,
+            due_epoch,
+            status,
+            scheduled_by,
+            max_attempts,
+            next_attempt_ms,
+            created_ms,
+        ) = row;
+        let trigger = match (due_ms, due_epoch) {
+            (Some(ms), None) => Trigger::At(ms),
+            (None, Some(epoch)) => Trigger::Epoch(epoch),
+            _ => return Err(anyhow!("Scheduled change {id} has no single trigger")),
+        };
+        Ok(ScheduledChange {
+            id,
+            coin_type,
+            command: serde_json::from_str(&command)?,
+            context: serde_json::from_str(&context)?,
+            trigger,
+            status: status.parse()?,
+            scheduled_by: SuiAddress::from_str(&scheduled_by)?,
+            max_attempts,
+            next_attempt_ms,
+            created_ms,
+            attempts: self.attempts(id)?,
+        })
+    }
+
+    /// Changes with `status`, or all of them, oldest first.
+    pub fn list(&self, status: Option<ScheduleStatus>) -> Result<Vec<ScheduledChange>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM scheduled WHERE ?1 IS NULL OR status = ?1 ORDER BY id")?;
+        let ids = stmt
+            .query_map(params![status.map(|status| status.as_str())], |row| {
+                row.get::<_, u64>(0)
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        ids.into_iter().map(|id| self.get(id)).collect()
+    }
+
+    fn attempts(&self, id: u64) -> Result<Vec<Attempt>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT timestamp_ms, digest, error FROM attempts WHERE scheduled_id = ?1 ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![id], |row| {
+            Ok((
+                row.get::<_, u64>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?;
+        let mut attempts = vec![];
+        for row in rows {
+            let (timestamp_ms, digest, error) = row?;
+            attempts.push(Attempt {
+                timestamp_ms,
+                digest: digest
+                    .map(|digest| TransactionDigest::from_str(&digest))
+                    .transpose()?,
+                error,
+            });
+        }
+        Ok(attempts)
+    }
+
+    /// Records an attempt and the status it leaves the change in, in one database transaction.
+    pub fn record_attempt(
+        &mut self,
+        id: u64,
+        timestamp_ms: u64,
+        digest: Option<TransactionDigest>,
+        error: Option<&str>,
+        status: ScheduleStatus,
+        next_attempt_ms: u64,
+    ) -> Result<()> {
+        let db_tx = self.conn.transaction()?;
+        db_tx.execute(
+            "INSERT INTO attempts (scheduled_id, timestamp_ms, digest, error)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                id,
+                timestamp_ms,
+                digest.map(|digest| digest.to_string()),
+                error
+            ],
+        )?;
+        db_tx.execute(
+            "UPDATE scheduled SET status = ?1, next_attempt_ms = ?2 WHERE id = ?3",
+            params![status.as_str(), next_attempt_ms, id],
+        )?;
+        db_tx.commit()?;
+        Ok(())
+    }
+
+    pub fn set_status(&mut self, id: u64, status: ScheduleStatus) -> Result<()> {
+        self.conn.execute(
+            "UPDATE scheduled SET status = ?1 WHERE id = ?2",
+            params![status.as_str(), id],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+mod parse;
+mod screener;
+
+pub use parse::{parse_csv, parse_list, sui_address, ListFormat, ListedAddress, ParsedList};
+pub use screener::{
+    screen_counterparty, BlocklistScreener, DenyListScreener, HttpScreener, Screener, ScreenerHit,
+    ScreeningHit, ScreeningRequest, ScreeningResponse,
+};
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use anyhow::Result;
+use move_core_types::language_storage::TypeTag;
+use sui_sdk::types::base_types::SuiAddress;
+
+use crate::backend::ChainBackend;
+use crate::history::format_time;
+use crate::tx_run::{deny_list_status, get_deny_list, MAX_DENY_LIST_BATCH};
+
+/// A listed Sui address, compared with the coin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenMatch {
+    pub listed: ListedAddress,
+    /// Already in the deny-list of the coin
+    pub denied: bool,
+    /// When the address last held or moved the coin, if it is a recent counterparty
+    pub last_seen_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScreenReport {
+    /// Listed Sui addresses, each once
+    pub matches: Vec<ScreenMatch>,
+    /// Listed addresses that are not Sui addresses
+    pub other: usize,
+    /// Whether recent counterparties were looked up
+    pub counterparties_checked: bool,
+}
+
+impl ScreenReport {
+    /// The listed addresses not yet in the deny-list, recent counterparties first.
+    pub fn proposed(&self) -> Vec<SuiAddress> {
+        let mut proposed: Vec<&ScreenMatch> = self.matches.iter().filter(|m| !m.denied).collect();
+        // Stable, so addresses keep the order of the list otherwise
+        proposed.sort_by_key(|m| m.last_seen_ms.is_none());
+        proposed.iter().map(|m| m.listed.address).collect()
+    }
+
+    /// The proposed additions in batches, each fitting in a `DenyListAddMany` transaction.
+    pub fn deny_list_batches(&self) -> Vec<Vec<SuiAddress>> {
+        self.proposed()
+            .chunks(MAX_DENY_LIST_BATCH)
+            .map(<[SuiAddress]>::to_vec)
+            .collect()
+    }
+}
+
+impl fmt::Display for ScreenMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.denied { "DENIED" } else { "PROPOSED" };
+        write!(
+            f,
+            "{status:<9} {}  {}",
+            self.listed.address, self.listed.source
+        )?;
+        if !self.listed.name.is_empty() {
+            write!(f, "  {}", self.listed.name)?;
+        }
+        if let Some(ms) = self.last_seen_ms {
+            write!(f, "  COUNTERPARTY, last seen {}", format_time(ms))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ScreenReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for m in &self.matches {
+            writeln!(f, "{m}")?;
+        }
+        let counterparties = self
+            .matches
+            .iter()
+            .filter(|m| m.last_seen_ms.is_some())
+            .count();
+        writeln!(
+            f,
+            "{} Sui addresses listed, {} already denied, {} to deny; {} addresses of other chains",
+            self.matches.len(),
+            self.matches.iter().filter(|m| m.denied).count(),
+            self.proposed().len(),
+            self.other
+        )?;
+        if self.counterparties_checked {
+            writeln!(
+                f,
+                "{counterparties} listed addresses are recent counterparties"
+            )
+        } else {
+            writeln!(f, "Recent counterparties were not checked")
+        }
+    }
+}
+
+/// Compares the Sui addresses of `list` with the deny-list of `type_tag` and with
+/// `counterparties`, the addresses that recently held or moved the coin and when they last did.
+/// Pass `None` if the counterparties are not known.
+pub async fn screen<B: ChainBackend>(
+    client: &B,
+    type_tag: &TypeTag,
+    list: ParsedList,
+    counterparties: Option<&BTreeMap<SuiAddress, u64>>,
+) -> Result<ScreenReport> {
+    let deny_list = get_deny_list(client).await?;
+    let mut seen = BTreeSet::new();
+    let mut matches = vec![];
+    for listed in list.addresses {
+        if !seen.insert(listed.address) {
+            continue;
+        }
+        // A denial lifted from the next epoch needs adding again
+        let denied = deny_list_status(client, type_tag.clone(), deny_list, listed.address)
+            .await?
+            .next_epoch;
+        let last_seen_ms = counterparties.and_then(|c| c.get(&listed.address).copied());
+        matches.push(ScreenMatch {
+            listed,
+            denied,
+            last_seen_ms,
+        });
+    }
+    Ok(ScreenReport {
+        matches,
+        other: list.other,
+        counterparties_checked: counterparties.is_some(),
+    })
+}
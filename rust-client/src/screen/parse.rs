@@ -0,0 +1,257 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use sui_sdk::types::base_types::SuiAddress;
+
+/// The `idType` of OFAC digital currency addresses, followed by the currency, eg. `- XBT`
+const DIGITAL_CURRENCY_ADDRESS: &str = "Digital Currency Address";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListFormat {
+    /// OFAC `sdn.xml`, with addresses in the `idList` of each entry
+    SdnXml,
+    /// OFAC `sdn.csv`, with addresses in the remarks of each entry
+    SdnCsv,
+    /// A CSV file with a header row, an `address` column and an optional `name` column
+    Csv,
+}
+
+impl FromStr for ListFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "sdn-xml" => Ok(ListFormat::SdnXml),
+            "sdn-csv" => Ok(ListFormat::SdnCsv),
+            "csv" => Ok(ListFormat::Csv),
+            _ => Err(anyhow!(
+                "Unknown list format {s}, expected sdn-xml, sdn-csv or csv"
+            )),
+        }
+    }
+}
+
+impl ListFormat {
+    /// Guesses the format of the list at `path` from its extension and contents.
+    pub fn detect(path: &Path, contents: &str) -> Self {
+        let is_xml = path
+            .extension()
+            .map_or(false, |ext| ext.eq_ignore_ascii_case("xml"));
+        if is_xml || contents.trim_start().starts_with('<') {
+            ListFormat::SdnXml
+        } else if contents.contains(DIGITAL_CURRENCY_ADDRESS) {
+            ListFormat::SdnCsv
+        } else {
+            ListFormat::Csv
+        }
+    }
+}
+
+/// A Sui address found in a list, with the entry it belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListedAddress {
+    pub address: SuiAddress,
+    /// The name of the listed person or entity, if the list has one
+    pub name: String,
+    /// Where in the list the address is, eg. the SDN entry number
+    pub source: String,
+}
+
+/// The Sui addresses of a list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedList {
+    pub addresses: Vec<ListedAddress>,
+    /// Addresses that are not Sui addresses, eg. of other chains
+    pub other: usize,
+}
+
+impl ParsedList {
+    fn push(&mut self, value: &str, name: String, source: String) {
+        match sui_address(value) {
+            Some(address) => self.addresses.push(ListedAddress {
+                address,
+                name,
+                source,
+            }),
+            None => self.other += 1,
+        }
+    }
+}
+
+pub fn parse_list(format: ListFormat, contents: &str) -> Result<ParsedList> {
+    match format {
+        ListFormat::SdnXml => parse_sdn_xml(contents),
+        ListFormat::SdnCsv => parse_sdn_csv(contents),
+        ListFormat::Csv => parse_csv_list(contents),
+    }
+}
+
+/// A Sui address written in full, `0x` and 64 hex digits. Shorter hex strings, such as Ethereum
+/// addresses, are not padded into Sui addresses.
+pub fn sui_address(s: &str) -> Option<SuiAddress> {
+    let s = s.trim().trim_end_matches(['.', ',', ';']);
+    let hex = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))?;
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    SuiAddress::from_str(&format!("0x{}", hex.to_lowercase())).ok()
+}
+
+/// Reads the classic `sdn.xml`: each `sdnEntry` has a `uid`, names, and an `idList` whose ids of
+/// type `Digital Currency Address - <currency>` hold an address in `idNumber`.
+fn parse_sdn_xml(xml: &str) -> Result<ParsedList> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+    let mut list = ParsedList::default();
+    let mut is_sdn_list = false;
+    // The names of the open elements, without their namespace
+    let mut path: Vec<String> = vec![];
+    let mut entry = SdnEntry::default();
+    let (mut id_type, mut id_number) = (String::new(), String::new());
+    loop {
+        let text = match reader.read_event()? {
+            Event::Start(element) => {
+                let name = String::from_utf8(element.local_name().as_ref().to_vec())?;
+                is_sdn_list |= name == "sdnList" || name == "sdnEntry";
+                match name.as_str() {
+                    "sdnEntry" => entry = SdnEntry::default(),
+                    "id" => (id_type, id_number) = (String::new(), String::new()),
+                    _ => {}
+                }
+                path.push(name);
+                continue;
+            }
+            Event::End(_) => {
+                let name = path.pop().unwrap_or_default();
+                let in_id_list = path.last().map_or(false, |parent| parent == "idList");
+                if name == "id" && in_id_list && id_type.starts_with(DIGITAL_CURRENCY_ADDRESS) {
+                    let source = format!("SDN {}, {id_type}", entry.uid);
+                    list.push(&id_number, entry.name(), source);
+                }
+                continue;
+            }
+            Event::Text(text) => text.unescape()?.into_owned(),
+            Event::CData(data) => String::from_utf8(data.into_inner().into_owned())?,
+            Event::Eof => break,
+            _ => continue,
+        };
+        let [.., parent, tag] = path.as_slice() else {
+            continue;
+        };
+        let field = match (parent.as_str(), tag.as_str()) {
+            ("sdnEntry", "uid") => &mut entry.uid,
+            ("sdnEntry", "firstName") => &mut entry.first_name,
+            ("sdnEntry", "lastName") => &mut entry.last_name,
+            ("id", "idType") => &mut id_type,
+            ("id", "idNumber") => &mut id_number,
+            _ => continue,
+        };
+        field.push_str(text.trim());
+    }
+    if !is_sdn_list {
+        return Err(anyhow!("Not an SDN list: no sdnList or sdnEntry element"));
+    }
+    Ok(list)
+}
+
+/// The fields of an `sdnEntry` its addresses are listed with.
+#[derive(Debug, Default)]
+struct SdnEntry {
+    uid: String,
+    first_name: String,
+    last_name: String,
+}
+
+impl SdnEntry {
+    fn name(&self) -> String {
+        [&self.first_name, &self.last_name]
+            .into_iter()
+            .filter(|name| !name.is_empty())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Reads `sdn.csv`, which has no header: the entry number is the first column, the name the
+/// second and the remarks the last, where addresses are written
+/// `Digital Currency Address - <currency> <address>;`.
+fn parse_sdn_csv(contents: &str) -> Result<ParsedList> {
+    let mut list = ParsedList::default();
+    for row in parse_csv(contents)? {
+        let (Some(ent_num), Some(remarks)) = (row.first(), row.last()) else {
+            continue;
+        };
+        let name = row.get(1).cloned().unwrap_or_default();
+        for remark in remarks.split(';') {
+            let Some(start) = remark.find(DIGITAL_CURRENCY_ADDRESS) else {
+                continue;
+            };
+            // `- <currency> <address>`
+            let mut words = remark[start + DIGITAL_CURRENCY_ADDRESS.len()..]
+                .split_whitespace()
+                .skip_while(|word| *word == "-");
+            let (Some(currency), Some(address)) = (words.next(), words.next()) else {
+                continue;
+            };
+            list.push(
+                address,
+                name.clone(),
+                format!(
+                    "SDN {}, {DIGITAL_CURRENCY_ADDRESS} - {currency}",
+                    ent_num.trim()
+                ),
+            );
+        }
+    }
+    Ok(list)
+}
+
+/// Reads a CSV list with a header row naming an `address` column, and optionally a `name`
+/// column. Column names are case-insensitive.
+fn parse_csv_list(contents: &str) -> Result<ParsedList> {
+    let mut rows = parse_csv(contents)?.into_iter();
+    let header = rows.next().ok_or(anyhow!("The list is empty"))?;
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+    };
+    let address_column = column("address").ok_or(anyhow!("The list has no address column"))?;
+    let name_column = column("name");
+    let mut list = ParsedList::default();
+    for (i, row) in rows.enumerate() {
+        let Some(address) = row.get(address_column) else {
+            continue;
+        };
+        if address.trim().is_empty() {
+            continue;
+        }
+        let name = name_column
+            .and_then(|column| row.get(column))
+            .map(|name| name.trim().to_string())
+            .unwrap_or_default();
+        list.push(address, name, format!("row {}", i + 1));
+    }
+    Ok(list)
+}
+
+/// Splits CSV into rows of fields. Fields may be quoted, with `""` for a quote, and quoted fields
+/// may span lines. Blank lines are skipped.
+pub fn parse_csv(contents: &str) -> Result<Vec<Vec<String>>> {
+    // The reader closes a quoted field left open at the end instead of failing, so that a
+    // truncated list would lose its last rows silently
+    if contents.matches('"').count() % 2 == 1 {
+        return Err(anyhow!("Unterminated quoted field in CSV"));
+    }
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(contents.as_bytes())
+        .into_records()
+        .map(|record| Ok(record?.iter().map(str::to_string).collect()))
+        .collect()
+}
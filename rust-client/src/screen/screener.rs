@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use move_core_types::language_storage::TypeTag;
+use serde::{Deserialize, Serialize};
+use sui_sdk::types::base_types::SuiAddress;
+
+use super::{parse_list, ListFormat, ListedAddress, ParsedList};
+use crate::backend::ChainBackend;
+use crate::tx_run::{get_deny_list, is_denied};
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A check of the counterparty of a mint or transfer, consulted before its transaction is built.
+#[async_trait]
+pub trait Screener: Send + Sync {
+    /// Names the screener in hits, eg. `deny-list`
+    fn name(&self) -> String;
+
+    /// Why `address` must not receive coins of `type_tag`, or `None` if it may.
+    async fn screen(&self, type_tag: &TypeTag, address: SuiAddress) -> Result<Option<String>>;
+}
+
+/// Why a screener flagged a counterparty.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScreenerHit {
+    pub screener: String,
+    pub reason: String,
+}
+
+/// A counterparty flagged by screening. Returned wrapped in `anyhow::Error`, so callers can tell
+/// it apart from other failures with `downcast_ref`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreeningHit {
+    pub address: SuiAddress,
+    pub hits: Vec<ScreenerHit>,
+}
+
+impl fmt::Display for ScreeningHit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Counterparty {} is flagged by screening", self.address)?;
+        for hit in &self.hits {
+            write!(f, "; {}: {}", hit.screener, hit.reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ScreeningHit {}
+
+/// Asks every screener about `address`. A screener that fails counts as a hit, so that an
+/// unreachable provider does not let counterparties through unscreened.
+pub async fn screen_counterparty(
+    screeners: &[Box<dyn Screener>],
+    type_tag: &TypeTag,
+    address: SuiAddress,
+) -> Vec<ScreenerHit> {
+    let mut hits = vec![];
+    for screener in screeners {
+        let reason = match screener.screen(type_tag, address).await {
+            Ok(Some(reason)) => reason,
+            Ok(None) => continue,
+            Err(e) => format!("screening failed: {e}"),
+        };
+        hits.push(ScreenerHit {
+            screener: screener.name(),
+            reason,
+        });
+    }
+    hits
+}
+
+/// Flags addresses in the on-chain deny-list of the coin, whose coins could never move again.
+pub struct DenyListScreener<B> {
+    client: B,
+}
+
+impl<B: ChainBackend> DenyListScreener<B> {
+    pub fn new(client: B) -> Self {
+        DenyListScreener { client }
+    }
+}
+
+#[async_trait]
+impl<B: ChainBackend> Screener for DenyListScreener<B> {
+    fn name(&self) -> String {
+        "deny-list".to_string()
+    }
+
+    async fn screen(&self, type_tag: &TypeTag, address: SuiAddress) -> Result<Option<String>> {
+        let deny_list = get_deny_list(&self.client).await?;
+        let denied = is_denied(&self.client, type_tag.clone(), deny_list, address).await?;
+        Ok(denied.then(|| format!("denied for {type_tag}")))
+    }
+}
+
+/// Flags the addresses of a local list, in any of the formats of `screen`.
+pub struct BlocklistScreener {
+    name: String,
+    addresses: BTreeMap<SuiAddress, ListedAddress>,
+}
+
+impl BlocklistScreener {
+    pub fn new(name: String, list: ParsedList) -> Self {
+        let mut addresses = BTreeMap::new();
+        for listed in list.addresses {
+            addresses.entry(listed.address).or_insert(listed);
+        }
+        BlocklistScreener { name, addresses }
+    }
+
+    /// Loads the list at `path`, guessing its format.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Cannot read blocklist {}: {e}", path.display()))?;
+        let list = parse_list(ListFormat::detect(path, &contents), &contents)
+            .map_err(|e| anyhow!("Invalid blocklist {}: {e}", path.display()))?;
+        Ok(Self::new(format!("blocklist {}", path.display()), list))
+    }
+}
+
+#[async_trait]
+impl Screener for BlocklistScreener {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn screen(&self, _type_tag: &TypeTag, address: SuiAddress) -> Result<Option<String>> {
+        Ok(self.addresses.get(&address).map(|listed| {
+            if listed.name.is_empty() {
+                format!("listed at {}", listed.source)
+            } else {
+                format!("listed at {}, {}", listed.source, listed.name)
+            }
+        }))
+    }
+}
+
+/// What [`HttpScreener`] posts to the provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScreeningRequest {
+    pub address: SuiAddress,
+    pub coin_type: String,
+}
+
+/// What the provider answers: whether the address is a hit, and why.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScreeningResponse {
+    pub hit: bool,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Asks a screening provider, or an adapter in front of it, with a JSON `POST` of
+/// [`ScreeningRequest`] answered by a [`ScreeningResponse`].
+pub struct HttpScreener {
+    url: String,
+    token: Option<String>,
+    http: reqwest::Client,
+}
+
+impl HttpScreener {
+    /// A screener posting to `url`, with `Authorization: Bearer <token>` if given.
+    pub fn new(url: String, token: Option<String>) -> Result<Self> {
+        Ok(HttpScreener {
+            url,
+            token,
+            http: reqwest::Client::builder().timeout(HTTP_TIMEOUT).build()?,
+        })
+    }
+}
+
+#[async_trait]
+impl Screener for HttpScreener {
+    fn name(&self) -> String {
+        format!("provider {}", self.url)
+    }
+
+    async fn screen(&self, type_tag: &TypeTag, address: SuiAddress) -> Result<Option<String>> {
+        let mut request = self.http.post(&self.url).json(&ScreeningRequest {
+            address,
+            coin_type: type_tag.to_string(),
+        });
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let resp = request.send().await?;
+        if !resp.status().is_success() {
+            return Err(anyhow!("Responded {}", resp.status()));
+        }
+        let answer: ScreeningResponse = resp.json().await?;
+        Ok(answer
+            .hit
+            .then(|| answer.reason.unwrap_or_else(|| "hit".to_string())))
+    }
+}
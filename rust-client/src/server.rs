@@ -0,0 +1,321 @@
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, Request, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use sui_sdk::rpc_types::SuiTransactionBlockResponse;
+use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+use sui_sdk::types::digests::TransactionDigest;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::backend::ChainBackend;
+use crate::policy::PolicyViolation;
+use crate::screen::ScreeningHit;
+use crate::tx_run::preflight::PreflightFailure;
+use crate::tx_run::{
+    deny_list_status, execute_command_with, execution_error, get_deny_list, AppCommand, AppConfig,
+    CommandContext,
+};
+
+/// What the handlers share: the wallet and client, built once for the lifetime of the server.
+struct ApiState<B: ChainBackend> {
+    config: AppConfig<B>,
+    token: String,
+    /// Held while executing, so concurrent requests do not select the same gas coin
+    execution: Mutex<()>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DenyRequest {
+    address: String,
+}
+
+/// Amounts are strings, as they do not fit JSON numbers
+#[derive(Debug, Deserialize)]
+struct MintRequest {
+    amount: String,
+    recipient: String,
+}
+
+/// Query of `DELETE /deny-list/:address`, for policies requiring a ticket
+#[derive(Debug, Deserialize)]
+struct TicketQuery {
+    ticket: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BurnRequest {
+    coin: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TxResponse {
+    pub digest: TransactionDigest,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BalanceResponse {
+    pub address: SuiAddress,
+    pub coin_type: String,
+    pub balance: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DenyStatusResponse {
+    pub address: SuiAddress,
+    pub coin_type: String,
+    /// Denied in the current or the next epoch
+    pub denied: bool,
+    pub denied_current_epoch: bool,
+    pub denied_next_epoch: bool,
+}
+
+/// Answered as `{"error": message}`.
+struct ApiError {
+    status: StatusCode,
+    message: String,
+}
+
+impl ApiError {
+    fn bad_request(e: impl Display) -> Self {
+        ApiError {
+            status: StatusCode::BAD_REQUEST,
+            message: e.to_string(),
+        }
+    }
+
+    /// The chain, the keystore or the audit log failed, not the request.
+    fn unavailable(e: impl Display) -> Self {
+        ApiError {
+            status: StatusCode::BAD_GATEWAY,
+            message: e.to_string(),
+        }
+    }
+
+    /// Forbidden if the policy or screening refused the command, unprocessable if the pre-flight
+    /// checks did, and unavailable otherwise.
+    fn from_command(e: anyhow::Error) -> Self {
+        let status = if e.downcast_ref::<PolicyViolation>().is_some()
+            || e.downcast_ref::<ScreeningHit>().is_some()
+        {
+            StatusCode::FORBIDDEN
+        } else if e
+            .downcast_ref::<PreflightFailure>()
+            .is_some_and(PreflightFailure::refused)
+        {
+            StatusCode::UNPROCESSABLE_ENTITY
+        } else {
+            StatusCode::BAD_GATEWAY
+        };
+        ApiError {
+            status,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": self.message });
+        (self.status, Json(body)).into_response()
+    }
+}
+
+/// The admin API of the coin of `config`, behind `Authorization: Bearer <token>`:
+///
+/// - `POST /deny-list` with `{"address"}`
+/// - `DELETE /deny-list/:address`, with `?ticket=` if the policy requires one
+/// - `GET /deny-list/:address`
+/// - `POST /mint` with `{"amount", "recipient"}`
+/// - `POST /burn` with `{"coin"}`
+/// - `GET /balance/:address`
+pub fn router<B: ChainBackend + 'static>(config: AppConfig<B>, token: String) -> Router {
+    let state = Arc::new(ApiState {
+        config,
+        token,
+        execution: Mutex::new(()),
+    });
+    Router::new()
+        .route("/deny-list", post(deny_list_add::<B>))
+        .route(
+            "/deny-list/:address",
+            get(deny_status::<B>).delete(deny_list_remove::<B>),
+        )
+        .route("/mint", post(mint::<B>))
+        .route("/burn", post(burn::<B>))
+        .route("/balance/:address", get(balance::<B>))
+        .route_layer(middleware::from_fn_with_state(
+            state.clone(),
+            authenticate::<B>,
+        ))
+        .with_state(state)
+}
+
+pub async fn serve<B: ChainBackend + 'static>(
+    config: AppConfig<B>,
+    token: String,
+    addr: SocketAddr,
+) -> Result<()> {
+    info!("Serving the admin API of {} on {addr}", config.type_tag);
+    axum::Server::bind(&addr)
+        .serve(router(config, token).into_make_service())
+        .await?;
+    Ok(())
+}
+
+async fn authenticate<B: ChainBackend>(
+    State(state): State<Arc<ApiState<B>>>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match token {
+        Some(token) if constant_time_eq(token.as_bytes(), state.token.as_bytes()) => {
+            next.run(request).await
+        }
+        _ => ApiError {
+            status: StatusCode::UNAUTHORIZED,
+            message: "Missing or invalid bearer token".to_string(),
+        }
+        .into_response(),
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn parse_address(address: &str) -> Result<SuiAddress, ApiError> {
+    SuiAddress::from_str(address).map_err(ApiError::bad_request)
+}
+
+async fn execute<B: ChainBackend>(
+    state: &ApiState<B>,
+    command: AppCommand,
+    context: CommandContext,
+) -> Result<Response, ApiError> {
+    let _execution = state.execution.lock().await;
+    let resp = execute_command_with(command, &context, &state.config)
+        .await
+        .map_err(ApiError::from_command)?;
+    let response = tx_response(&resp);
+    let status = if response.success {
+        StatusCode::OK
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
+    Ok((status, Json(response)).into_response())
+}
+
+fn tx_response(resp: &SuiTransactionBlockResponse) -> TxResponse {
+    let error = execution_error(resp);
+    TxResponse {
+        digest: resp.digest,
+        success: error.is_none(),
+        error,
+    }
+}
+
+async fn deny_list_add<B: ChainBackend>(
+    State(state): State<Arc<ApiState<B>>>,
+    Json(request): Json<DenyRequest>,
+) -> Result<Response, ApiError> {
+    let address = parse_address(&request.address)?;
+    execute(
+        &state,
+        AppCommand::DenyListAdd(address),
+        CommandContext::default(),
+    )
+    .await
+}
+
+async fn deny_list_remove<B: ChainBackend>(
+    State(state): State<Arc<ApiState<B>>>,
+    Path(address): Path<String>,
+    Query(query): Query<TicketQuery>,
+) -> Result<Response, ApiError> {
+    let address = parse_address(&address)?;
+    let context = CommandContext {
+        ticket: query.ticket,
+        ..CommandContext::default()
+    };
+    execute(&state, AppCommand::DenyListRemove(address), context).await
+}
+
+async fn mint<B: ChainBackend>(
+    State(state): State<Arc<ApiState<B>>>,
+    Json(request): Json<MintRequest>,
+) -> Result<Response, ApiError> {
+    let amount = request.amount.parse().map_err(ApiError::bad_request)?;
+    let recipient = parse_address(&request.recipient)?;
+    execute(
+        &state,
+        AppCommand::MintAndTransfer(amount, recipient),
+        CommandContext::default(),
+    )
+    .await
+}
+
+async fn burn<B: ChainBackend>(
+    State(state): State<Arc<ApiState<B>>>,
+    Json(request): Json<BurnRequest>,
+) -> Result<Response, ApiError> {
+    let coin = ObjectID::from_hex_literal(&request.coin).map_err(ApiError::bad_request)?;
+    execute(&state, AppCommand::Burn(coin), CommandContext::default()).await
+}
+
+async fn balance<B: ChainBackend>(
+    State(state): State<Arc<ApiState<B>>>,
+    Path(address): Path<String>,
+) -> Result<Json<BalanceResponse>, ApiError> {
+    let address = parse_address(&address)?;
+    let coin_type = state.config.type_tag.to_string();
+    let balance = state
+        .config
+        .client
+        .get_balance(address, coin_type.clone())
+        .await
+        .map_err(ApiError::unavailable)?;
+    Ok(Json(BalanceResponse {
+        address,
+        coin_type,
+        balance: balance.total_balance.to_string(),
+    }))
+}
+
+async fn deny_status<B: ChainBackend>(
+    State(state): State<Arc<ApiState<B>>>,
+    Path(address): Path<String>,
+) -> Result<Json<DenyStatusResponse>, ApiError> {
+    let address = parse_address(&address)?;
+    let client = &state.config.client;
+    let type_tag = state.config.type_tag.clone();
+    let deny_list = get_deny_list(client).await.map_err(ApiError::unavailable)?;
+    let status = deny_list_status(client, type_tag.clone(), deny_list, address)
+        .await
+        .map_err(ApiError::unavailable)?;
+    Ok(Json(DenyStatusResponse {
+        address,
+        coin_type: type_tag.to_string(),
+        denied: status.any(),
+        denied_current_epoch: status.current_epoch,
+        denied_next_epoch: status.next_epoch,
+    }))
+}
@@ -1,67 +1,435 @@
 mod coin;
 mod deny;
+pub mod preflight;
 
-use anyhow::Result;
+pub use coin::{
+    get_coin_metadata, get_coin_objects, get_treasury_cap, inspect_coin, CoinInfo, CoinObjects,
+};
+pub use deny::{
+    deny_list_status, get_deny_cap, get_deny_list, global_pause_status, is_denied, DenyCap,
+    DenyCapVersion, DenyReason, EpochStatus, COMPLIANCE_MODULE, MAX_DENY_LIST_BATCH,
+    RECORD_DENY_LIST_CHANGE, RECORD_DENY_LIST_CHANGE_V2,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
 use move_core_types::language_storage::TypeTag;
-use sui_keys::keystore::AccountKeystore;
+use serde::{Deserialize, Serialize};
+use shared_crypto::intent::{Intent, IntentMessage};
+use sui_keys::keystore::{AccountKeystore, Keystore};
 use sui_sdk::SuiClient;
-use sui_sdk::rpc_types::SuiTransactionBlockResponse;
+use sui_sdk::rpc_types::{SuiCoinMetadata, SuiExecutionStatus, SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse};
 use sui_sdk::types::base_types::{SuiAddress, ObjectID};
-use sui_sdk::wallet_context::WalletContext;
+use sui_sdk::types::crypto::{Signature, SuiKeyPair};
+use sui_sdk::types::digests::TransactionDigest;
+use sui_sdk::types::transaction::{ProgrammableTransaction, Transaction, TransactionData};
+use tracing::{debug, warn};
+
+use crate::audit::{AuditLog, AuditRecord};
+use crate::backend::ChainBackend;
+use crate::gas::select_gas;
+use crate::policy::PolicyEngine;
+use crate::publish::PackageIds;
+use crate::registry::CoinRegistry;
+use crate::screen::{screen_counterparty, Screener, ScreeningHit};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AppCommand {
     DenyListAdd(SuiAddress),
+    /// Adds the address to the deny-list of every coin in the registry
+    DenyListAddAll(SuiAddress),
     DenyListRemove(SuiAddress),
+    /// Adds every address to the deny-list of the coin, in one transaction
+    DenyListAddMany(Vec<SuiAddress>),
     MintAndTransfer(u64, SuiAddress),
     Transfer(ObjectID, SuiAddress),
-    Burn(ObjectID)
+    Burn(ObjectID),
+    /// Denies the coin to every address, with a `DenyCapV2` that allows global pause
+    Pause,
+    Unpause,
+    /// Pauses the coin if `pause`, denies `addresses` and then moves the `TreasuryCap` and the
+    /// deny-cap to `cold_address` if set, in one transaction
+    EmergencyFreeze {
+        pause: bool,
+        addresses: Vec<SuiAddress>,
+        cold_address: Option<SuiAddress>,
+    },
+    /// Moves the `TreasuryCap` and the deny-cap of the coin to the address, in one transaction
+    TransferCaps(SuiAddress),
+}
+
+/// Why a command is run, checked by the policy and kept in the audit log.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandContext {
+    /// The change or incident ticket the command is made under
+    pub ticket: Option<String>,
+    // Skipped when unset, so that the hashes of older audit records do not change
+    /// Why the deny-list is changed, eg. a reason code of the compliance team
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The case a deny-list change belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case: Option<String>,
+    /// Also emit `reason` and `case` on chain, in the same transaction as the deny-list change
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub on_chain: bool,
+    /// Why a counterparty flagged by screening is paid anyway
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub override_screening: Option<String>,
 }
 
-pub struct AppConfig {
-    pub client: SuiClient,
-    pub wallet_context: WalletContext,
+pub struct AppConfig<B: ChainBackend = SuiClient> {
+    pub client: B,
+    pub keystore: Keystore,
+    /// The address signing the commands, which must be in `keystore`
+    pub active_address: SuiAddress,
     pub type_tag: TypeTag,
+    /// The original and latest package of the coin, which differ once the package is upgraded
+    pub packages: PackageIds,
+    pub registry: CoinRegistry,
+    /// Checked before every command is signed, if set
+    pub policy: Option<PolicyEngine>,
+    /// Where every executed command is recorded, if set
+    pub audit_log: Option<AuditLog>,
+    /// Consulted before minting or transferring to an address
+    pub screeners: Vec<Box<dyn Screener>>,
+}
+
+/// Checks on chain that `type_tag` is a regulated coin: its `CoinMetadata` must exist and a
+/// `DenyCap` or `DenyCapV2` must have been created with it. Who holds the caps is left to the
+/// pre-flight checks of each command.
+pub async fn verify_coin_type<B: ChainBackend>(
+    client: &B,
+    type_tag: &TypeTag,
+) -> Result<SuiCoinMetadata> {
+    let metadata = coin::get_coin_metadata(client, type_tag).await?;
+    if coin::get_coin_objects(client, type_tag).await?.deny_cap.is_none() {
+        return Err(anyhow!("{type_tag} is not a regulated coin: it has no DenyCap"));
+    }
+    Ok(metadata)
+}
+
+/// Selects gas, signs and executes a programmable transaction.
+pub(crate) async fn sign_and_execute<B: ChainBackend>(
+    client: &B,
+    signer: &SuiKeyPair,
+    pt: ProgrammableTransaction,
+) -> Result<SuiTransactionBlockResponse> {
+    sign_and_execute_with_budget(client, signer, pt, None).await
+}
+
+/// Like [`sign_and_execute`], for transactions that need more than the default gas budget.
+pub(crate) async fn sign_and_execute_with_budget<B: ChainBackend>(
+    client: &B,
+    signer: &SuiKeyPair,
+    pt: ProgrammableTransaction,
+    budget: Option<u64>,
+) -> Result<SuiTransactionBlockResponse> {
+    let signer_addr = SuiAddress::from(&signer.public());
+    let tx_data = transaction_data(client, signer_addr, pt, budget).await?;
+    sign_and_execute_data(client, signer, tx_data).await
+}
+
+/// Selects gas for a programmable transaction sent by `sender`.
+pub(crate) async fn transaction_data<B: ChainBackend>(
+    client: &B,
+    sender: SuiAddress,
+    pt: ProgrammableTransaction,
+    budget: Option<u64>,
+) -> Result<TransactionData> {
+    let gas_data = select_gas(client, sender, None, budget, vec![], None).await?;
+    Ok(TransactionData::new_programmable(
+        sender,
+        vec![gas_data.object],
+        pt,
+        gas_data.budget,
+        gas_data.price,
+    ))
 }
 
-pub async fn execute_command(
+/// Signs and executes a transaction whose gas is already selected.
+pub(crate) async fn sign_and_execute_data<B: ChainBackend>(
+    client: &B,
+    signer: &SuiKeyPair,
+    tx_data: TransactionData,
+) -> Result<SuiTransactionBlockResponse> {
+    let msg = IntentMessage {
+        intent: Intent::sui_transaction(),
+        value: tx_data,
+    };
+    let sig = Signature::new_secure(&msg, signer);
+
+    client
+        .execute(Transaction::from_data(msg.value, vec![sig]))
+        .await
+}
+
+pub async fn execute_command<B: ChainBackend>(
+    command: AppCommand,
+    config: &AppConfig<B>,
+) -> Result<SuiTransactionBlockResponse> {
+    execute_command_with(command, &CommandContext::default(), config).await
+}
+
+/// Like [`execute_command`], for commands the policy needs a context for, eg. a ticket.
+/// The command is recorded in the audit log whether it executes or not. Commands the policy
+/// requires to be proposed are refused, they are executed by [`crate::proposal::execute`].
+pub async fn execute_command_with<B: ChainBackend>(
     command: AppCommand,
-    config: AppConfig,
+    context: &CommandContext,
+    config: &AppConfig<B>,
 ) -> Result<SuiTransactionBlockResponse> {
+    let result = run_command(&command, context, config).await;
+    let recorded = match &result {
+        Ok(resp) if execution_error(resp).is_none() => {
+            record_policy(&command, config.active_address, resp.digest, config)
+        }
+        _ => Ok(()),
+    };
+    audit_command(&command, context, config.active_address, &result, config)?;
+    recorded?;
+    result
+}
+
+async fn run_command<B: ChainBackend>(
+    command: &AppCommand,
+    context: &CommandContext,
+    config: &AppConfig<B>,
+) -> Result<SuiTransactionBlockResponse> {
+    let signer = config.keystore.get_key(&config.active_address)?;
+    check_policy(command, context, config)?;
+    if let Some(policy) = &config.policy {
+        policy.check_unproposed(command)?;
+    }
+    let pt = build_command(command, context, config).await?;
+    sign_and_execute(&config.client, signer, pt).await
+}
+
+/// Appends what running `command` as `operator` returned to the audit log of `config`, if any,
+/// signed with the key of `operator`.
+pub(crate) fn audit_command<B: ChainBackend>(
+    command: &AppCommand,
+    context: &CommandContext,
+    operator: SuiAddress,
+    result: &Result<SuiTransactionBlockResponse>,
+    config: &AppConfig<B>,
+) -> Result<()> {
+    let Some(audit_log) = &config.audit_log else {
+        return Ok(());
+    };
+    let record = AuditRecord::new(operator, config.type_tag.to_string(), command, context, result);
+    let signer = config.keystore.get_key(&operator)?;
+    audit_log.append(signer, record).map_err(|e| match result {
+        Ok(resp) => anyhow!("Executed {}, but could not record it in the audit log: {e}", resp.digest),
+        Err(_) => e,
+    })?;
+    Ok(())
+}
+
+/// Checks `command` against the policy of `config`, if any, as signed by the active address now.
+pub(crate) fn check_policy<B: ChainBackend>(
+    command: &AppCommand,
+    context: &CommandContext,
+    config: &AppConfig<B>,
+) -> Result<()> {
+    match &config.policy {
+        Some(policy) => policy.check(&config.type_tag, config.active_address, command, context, Utc::now()),
+        None => Ok(()),
+    }
+}
+
+/// Counts an executed `command` against the caps of `operator`.
+pub(crate) fn record_policy<B: ChainBackend>(
+    command: &AppCommand,
+    operator: SuiAddress,
+    digest: TransactionDigest,
+    config: &AppConfig<B>,
+) -> Result<()> {
+    let Some(policy) = &config.policy else {
+        return Ok(());
+    };
+    policy
+        .record(&config.type_tag, operator, command, digest, Utc::now())
+        .map_err(|e| anyhow!("Executed {digest}, but could not record it for the policy: {e}"))
+}
+
+/// Why an executed transaction failed, if it did.
+pub fn execution_error(resp: &SuiTransactionBlockResponse) -> Option<String> {
+    if !resp.errors.is_empty() {
+        return Some(resp.errors.join("; "));
+    }
+    match resp.effects.as_ref().map(|effects| effects.status()) {
+        Some(SuiExecutionStatus::Failure { error }) => Some(error.clone()),
+        _ => None,
+    }
+}
+
+/// Runs the pre-flight checks of `command` and builds its transaction, to be signed by the
+/// active address.
+pub(crate) async fn build_command<B: ChainBackend>(
+    command: &AppCommand,
+    context: &CommandContext,
+    config: &AppConfig<B>,
+) -> Result<ProgrammableTransaction> {
     let AppConfig {
         client,
-        mut wallet_context,
+        active_address: active_addr,
         type_tag,
+        packages,
+        registry,
+        ..
     } = config;
-    let active_addr = wallet_context.active_address()?;
-    let signer = wallet_context.config.keystore.get_key(&active_addr)?;
+    let active_addr = *active_addr;
+
+    check_screening(command, context, config).await?;
+    let report = preflight::run(client, active_addr, type_tag, registry, command).await?;
+    debug!("Pre-flight checks:\n{report}");
+    report.into_result()?;
+    let reason = deny_reason(context, packages)?;
+    if reason.is_some() && !is_deny_list_change(command) {
+        return Err(anyhow!("Only deny-list changes of a single coin can be recorded on chain"));
+    }
 
-    match command {
+    match *command {
         AppCommand::DenyListAdd(address) => {
-            let deny_list = deny::get_deny_list(&client).await?;
-            let deny_cap = deny::get_deny_cap(&client, active_addr, type_tag.clone()).await?;
-            deny::deny_list_add(&client, signer, type_tag, deny_list, deny_cap, address).await
+            let deny_list = deny::get_deny_list(client).await?;
+            let deny_cap = deny::get_deny_cap(client, active_addr, type_tag.clone()).await?;
+            deny::deny_list_add(type_tag.clone(), deny_list, deny_cap, address, reason.as_ref())
+        }
+        AppCommand::DenyListAddAll(address) => {
+            if registry.coins.is_empty() {
+                return Err(anyhow!("No coins in registry"));
+            }
+            let deny_list = deny::get_deny_list(client).await?;
+            let mut coins = vec![];
+            for coin in &registry.coins {
+                if coin.deny_cap_owner != active_addr {
+                    return Err(anyhow!(
+                        "DenyCap of {} is owned by {}, not the active address {active_addr}",
+                        coin.alias,
+                        coin.deny_cap_owner
+                    ));
+                }
+                let deny_cap =
+                    deny::get_deny_cap(client, active_addr, coin.type_tag.clone()).await?;
+                coins.push((coin.type_tag.clone(), deny_cap));
+            }
+            deny::deny_list_add_all(coins, deny_list, address)
         }
         AppCommand::DenyListRemove(address) => {
-            let deny_list = deny::get_deny_list(&client).await?;
-            let deny_cap = deny::get_deny_cap(&client, active_addr, type_tag.clone()).await?;
-            deny::deny_list_remove(&client, signer, type_tag, deny_list, deny_cap, address).await
+            let deny_list = deny::get_deny_list(client).await?;
+            let deny_cap = deny::get_deny_cap(client, active_addr, type_tag.clone()).await?;
+            deny::deny_list_remove(type_tag.clone(), deny_list, deny_cap, address, reason.as_ref())
+        }
+        AppCommand::DenyListAddMany(ref addresses) => {
+            let deny_list = deny::get_deny_list(client).await?;
+            let deny_cap = deny::get_deny_cap(client, active_addr, type_tag.clone()).await?;
+            deny::deny_list_add_many(
+                type_tag.clone(),
+                deny_list,
+                deny_cap,
+                addresses,
+                reason.as_ref(),
+            )
         }
         AppCommand::MintAndTransfer(balance, to_address) => {
             let treasury_cap =
-                coin::get_treasury_cap(&client, active_addr, type_tag.clone()).await?;
-            coin::mint_and_transfer(&client, signer, type_tag, treasury_cap, to_address, balance)
-                .await
+                coin::get_treasury_cap(client, active_addr, type_tag.clone()).await?;
+            coin::mint_and_transfer(type_tag.clone(), treasury_cap, to_address, balance)
         }
         AppCommand::Transfer(coin_id, to_address) => {
-            let coin = coin::get_coin(&client, coin_id).await?;
-            coin::transfer(&client, signer, coin, to_address).await
+            let coin = coin::get_coin(client, coin_id, type_tag, active_addr).await?;
+            coin::transfer(coin, to_address)
         }
         AppCommand::Burn(coin_id) => {
             let treasury_cap =
-                coin::get_treasury_cap(&client, active_addr, type_tag.clone()).await?;
-            let coin = coin::get_coin(&client, coin_id).await?;
-            coin::burn(&client, signer, type_tag, treasury_cap, coin).await
+                coin::get_treasury_cap(client, active_addr, type_tag.clone()).await?;
+            let coin = coin::get_coin(client, coin_id, type_tag, active_addr).await?;
+            coin::burn(type_tag.clone(), treasury_cap, coin)
+        }
+        AppCommand::Pause | AppCommand::Unpause => {
+            let deny_list = deny::get_deny_list(client).await?;
+            let deny_cap = deny::get_deny_cap(client, active_addr, type_tag.clone()).await?;
+            let paused = *command == AppCommand::Pause;
+            deny::set_global_pause(type_tag.clone(), deny_list, deny_cap, paused)
+        }
+        AppCommand::EmergencyFreeze {
+            pause,
+            ref addresses,
+            cold_address,
+        } => {
+            let deny_list = deny::get_deny_list(client).await?;
+            let deny_cap = deny::get_deny_cap(client, active_addr, type_tag.clone()).await?;
+            let caps_to = match cold_address {
+                Some(cold_address) => {
+                    let treasury_cap =
+                        coin::get_treasury_cap(client, active_addr, type_tag.clone()).await?;
+                    Some((treasury_cap, cold_address))
+                }
+                None => None,
+            };
+            deny::emergency_freeze(type_tag.clone(), deny_list, deny_cap, pause, addresses, caps_to)
+        }
+        AppCommand::TransferCaps(to_address) => {
+            let treasury_cap =
+                coin::get_treasury_cap(client, active_addr, type_tag.clone()).await?;
+            let deny_cap = deny::get_deny_cap(client, active_addr, type_tag.clone()).await?;
+            coin::transfer_caps(treasury_cap, deny_cap.object_ref, to_address)
         }
     }
 }
+
+fn is_deny_list_change(command: &AppCommand) -> bool {
+    matches!(
+        command,
+        AppCommand::DenyListAdd(_)
+            | AppCommand::DenyListRemove(_)
+            | AppCommand::DenyListAddMany(_)
+    )
+}
+
+/// Asks the screeners of `config` about the recipient of a mint or transfer. A flagged recipient
+/// fails with a [`ScreeningHit`], unless `context` overrides screening with a justification.
+async fn check_screening<B: ChainBackend>(
+    command: &AppCommand,
+    context: &CommandContext,
+    config: &AppConfig<B>,
+) -> Result<()> {
+    let recipient = match *command {
+        AppCommand::MintAndTransfer(_, recipient) | AppCommand::Transfer(_, recipient) => {
+            recipient
+        }
+        _ => return Ok(()),
+    };
+    let hits = screen_counterparty(&config.screeners, &config.type_tag, recipient).await;
+    if hits.is_empty() {
+        return Ok(());
+    }
+    let hit = ScreeningHit {
+        address: recipient,
+        hits,
+    };
+    match context.override_screening.as_deref().map(str::trim) {
+        Some(justification) if !justification.is_empty() => {
+            warn!("OVERRIDING SCREENING: {hit}. Justification: {justification}");
+            Ok(())
+        }
+        _ => Err(hit.into()),
+    }
+}
+
+/// The reason and case to record on chain with a deny-list change, if `context` asks for it.
+/// Calls go to the latest package, which must have the compliance module.
+fn deny_reason(context: &CommandContext, packages: &PackageIds) -> Result<Option<DenyReason>> {
+    if !context.on_chain {
+        return Ok(None);
+    }
+    if context.reason.is_none() && context.case.is_none() {
+        return Err(anyhow!("Recording a deny-list change on chain needs a reason or a case"));
+    }
+    Ok(Some(DenyReason {
+        package: packages.latest,
+        reason: context.reason.clone().unwrap_or_default(),
+        case_id: context.case.clone().unwrap_or_default(),
+    }))
+}
@@ -3,30 +3,40 @@ use std::str::FromStr;
 use anyhow::{anyhow, Result};
 use move_core_types::identifier::Identifier;
 use move_core_types::language_storage::{StructTag, TypeTag};
-use shared_crypto::intent::{Intent, IntentMessage};
 use sui_sdk::rpc_types::{
-    SuiObjectDataFilter, SuiObjectDataOptions, SuiObjectResponseQuery, SuiTransactionBlockResponse,
-    SuiTransactionBlockResponseOptions,
+    ObjectChange, SuiCoinMetadata, SuiObjectDataFilter, SuiObjectDataOptions,
+    SuiObjectResponseQuery, SuiRawData, SuiTransactionBlockResponseOptions,
 };
 use sui_sdk::types::base_types::{ObjectID, ObjectRef, SuiAddress};
-use sui_sdk::types::coin::{COIN_MODULE_NAME, COIN_TREASURE_CAP_NAME};
-use sui_sdk::types::crypto::{Signature, SuiKeyPair};
+use sui_sdk::types::coin::{Coin, COIN_MODULE_NAME, COIN_TREASURE_CAP_NAME};
+use sui_sdk::types::digests::TransactionDigest;
+use sui_sdk::types::object::Owner;
 use sui_sdk::types::programmable_transaction_builder::ProgrammableTransactionBuilder;
-use sui_sdk::types::quorum_driver_types::ExecuteTransactionRequestType;
-use sui_sdk::types::transaction::{Argument, Command, ObjectArg, Transaction, TransactionData};
+use sui_sdk::types::transaction::{Argument, Command, ObjectArg, ProgrammableTransaction};
 use sui_sdk::types::{SUI_FRAMEWORK_ADDRESS, SUI_FRAMEWORK_PACKAGE_ID};
-use sui_sdk::SuiClient;
 use tracing::info;
 
-use crate::gas::select_gas;
+use super::deny::DenyCapVersion;
+use crate::backend::ChainBackend;
 
-pub async fn get_treasury_cap(
-    client: &SuiClient,
+pub async fn get_treasury_cap<B: ChainBackend>(
+    client: &B,
     owner_addr: SuiAddress,
     type_tag: TypeTag,
 ) -> Result<ObjectRef> {
+    find_treasury_cap(client, owner_addr, type_tag)
+        .await?
+        .ok_or(anyhow!("No treasury-cap found!"))
+}
+
+/// Finds the `TreasuryCap` of `type_tag` owned by `owner_addr`, `None` if it owns none. `Err`
+/// means the chain could not be read.
+pub async fn find_treasury_cap<B: ChainBackend>(
+    client: &B,
+    owner_addr: SuiAddress,
+    type_tag: TypeTag,
+) -> Result<Option<ObjectRef>> {
     let resp = client
-        .read_api()
         .get_owned_objects(
             owner_addr,
             Some(SuiObjectResponseQuery {
@@ -43,50 +53,184 @@ pub async fn get_treasury_cap(
         )
         .await?;
 
-    let treasury_cap = resp
+    let Some(treasury_cap) = resp.data.into_iter().next() else {
+        return Ok(None);
+    };
+    Ok(Some(
+        treasury_cap
+            .data
+            .ok_or(anyhow!("TreasuryCap empty!"))?
+            .object_ref(),
+    ))
+}
+
+pub async fn get_coin_metadata<B: ChainBackend>(
+    client: &B,
+    type_tag: &TypeTag,
+) -> Result<SuiCoinMetadata> {
+    client
+        .get_coin_metadata(type_tag.to_string())
+        .await?
+        .ok_or(anyhow!("No CoinMetadata found for {type_tag}"))
+}
+
+/// The objects a coin was created with, whoever owns them now.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CoinObjects {
+    pub metadata: ObjectID,
+    pub treasury_cap: Option<ObjectID>,
+    /// Only created for regulated coins
+    pub deny_cap: Option<(ObjectID, DenyCapVersion)>,
+    /// The checkpoint of the transaction that created them, once checkpointed
+    pub checkpoint: Option<u64>,
+}
+
+/// Finds the `TreasuryCap` and the `DenyCap` or `DenyCapV2` of `type_tag` in the transaction
+/// that published its package, the previous transaction of the immutable package object. The
+/// coin is created in the `init` of its module, which only runs on publish.
+pub async fn get_coin_objects<B: ChainBackend>(
+    client: &B,
+    type_tag: &TypeTag,
+) -> Result<CoinObjects> {
+    let metadata = get_coin_metadata(client, type_tag).await?;
+    let id = metadata
+        .id
+        .ok_or(anyhow!("CoinMetadata of {type_tag} has no id"))?;
+    let TypeTag::Struct(tag) = type_tag else {
+        return Err(anyhow!("{type_tag} is not a struct"));
+    };
+    let package = ObjectID::from(tag.address);
+    let digest = client
+        .get_object_with_options(
+            package,
+            SuiObjectDataOptions::new().with_previous_transaction(),
+        )
+        .await?
         .data
+        .and_then(|data| data.previous_transaction)
+        .ok_or(anyhow!(
+            "Cannot read the publish transaction of package {package}"
+        ))?;
+    let options = SuiTransactionBlockResponseOptions::new().with_object_changes();
+    let resp = client
+        .multi_get_transaction_blocks(vec![digest], options)
+        .await?
         .into_iter()
         .next()
-        .ok_or(anyhow!("No deny-cap found!"))?;
-    Ok(treasury_cap
-        .data
-        .ok_or(anyhow!("DenyCap empty!"))?
-        .object_ref())
+        .ok_or(anyhow!("Transaction {digest} not found"))?;
+
+    let mut objects = CoinObjects {
+        metadata: id,
+        treasury_cap: None,
+        deny_cap: None,
+        checkpoint: resp.checkpoint,
+    };
+    for change in resp.object_changes.unwrap_or_default() {
+        let ObjectChange::Created {
+            object_type,
+            object_id,
+            ..
+        } = change
+        else {
+            continue;
+        };
+        if object_type.address != SUI_FRAMEWORK_ADDRESS
+            || object_type.module.as_str() != COIN_MODULE_NAME.as_str()
+            || object_type.type_params != [type_tag.clone()]
+        {
+            continue;
+        }
+        match object_type.name.as_str() {
+            "TreasuryCap" => objects.treasury_cap = Some(object_id),
+            "DenyCap" => objects.deny_cap = Some((object_id, DenyCapVersion::V1)),
+            "DenyCapV2" => objects.deny_cap = Some((object_id, DenyCapVersion::V2)),
+            _ => {}
+        }
+    }
+    Ok(objects)
+}
+
+/// A `Coin<T>` as read from the chain.
+#[derive(Debug, Clone)]
+pub struct CoinInfo {
+    pub object_ref: ObjectRef,
+    /// The `T` of `Coin<T>`
+    pub coin_type: TypeTag,
+    pub value: u64,
+    pub owner: Owner,
+    pub previous_transaction: TransactionDigest,
 }
 
-pub async fn get_coin(client: &SuiClient, id: ObjectID) -> Result<ObjectRef> {
+/// Reads any `Coin<T>` object, failing if `id` is not a coin.
+pub async fn inspect_coin<B: ChainBackend>(client: &B, id: ObjectID) -> Result<CoinInfo> {
     let resp = client
-        .read_api()
         .get_object_with_options(
             id,
-            SuiObjectDataOptions {
-                // Note that we could have the type-tag from here and transfer in a moment
-                show_type: false,
-                show_owner: false,
-                show_previous_transaction: false,
-                show_display: false,
-                show_content: false,
-                show_bcs: false,
-                show_storage_rebate: false,
-            },
+            SuiObjectDataOptions::new()
+                .with_type()
+                .with_owner()
+                .with_previous_transaction()
+                .with_bcs(),
         )
         .await?;
+    let data = resp.data.ok_or(anyhow!("No object {id} found"))?;
+    let object_ref = data.object_ref();
 
-    Ok(resp.data.ok_or(anyhow!("No coin found"))?.object_ref())
+    let Some(SuiRawData::MoveObject(raw_obj)) = &data.bcs else {
+        return Err(anyhow!("Object {id} is not a Move object"));
+    };
+    if !Coin::is_coin(&raw_obj.type_) {
+        return Err(anyhow!("Object {id} is a {}, not a Coin", raw_obj.type_));
+    }
+    let coin_type = raw_obj
+        .type_
+        .type_params
+        .first()
+        .cloned()
+        .ok_or(anyhow!("Coin {id} has no type parameter"))?;
+    let coin: Coin = bcs::from_bytes(&raw_obj.bcs_bytes)?;
+
+    Ok(CoinInfo {
+        object_ref,
+        coin_type,
+        value: coin.value(),
+        owner: data.owner.ok_or(anyhow!("Coin {id} has no owner"))?,
+        previous_transaction: data
+            .previous_transaction
+            .ok_or(anyhow!("Coin {id} has no previous transaction"))?,
+    })
+}
+
+/// Gets a `Coin<type_tag>` owned by `owner_addr`, rejecting any other object.
+pub async fn get_coin<B: ChainBackend>(
+    client: &B,
+    id: ObjectID,
+    type_tag: &TypeTag,
+    owner_addr: SuiAddress,
+) -> Result<ObjectRef> {
+    let coin = inspect_coin(client, id).await?;
+    if &coin.coin_type != type_tag {
+        return Err(anyhow!(
+            "Coin {id} is a Coin<{}>, expected Coin<{type_tag}>",
+            coin.coin_type
+        ));
+    }
+    if coin.owner != Owner::AddressOwner(owner_addr) {
+        return Err(anyhow!(
+            "Coin {id} is owned by {}, not by {owner_addr}",
+            coin.owner
+        ));
+    }
+    Ok(coin.object_ref)
 }
 
-pub async fn mint_and_transfer(
-    client: &SuiClient,
-    signer: &SuiKeyPair,
+pub fn mint_and_transfer(
     type_tag: TypeTag,
     treasury_cap: ObjectRef,
     to_address: SuiAddress,
     balance: u64,
-) -> Result<SuiTransactionBlockResponse> {
+) -> Result<ProgrammableTransaction> {
     info!("MINTING COIN OF BALANCE {balance} TO ADDRESS {to_address}");
-    let signer_addr = SuiAddress::from(&signer.public());
-    let gas_data = select_gas(client, signer_addr, None, None, vec![], None).await?;
-
     let mut ptb = ProgrammableTransactionBuilder::new();
 
     let treasury_cap = ptb.obj(ObjectArg::ImmOrOwnedObject(treasury_cap))?;
@@ -100,92 +244,45 @@ pub async fn mint_and_transfer(
     ));
     ptb.transfer_arg(to_address, Argument::Result(0));
 
-    let builder = ptb.finish();
-
-    // Sign transaction
-    let msg = IntentMessage {
-        intent: Intent::sui_transaction(),
-        value: TransactionData::new_programmable(
-            signer_addr,
-            vec![gas_data.object],
-            builder,
-            gas_data.budget,
-            gas_data.price,
-        ),
-    };
-    let sig = Signature::new_secure(&msg, signer);
-
-    let res = client
-        .quorum_driver_api()
-        .execute_transaction_block(
-            Transaction::from_data(msg.value, vec![sig]),
-            SuiTransactionBlockResponseOptions::new()
-                .with_effects()
-                .with_object_changes()
-                .with_input(),
-            Some(ExecuteTransactionRequestType::WaitForLocalExecution),
-        )
-        .await?;
-
-    Ok(res)
+    Ok(ptb.finish())
 }
 
-pub async fn transfer(
-    client: &SuiClient,
-    signer: &SuiKeyPair,
-    coin: ObjectRef,
-    to_address: SuiAddress,
-) -> Result<SuiTransactionBlockResponse> {
+pub fn transfer(coin: ObjectRef, to_address: SuiAddress) -> Result<ProgrammableTransaction> {
     info!("TRANSFERING COIN {} TO ADDRESS {to_address}", coin.0);
-    let signer_addr = SuiAddress::from(&signer.public());
-    let gas_data = select_gas(client, signer_addr, None, None, vec![], None).await?;
-
     let mut ptb = ProgrammableTransactionBuilder::new();
 
     let coin = ptb.obj(ObjectArg::ImmOrOwnedObject(coin))?;
     ptb.transfer_arg(to_address, coin);
 
-    let builder = ptb.finish();
-
-    // Sign transaction
-    let msg = IntentMessage {
-        intent: Intent::sui_transaction(),
-        value: TransactionData::new_programmable(
-            signer_addr,
-            vec![gas_data.object],
-            builder,
-            gas_data.budget,
-            gas_data.price,
-        ),
-    };
-    let sig = Signature::new_secure(&msg, signer);
-
-    let res = client
-        .quorum_driver_api()
-        .execute_transaction_block(
-            Transaction::from_data(msg.value, vec![sig]),
-            SuiTransactionBlockResponseOptions::new()
-                .with_effects()
-                .with_object_changes()
-                .with_input(),
-            Some(ExecuteTransactionRequestType::WaitForLocalExecution),
-        )
-        .await?;
+    Ok(ptb.finish())
+}
 
-    Ok(res)
+/// Moves the `TreasuryCap` and the deny-cap of a coin to `to_address`, in one transaction.
+pub(crate) fn transfer_caps(
+    treasury_cap: ObjectRef,
+    deny_cap: ObjectRef,
+    to_address: SuiAddress,
+) -> Result<ProgrammableTransaction> {
+    info!("TRANSFERING TREASURY_CAP AND DENY_CAP TO ADDRESS {to_address}");
+    let mut ptb = ProgrammableTransactionBuilder::new();
+
+    let treasury_cap = ptb.obj(ObjectArg::ImmOrOwnedObject(treasury_cap))?;
+    let deny_cap = ptb.obj(ObjectArg::ImmOrOwnedObject(deny_cap))?;
+    let recipient = ptb.pure(to_address)?;
+    ptb.command(Command::TransferObjects(
+        vec![treasury_cap, deny_cap],
+        recipient,
+    ));
+
+    Ok(ptb.finish())
 }
 
-pub(crate) async fn burn(
-    client: &SuiClient,
-    signer: &SuiKeyPair,
+pub(crate) fn burn(
     type_tag: TypeTag,
     treasury_cap: ObjectRef,
     coin: ObjectRef,
-) -> Result<SuiTransactionBlockResponse> {
+) -> Result<ProgrammableTransaction> {
     info!("BURNING COIN {}", coin.0);
-    let signer_addr = SuiAddress::from(&signer.public());
-    let gas_data = select_gas(client, signer_addr, None, None, vec![], None).await?;
-
     let mut ptb = ProgrammableTransactionBuilder::new();
 
     let treasury_cap = ptb.obj(ObjectArg::ImmOrOwnedObject(treasury_cap))?;
@@ -198,32 +295,5 @@ pub(crate) async fn burn(
         vec![treasury_cap, coin],
     ));
 
-    let builder = ptb.finish();
-
-    // Sign transaction
-    let msg = IntentMessage {
-        intent: Intent::sui_transaction(),
-        value: TransactionData::new_programmable(
-            signer_addr,
-            vec![gas_data.object],
-            builder,
-            gas_data.budget,
-            gas_data.price,
-        ),
-    };
-    let sig = Signature::new_secure(&msg, signer);
-
-    let res = client
-        .quorum_driver_api()
-        .execute_transaction_block(
-            Transaction::from_data(msg.value, vec![sig]),
-            SuiTransactionBlockResponseOptions::new()
-                .with_effects()
-                .with_input(),
-            Some(ExecuteTransactionRequestType::WaitForLocalExecution),
-        )
-        .await?;
-
-    Ok(res)
-
+    Ok(ptb.finish())
 }
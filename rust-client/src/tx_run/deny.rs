@@ -3,30 +3,47 @@ use std::str::FromStr;
 use anyhow::{anyhow, Result};
 use move_core_types::identifier::Identifier;
 use move_core_types::language_storage::StructTag;
-use shared_crypto::intent::{Intent, IntentMessage};
 use sui_sdk::rpc_types::{
-    SuiObjectDataFilter, SuiObjectDataOptions, SuiObjectResponseQuery, SuiTransactionBlockResponse,
-    SuiTransactionBlockResponseOptions,
+    SuiObjectDataFilter, SuiObjectDataOptions, SuiObjectResponseQuery, SuiRawData,
 };
 use sui_sdk::types::base_types::{ObjectID, ObjectRef, SequenceNumber, SuiAddress};
 use sui_sdk::types::coin::COIN_MODULE_NAME;
-use sui_sdk::types::crypto::{Signature, SuiKeyPair};
+use sui_sdk::types::id::UID;
 use sui_sdk::types::object::Owner;
 use sui_sdk::types::programmable_transaction_builder::ProgrammableTransactionBuilder;
-use sui_sdk::types::quorum_driver_types::ExecuteTransactionRequestType;
-use sui_sdk::types::transaction::{Command, ObjectArg, Transaction, TransactionData};
+use sui_sdk::types::transaction::{
+    Argument, Command, ObjectArg, ProgrammableTransaction, TransactionKind,
+};
 use sui_sdk::types::{
     TypeTag, SUI_DENY_LIST_OBJECT_ID, SUI_FRAMEWORK_ADDRESS, SUI_FRAMEWORK_PACKAGE_ID,
 };
-use sui_sdk::SuiClient;
 use tracing::info;
 
 use super::AppCommand;
-use crate::gas::select_gas;
+use crate::backend::ChainBackend;
+
+/// The module of the coin's package recording why the deny-list changes, see
+/// `move/regulated_coin_example/sources/compliance.move`
+pub const COMPLIANCE_MODULE: &str = "compliance";
+pub const RECORD_DENY_LIST_CHANGE: &str = "record_deny_list_change";
+/// Like `RECORD_DENY_LIST_CHANGE`, taking a `DenyCapV2`
+pub const RECORD_DENY_LIST_CHANGE_V2: &str = "record_deny_list_change_v2";
+/// The most addresses added to the deny-list in one transaction, well within the limits on
+/// the commands and arguments of a programmable transaction
+pub const MAX_DENY_LIST_BATCH: usize = 250;
 
-pub async fn get_deny_list(client: &SuiClient) -> Result<(ObjectID, SequenceNumber)> {
+/// Why a deny-list change is made, emitted on chain by `package::compliance` in the same
+/// transaction.
+#[derive(Debug, Clone)]
+pub struct DenyReason {
+    /// A package of the coin with the compliance module, usually its latest version
+    pub package: ObjectID,
+    pub reason: String,
+    pub case_id: String,
+}
+
+pub async fn get_deny_list<B: ChainBackend>(client: &B) -> Result<(ObjectID, SequenceNumber)> {
     let resp = client
-        .read_api()
         .get_object_with_options(
             SUI_DENY_LIST_OBJECT_ID,
             SuiObjectDataOptions {
@@ -50,35 +67,213 @@ pub async fn get_deny_list(client: &SuiClient) -> Result<(ObjectID, SequenceNumb
     Ok((SUI_DENY_LIST_OBJECT_ID, initial_shared_version))
 }
 
-pub async fn get_deny_cap(
-    client: &SuiClient,
+/// Which of the framework's deny capabilities a coin was created with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DenyCapVersion {
+    /// `DenyCap<T>` of `coin::create_regulated_currency`, whose changes apply at once
+    V1,
+    /// `DenyCapV2<T>` of `coin::create_regulated_currency_v2`. Its changes stop spending at
+    /// once, but only stop receiving from the next epoch.
+    V2,
+}
+
+impl DenyCapVersion {
+    pub fn struct_name(&self) -> &'static str {
+        match self {
+            DenyCapVersion::V1 => "DenyCap",
+            DenyCapVersion::V2 => "DenyCapV2",
+        }
+    }
+}
+
+/// The deny capability of a coin, as found in the objects of its owner.
+#[derive(Debug, Copy, Clone)]
+pub struct DenyCap {
+    pub object_ref: ObjectRef,
+    pub version: DenyCapVersion,
+    /// Whether the coin can be paused for everyone, never for a `V1` cap
+    pub allow_global_pause: bool,
+}
+
+/// Finds the `DenyCapV2` or `DenyCap` of `type_tag` owned by `owner_addr`.
+pub async fn get_deny_cap<B: ChainBackend>(
+    client: &B,
     owner_addr: SuiAddress,
     type_tag: TypeTag,
-) -> Result<ObjectRef> {
-    let resp = client
-        .read_api()
-        .get_owned_objects(
-            owner_addr,
-            Some(SuiObjectResponseQuery {
-                filter: Some(SuiObjectDataFilter::StructType(StructTag {
-                    address: SUI_FRAMEWORK_ADDRESS,
-                    module: Identifier::from(COIN_MODULE_NAME),
-                    name: Identifier::from_str("DenyCap")?,
-                    type_params: vec![type_tag],
-                })),
-                options: None,
-            }),
-            None,
-            None,
-        )
-        .await?;
+) -> Result<DenyCap> {
+    find_deny_cap(client, owner_addr, type_tag)
+        .await?
+        .ok_or(anyhow!("No deny-cap found!"))
+}
+
+/// Like [`get_deny_cap`], `None` if `owner_addr` owns no deny-cap. `Err` means the chain could
+/// not be read.
+pub async fn find_deny_cap<B: ChainBackend>(
+    client: &B,
+    owner_addr: SuiAddress,
+    type_tag: TypeTag,
+) -> Result<Option<DenyCap>> {
+    for version in [DenyCapVersion::V2, DenyCapVersion::V1] {
+        let resp = client
+            .get_owned_objects(
+                owner_addr,
+                Some(SuiObjectResponseQuery {
+                    filter: Some(SuiObjectDataFilter::StructType(StructTag {
+                        address: SUI_FRAMEWORK_ADDRESS,
+                        module: Identifier::from(COIN_MODULE_NAME),
+                        name: Identifier::from_str(version.struct_name())?,
+                        type_params: vec![type_tag.clone()],
+                    })),
+                    options: Some(SuiObjectDataOptions::new().with_bcs()),
+                }),
+                None,
+                None,
+            )
+            .await?;
+        let Some(deny_cap) = resp.data.into_iter().next() else {
+            continue;
+        };
+        let data = deny_cap.data.ok_or(anyhow!("DenyCap empty!"))?;
+        let allow_global_pause = match (version, &data.bcs) {
+            (DenyCapVersion::V1, _) => false,
+            (DenyCapVersion::V2, Some(SuiRawData::MoveObject(raw_obj))) => {
+                bcs::from_bytes::<(UID, bool)>(&raw_obj.bcs_bytes)?.1
+            }
+            (DenyCapVersion::V2, _) => return Err(anyhow!("DenyCapV2 has no bcs")),
+        };
+        return Ok(Some(DenyCap {
+            object_ref: data.object_ref(),
+            version,
+            allow_global_pause,
+        }));
+    }
+    Ok(None)
+}
+
+/// Whether an address, or the whole coin, is denied in the current and in the next epoch.
+/// The two only differ for `DenyCapV2` coins, whose changes reach receivers an epoch later.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct EpochStatus {
+    pub current_epoch: bool,
+    /// As left by the latest change, which also applies to spending at once
+    pub next_epoch: bool,
+}
+
+impl EpochStatus {
+    pub fn any(&self) -> bool {
+        self.current_epoch || self.next_epoch
+    }
+}
+
+/// Checks whether `addr` is in the deny-list of `otw_type` in the current and the next epoch,
+/// without executing a transaction. Reads both the v1 and the v2 deny-list, so it works for
+/// coins of either cap.
+pub async fn deny_list_status<B: ChainBackend>(
+    client: &B,
+    otw_type: TypeTag,
+    deny_list: (ObjectID, SequenceNumber),
+    addr: SuiAddress,
+) -> Result<EpochStatus> {
+    let mut ptb = ProgrammableTransactionBuilder::new();
 
-    let deny_cap = resp
-        .data
-        .into_iter()
-        .next()
-        .ok_or(anyhow!("No deny-cap found!"))?;
-    Ok(deny_cap.data.ok_or(anyhow!("DenyCap empty!"))?.object_ref())
+    let deny_list = ptb.obj(ObjectArg::SharedObject {
+        id: deny_list.0,
+        initial_shared_version: deny_list.1,
+        mutable: false,
+    })?;
+    let address = ptb.pure(addr)?;
+    let functions = [
+        "deny_list_contains",
+        "deny_list_v2_contains_current_epoch",
+        "deny_list_v2_contains_next_epoch",
+    ];
+    for function in functions {
+        ptb.command(Command::move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::from(COIN_MODULE_NAME),
+            Identifier::from_str(function)?,
+            vec![otw_type.clone()],
+            vec![deny_list, address],
+        ));
+    }
+
+    let results = inspect_bools(client, addr, ptb.finish(), &functions).await?;
+    let [v1, current, next] = results[..] else {
+        unreachable!("one result per function");
+    };
+    Ok(EpochStatus {
+        current_epoch: v1 || current,
+        next_epoch: v1 || next,
+    })
+}
+
+/// Checks whether `addr` is in the deny-list of `otw_type` in the current or the next epoch,
+/// without executing a transaction.
+pub async fn is_denied<B: ChainBackend>(
+    client: &B,
+    otw_type: TypeTag,
+    deny_list: (ObjectID, SequenceNumber),
+    addr: SuiAddress,
+) -> Result<bool> {
+    Ok(deny_list_status(client, otw_type, deny_list, addr)
+        .await?
+        .any())
+}
+
+/// Checks whether every address is denied `otw_type` by a global pause, in the current and the
+/// next epoch. Always false for coins with a `V1` cap.
+pub async fn global_pause_status<B: ChainBackend>(
+    client: &B,
+    otw_type: TypeTag,
+    deny_list: (ObjectID, SequenceNumber),
+) -> Result<EpochStatus> {
+    let mut ptb = ProgrammableTransactionBuilder::new();
+
+    let deny_list = ptb.obj(ObjectArg::SharedObject {
+        id: deny_list.0,
+        initial_shared_version: deny_list.1,
+        mutable: false,
+    })?;
+    let functions = [
+        "deny_list_v2_is_global_pause_enabled_current_epoch",
+        "deny_list_v2_is_global_pause_enabled_next_epoch",
+    ];
+    for function in functions {
+        ptb.command(Command::move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::from(COIN_MODULE_NAME),
+            Identifier::from_str(function)?,
+            vec![otw_type.clone()],
+            vec![deny_list],
+        ));
+    }
+
+    let results = inspect_bools(client, SuiAddress::ZERO, ptb.finish(), &functions).await?;
+    Ok(EpochStatus {
+        current_epoch: results[0],
+        next_epoch: results[1],
+    })
+}
+
+/// Dev-inspects `pt`, whose commands each return a bool, named by `functions`.
+async fn inspect_bools<B: ChainBackend>(
+    client: &B,
+    sender: SuiAddress,
+    pt: ProgrammableTransaction,
+    functions: &[&str],
+) -> Result<Vec<bool>> {
+    let results = client
+        .dev_inspect(sender, TransactionKind::ProgrammableTransaction(pt))
+        .await?;
+    let mut values = vec![];
+    for (i, function) in functions.iter().enumerate() {
+        let (bytes, _) = results
+            .get(i)
+            .and_then(|result| result.return_values.first())
+            .ok_or(anyhow!("{function} returned nothing"))?;
+        values.push(bcs::from_bytes(bytes)?);
+    }
+    Ok(values)
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -92,7 +287,9 @@ impl TryFrom<AppCommand> for DenyListCommand {
 
     fn try_from(cmd: AppCommand) -> Result<Self> {
         match cmd {
-            AppCommand::DenyListAdd(address) => Ok(DenyListCommand::Add(address)),
+            AppCommand::DenyListAdd(address) | AppCommand::DenyListAddAll(address) => {
+                Ok(DenyListCommand::Add(address))
+            }
             AppCommand::DenyListRemove(address) => Ok(DenyListCommand::Remove(address)),
             _ => Err(anyhow!("Invalid command for deny list")),
         }
@@ -106,69 +303,195 @@ impl DenyListCommand {
             DenyListCommand::Remove(addr) => *addr,
         }
     }
-}
 
-impl ToString for DenyListCommand {
-    fn to_string(&self) -> String {
-        match self {
-            DenyListCommand::Add(_) => "deny_list_add",
-            DenyListCommand::Remove(_) => "deny_list_remove",
+    /// The `0x2::coin` function making the change with a cap of `version`.
+    pub fn function(&self, version: DenyCapVersion) -> &'static str {
+        match (self, version) {
+            (DenyListCommand::Add(_), DenyCapVersion::V1) => "deny_list_add",
+            (DenyListCommand::Remove(_), DenyCapVersion::V1) => "deny_list_remove",
+            (DenyListCommand::Add(_), DenyCapVersion::V2) => "deny_list_v2_add",
+            (DenyListCommand::Remove(_), DenyCapVersion::V2) => "deny_list_v2_remove",
         }
-        .to_string()
     }
 }
 
-pub async fn deny_list_add(
-    client: &SuiClient,
-    signer: &SuiKeyPair,
+pub fn deny_list_add(
     otw_type: TypeTag,
     deny_list: (ObjectID, SequenceNumber),
-    deny_cap: ObjectRef,
+    deny_cap: DenyCap,
     addr: SuiAddress,
-) -> Result<SuiTransactionBlockResponse> {
+    reason: Option<&DenyReason>,
+) -> Result<ProgrammableTransaction> {
     info!("ADDING {addr} TO DENY_LIST");
     deny_list_cmd(
-        client,
-        signer,
         DenyListCommand::Add(addr),
-        otw_type,
+        vec![(otw_type, deny_cap)],
         deny_list,
-        deny_cap,
+        reason,
     )
-    .await
 }
 
-pub async fn deny_list_remove(
-    client: &SuiClient,
-    signer: &SuiKeyPair,
+/// Adds `addr` to the deny-list of every coin in `coins` in a single transaction.
+/// All deny-caps need to be owned by the signer.
+pub fn deny_list_add_all(
+    coins: Vec<(TypeTag, DenyCap)>,
+    deny_list: (ObjectID, SequenceNumber),
+    addr: SuiAddress,
+) -> Result<ProgrammableTransaction> {
+    info!("ADDING {addr} TO DENY_LIST OF {} COINS", coins.len());
+    deny_list_cmd(DenyListCommand::Add(addr), coins, deny_list, None)
+}
+
+/// Adds every address of `addrs` to the deny-list of `otw_type` in a single transaction.
+pub fn deny_list_add_many(
     otw_type: TypeTag,
     deny_list: (ObjectID, SequenceNumber),
-    deny_cap: ObjectRef,
+    deny_cap: DenyCap,
+    addrs: &[SuiAddress],
+    reason: Option<&DenyReason>,
+) -> Result<ProgrammableTransaction> {
+    if addrs.is_empty() || addrs.len() > MAX_DENY_LIST_BATCH {
+        return Err(anyhow!(
+            "Expected 1 to {MAX_DENY_LIST_BATCH} addresses to deny, got {}",
+            addrs.len()
+        ));
+    }
+    info!("ADDING {} ADDRESSES TO DENY_LIST", addrs.len());
+    let mut ptb = ProgrammableTransactionBuilder::new();
+
+    let deny_list = ptb.obj(ObjectArg::SharedObject {
+        id: deny_list.0,
+        initial_shared_version: deny_list.1,
+        mutable: true,
+    })?;
+    let version = deny_cap.version;
+    let deny_cap = ptb.obj(ObjectArg::ImmOrOwnedObject(deny_cap.object_ref))?;
+    for addr in addrs {
+        let address = ptb.pure(*addr)?;
+        ptb.command(Command::move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::from(COIN_MODULE_NAME),
+            Identifier::from_str(DenyListCommand::Add(*addr).function(version))?,
+            vec![otw_type.clone()],
+            vec![deny_list, deny_cap, address],
+        ));
+        if let Some(reason) = reason {
+            let cap = (deny_cap, version);
+            record_reason(&mut ptb, reason, otw_type.clone(), cap, address, true)?;
+        }
+    }
+
+    Ok(ptb.finish())
+}
+
+pub fn deny_list_remove(
+    otw_type: TypeTag,
+    deny_list: (ObjectID, SequenceNumber),
+    deny_cap: DenyCap,
     addr: SuiAddress,
-) -> Result<SuiTransactionBlockResponse> {
+    reason: Option<&DenyReason>,
+) -> Result<ProgrammableTransaction> {
     info!("REMOVING {addr} FROM DENY_LIST");
     deny_list_cmd(
-        client,
-        signer,
         DenyListCommand::Remove(addr),
-        otw_type,
+        vec![(otw_type, deny_cap)],
         deny_list,
-        deny_cap,
+        reason,
     )
-    .await
 }
 
-async fn deny_list_cmd(
-    client: &SuiClient,
-    signer: &SuiKeyPair,
+/// Changes the deny-list of every coin in `coins`, and records `reason` with each change if
+/// given.
+fn deny_list_cmd(
     cmd: DenyListCommand,
+    coins: Vec<(TypeTag, DenyCap)>,
+    deny_list: (ObjectID, SequenceNumber),
+    reason: Option<&DenyReason>,
+) -> Result<ProgrammableTransaction> {
+    let mut ptb = ProgrammableTransactionBuilder::new();
+
+    let deny_list = ptb.obj(ObjectArg::SharedObject {
+        id: deny_list.0,
+        initial_shared_version: deny_list.1,
+        mutable: true,
+    })?;
+    let address = ptb.pure(cmd.address())?;
+    for (otw_type, deny_cap) in coins {
+        let version = deny_cap.version;
+        let deny_cap = ptb.obj(ObjectArg::ImmOrOwnedObject(deny_cap.object_ref))?;
+        ptb.command(Command::move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::from(COIN_MODULE_NAME),
+            Identifier::from_str(cmd.function(version))?,
+            vec![otw_type.clone()],
+            vec![deny_list, deny_cap, address],
+        ));
+        if let Some(reason) = reason {
+            let added = matches!(cmd, DenyListCommand::Add(_));
+            record_reason(
+                &mut ptb,
+                reason,
+                otw_type,
+                (deny_cap, version),
+                address,
+                added,
+            )?;
+        }
+    }
+
+    Ok(ptb.finish())
+}
+
+/// Enables or disables the global pause of `otw_type`, which denies the coin to every address.
+/// Only a `DenyCapV2` created with `allow_global_pause` can pause.
+pub fn set_global_pause(
     otw_type: TypeTag,
     deny_list: (ObjectID, SequenceNumber),
-    deny_cap: ObjectRef,
-) -> Result<SuiTransactionBlockResponse> {
-    let signer_addr = SuiAddress::from(&signer.public());
-    let gas_data = select_gas(client, signer_addr, None, None, vec![], None).await?;
+    deny_cap: DenyCap,
+    paused: bool,
+) -> Result<ProgrammableTransaction> {
+    check_pause_allowed(&otw_type, &deny_cap)?;
+    info!(
+        "{} GLOBAL PAUSE OF {otw_type}",
+        if paused { "ENABLING" } else { "DISABLING" }
+    );
+    let mut ptb = ProgrammableTransactionBuilder::new();
 
+    let deny_list = ptb.obj(ObjectArg::SharedObject {
+        id: deny_list.0,
+        initial_shared_version: deny_list.1,
+        mutable: true,
+    })?;
+    let deny_cap = ptb.obj(ObjectArg::ImmOrOwnedObject(deny_cap.object_ref))?;
+    ptb.command(global_pause_call(otw_type, deny_list, deny_cap, paused)?);
+
+    Ok(ptb.finish())
+}
+
+/// Pauses `otw_type` if `pause`, adds every address of `addrs` to its deny-list, and lastly
+/// transfers the `TreasuryCap` of `caps_to` and `deny_cap` to its address, in one transaction.
+/// All caps need to be owned by the signer.
+pub fn emergency_freeze(
+    otw_type: TypeTag,
+    deny_list: (ObjectID, SequenceNumber),
+    deny_cap: DenyCap,
+    pause: bool,
+    addrs: &[SuiAddress],
+    caps_to: Option<(ObjectRef, SuiAddress)>,
+) -> Result<ProgrammableTransaction> {
+    if pause {
+        check_pause_allowed(&otw_type, &deny_cap)?;
+    }
+    if addrs.len() > MAX_DENY_LIST_BATCH {
+        return Err(anyhow!(
+            "Expected at most {MAX_DENY_LIST_BATCH} addresses to deny, got {}",
+            addrs.len()
+        ));
+    }
+    info!(
+        "EMERGENCY FREEZE OF {otw_type}: PAUSE {pause}, DENYING {} ADDRESSES",
+        addrs.len()
+    );
     let mut ptb = ProgrammableTransactionBuilder::new();
 
     let deny_list = ptb.obj(ObjectArg::SharedObject {
@@ -176,42 +499,90 @@ async fn deny_list_cmd(
         initial_shared_version: deny_list.1,
         mutable: true,
     })?;
-    let deny_cap = ptb.obj(ObjectArg::ImmOrOwnedObject(deny_cap))?;
-    let address = ptb.pure(cmd.address())?;
-    ptb.command(Command::move_call(
+    let version = deny_cap.version;
+    let deny_cap = ptb.obj(ObjectArg::ImmOrOwnedObject(deny_cap.object_ref))?;
+    if pause {
+        ptb.command(global_pause_call(
+            otw_type.clone(),
+            deny_list,
+            deny_cap,
+            true,
+        )?);
+    }
+    for addr in addrs {
+        let address = ptb.pure(*addr)?;
+        ptb.command(Command::move_call(
+            SUI_FRAMEWORK_PACKAGE_ID,
+            Identifier::from(COIN_MODULE_NAME),
+            Identifier::from_str(DenyListCommand::Add(*addr).function(version))?,
+            vec![otw_type.clone()],
+            vec![deny_list, deny_cap, address],
+        ));
+    }
+    if let Some((treasury_cap, cold_address)) = caps_to {
+        info!("MOVING TREASURY_CAP AND DENY_CAP TO {cold_address}");
+        let treasury_cap = ptb.obj(ObjectArg::ImmOrOwnedObject(treasury_cap))?;
+        let recipient = ptb.pure(cold_address)?;
+        ptb.command(Command::TransferObjects(
+            vec![treasury_cap, deny_cap],
+            recipient,
+        ));
+    }
+
+    Ok(ptb.finish())
+}
+
+fn check_pause_allowed(otw_type: &TypeTag, deny_cap: &DenyCap) -> Result<()> {
+    if deny_cap.version != DenyCapVersion::V2 || !deny_cap.allow_global_pause {
+        return Err(anyhow!(
+            "{otw_type} cannot be paused, its deny-cap does not allow global pause"
+        ));
+    }
+    Ok(())
+}
+
+fn global_pause_call(
+    otw_type: TypeTag,
+    deny_list: Argument,
+    deny_cap: Argument,
+    paused: bool,
+) -> Result<Command> {
+    let function = match paused {
+        true => "deny_list_v2_enable_global_pause",
+        false => "deny_list_v2_disable_global_pause",
+    };
+    Ok(Command::move_call(
         SUI_FRAMEWORK_PACKAGE_ID,
         Identifier::from(COIN_MODULE_NAME),
-        Identifier::from_str(&cmd.to_string())?,
+        Identifier::from_str(function)?,
         vec![otw_type],
-        vec![deny_list, deny_cap, address],
-    ));
+        vec![deny_list, deny_cap],
+    ))
+}
 
-    let builder = ptb.finish();
-
-    // Sign transaction
-    let msg = IntentMessage {
-        intent: Intent::sui_transaction(),
-        value: TransactionData::new_programmable(
-            signer_addr,
-            vec![gas_data.object],
-            builder,
-            gas_data.budget,
-            gas_data.price,
-        ),
+/// Calls `compliance::record_deny_list_change`, or its v2 variant, for the change of `address`
+/// made with `deny_cap`.
+fn record_reason(
+    ptb: &mut ProgrammableTransactionBuilder,
+    reason: &DenyReason,
+    otw_type: TypeTag,
+    (deny_cap, version): (Argument, DenyCapVersion),
+    address: Argument,
+    added: bool,
+) -> Result<()> {
+    let function = match version {
+        DenyCapVersion::V1 => RECORD_DENY_LIST_CHANGE,
+        DenyCapVersion::V2 => RECORD_DENY_LIST_CHANGE_V2,
     };
-    let sig = Signature::new_secure(&msg, signer);
-
-    let res = client
-        .quorum_driver_api()
-        .execute_transaction_block(
-            Transaction::from_data(msg.value, vec![sig]),
-            SuiTransactionBlockResponseOptions::new()
-                .with_effects()
-                .with_object_changes()
-                .with_input(),
-            Some(ExecuteTransactionRequestType::WaitForLocalExecution),
-        )
-        .await?;
-
-    Ok(res)
+    let added = ptb.pure(added)?;
+    let reason_code = ptb.pure(reason.reason.clone())?;
+    let case_id = ptb.pure(reason.case_id.clone())?;
+    ptb.command(Command::move_call(
+        reason.package,
+        Identifier::from_str(COMPLIANCE_MODULE)?,
+        Identifier::from_str(function)?,
+        vec![otw_type],
+        vec![deny_cap, address, added, reason_code, case_id],
+    ));
+    Ok(())
 }
@@ -0,0 +1,358 @@
+use std::fmt;
+
+use anyhow::Result;
+use move_core_types::language_storage::TypeTag;
+use sui_sdk::types::base_types::{ObjectID, SequenceNumber, SuiAddress};
+
+use super::{coin, deny, AppCommand};
+use crate::backend::ChainBackend;
+use crate::gas::select_gas;
+use crate::registry::CoinRegistry;
+
+/// The outcome of a single pre-flight check.
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub description: String,
+    /// `Err` explains why the check failed
+    pub outcome: Result<(), String>,
+    /// The check failed because the chain could not be read, not because it refused
+    pub unavailable: bool,
+}
+
+/// The failed checks of a [`PreflightReport`]. Returned wrapped in `anyhow::Error`, so callers
+/// can tell it apart from other failures with `downcast_ref`.
+#[derive(Debug, Clone)]
+pub struct PreflightFailure {
+    pub failures: Vec<Check>,
+}
+
+impl PreflightFailure {
+    /// Whether every failed check ran, so that the command was refused rather than not checked.
+    pub fn refused(&self) -> bool {
+        self.failures.iter().all(|check| !check.unavailable)
+    }
+}
+
+impl fmt::Display for PreflightFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pre-flight checks failed:")?;
+        for check in &self.failures {
+            write!(f, "\n{check}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PreflightFailure {}
+
+/// Read-only checks run against the chain before a command is signed.
+#[derive(Debug, Clone, Default)]
+pub struct PreflightReport {
+    pub checks: Vec<Check>,
+}
+
+impl PreflightReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.outcome.is_ok())
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|check| check.outcome.is_err())
+    }
+
+    /// Turns a failed report into a [`PreflightFailure`] listing the failed checks.
+    pub fn into_result(self) -> Result<Self> {
+        if self.passed() {
+            return Ok(self);
+        }
+        let failures = self.failures().cloned().collect();
+        Err(PreflightFailure { failures }.into())
+    }
+
+    fn push(&mut self, description: impl Into<String>, outcome: Result<(), String>) {
+        self.checks.push(Check {
+            description: description.into(),
+            outcome,
+            unavailable: false,
+        });
+    }
+
+    /// Pushes a check that needed a read of the chain, which failed with `Err`.
+    fn push_query(&mut self, description: impl Into<String>, outcome: Result<Result<(), String>>) {
+        let (outcome, unavailable) = match outcome {
+            Ok(outcome) => (outcome, false),
+            Err(e) => (Err(e.to_string()), true),
+        };
+        self.checks.push(Check {
+            description: description.into(),
+            outcome,
+            unavailable,
+        });
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            Ok(()) => write!(f, "[PASS] {}", self.description),
+            Err(reason) if self.unavailable => {
+                write!(f, "[ERROR] {}: {reason}", self.description)
+            }
+            Err(reason) => write!(f, "[FAIL] {}: {reason}", self.description),
+        }
+    }
+}
+
+impl fmt::Display for PreflightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            writeln!(f, "{check}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs every check relevant to `command` when signed by `active_addr`.
+/// Failing checks do not stop the rest from running, so that the report is complete.
+pub async fn run<B: ChainBackend>(
+    client: &B,
+    active_addr: SuiAddress,
+    type_tag: &TypeTag,
+    registry: &CoinRegistry,
+    command: &AppCommand,
+) -> Result<PreflightReport> {
+    let mut report = PreflightReport::default();
+    let deny_list = deny::get_deny_list(client).await?;
+
+    match command {
+        AppCommand::DenyListAdd(_) => {
+            check_deny_cap(client, &mut report, active_addr, type_tag).await;
+        }
+        AppCommand::DenyListAddAll(_) => {
+            if registry.coins.is_empty() {
+                report.push("Registry has coins", Err("registry is empty".to_string()));
+            }
+            for managed in &registry.coins {
+                check_deny_cap(client, &mut report, active_addr, &managed.type_tag).await;
+            }
+        }
+        AppCommand::DenyListRemove(address) => {
+            check_deny_cap(client, &mut report, active_addr, type_tag).await;
+            // A removal that is not effective until the next epoch is already done
+            let status = deny::deny_list_status(client, type_tag.clone(), deny_list, *address);
+            let outcome = status.await.map(|status| match status.next_epoch {
+                true => Ok(()),
+                false => Err("address is not in the deny-list".to_string()),
+            });
+            report.push_query(format!("{address} is denied for {type_tag}"), outcome);
+        }
+        AppCommand::DenyListAddMany(addresses) => {
+            check_deny_cap(client, &mut report, active_addr, type_tag).await;
+            let outcome = if addresses.is_empty() {
+                Err("no addresses".to_string())
+            } else if addresses.len() > deny::MAX_DENY_LIST_BATCH {
+                Err(format!(
+                    "{} addresses, at most {} fit in a transaction",
+                    addresses.len(),
+                    deny::MAX_DENY_LIST_BATCH
+                ))
+            } else {
+                Ok(())
+            };
+            report.push("Batch of addresses to deny fits in a transaction", outcome);
+        }
+        AppCommand::MintAndTransfer(balance, to_address) => {
+            check_treasury_cap(client, &mut report, active_addr, type_tag, registry).await;
+            check_not_denied(client, &mut report, type_tag, deny_list, *to_address).await;
+            let supply = client.get_total_supply(type_tag.to_string()).await;
+            let outcome = supply.map(|supply| match supply.value.checked_add(*balance) {
+                Some(_) => Ok(()),
+                None => Err(format!(
+                    "current supply {} plus {balance} overflows u64",
+                    supply.value
+                )),
+            });
+            report.push_query(
+                format!("Minting {balance} does not overflow supply"),
+                outcome,
+            );
+        }
+        AppCommand::Transfer(coin_id, to_address) => {
+            check_coin(client, &mut report, active_addr, type_tag, *coin_id).await;
+            check_not_denied(client, &mut report, type_tag, deny_list, active_addr).await;
+            check_not_denied(client, &mut report, type_tag, deny_list, *to_address).await;
+        }
+        AppCommand::Burn(coin_id) => {
+            check_treasury_cap(client, &mut report, active_addr, type_tag, registry).await;
+            check_coin(client, &mut report, active_addr, type_tag, *coin_id).await;
+        }
+        AppCommand::Pause | AppCommand::Unpause => {
+            let pause = *command == AppCommand::Pause;
+            check_pause_cap(client, &mut report, active_addr, type_tag).await;
+            let status = deny::global_pause_status(client, type_tag.clone(), deny_list).await;
+            let outcome = status.map(|status| match status.next_epoch {
+                paused if paused != pause => Ok(()),
+                _ if pause => Err("global pause is already enabled".to_string()),
+                _ => Err("global pause is not enabled".to_string()),
+            });
+            let state = if pause { "not paused" } else { "paused" };
+            report.push_query(format!("{type_tag} is {state}"), outcome);
+        }
+        AppCommand::EmergencyFreeze {
+            pause,
+            addresses,
+            cold_address,
+        } => {
+            if *pause {
+                check_pause_cap(client, &mut report, active_addr, type_tag).await;
+            } else {
+                check_deny_cap(client, &mut report, active_addr, type_tag).await;
+            }
+            let outcome = if !pause && addresses.is_empty() && cold_address.is_none() {
+                Err("nothing to pause, deny or move".to_string())
+            } else if addresses.len() > deny::MAX_DENY_LIST_BATCH {
+                Err(format!(
+                    "{} addresses, at most {} fit in a transaction",
+                    addresses.len(),
+                    deny::MAX_DENY_LIST_BATCH
+                ))
+            } else {
+                Ok(())
+            };
+            report.push("Emergency freeze fits in a transaction", outcome);
+            if let Some(cold_address) = cold_address {
+                check_treasury_cap(client, &mut report, active_addr, type_tag, registry).await;
+                let outcome = match *cold_address == active_addr {
+                    true => Err("the caps are already owned by this address".to_string()),
+                    false => Ok(()),
+                };
+                report.push(
+                    format!("Cold address {cold_address} is not {active_addr}"),
+                    outcome,
+                );
+            }
+        }
+        AppCommand::TransferCaps(to_address) => {
+            check_treasury_cap(client, &mut report, active_addr, type_tag, registry).await;
+            check_deny_cap(client, &mut report, active_addr, type_tag).await;
+            let multisig = registry.multisigs.iter().find(|m| m.address == *to_address);
+            let outcome = if *to_address == active_addr {
+                Err("the caps are already owned by this address".to_string())
+            } else if registry.describe(*to_address).is_none() {
+                Err("not a multisig or key of the registry".to_string())
+            } else if let Some(Err(e)) = multisig.map(|multisig| multisig.public_key()) {
+                Err(e.to_string())
+            } else {
+                Ok(())
+            };
+            report.push(
+                format!("Recipient {to_address} is a known multisig or key"),
+                outcome,
+            );
+        }
+    }
+
+    let outcome = select_gas(client, active_addr, None, None, vec![], None)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string());
+    report.push(format!("{active_addr} has a gas coin"), outcome);
+
+    Ok(report)
+}
+
+async fn check_deny_cap<B: ChainBackend>(
+    client: &B,
+    report: &mut PreflightReport,
+    owner: SuiAddress,
+    type_tag: &TypeTag,
+) {
+    let outcome = deny::find_deny_cap(client, owner, type_tag.clone())
+        .await
+        .map(|cap| match cap {
+            Some(_) => Ok(()),
+            None => Err("no DenyCap owned by this address".to_string()),
+        });
+    report.push_query(format!("{owner} owns DenyCap<{type_tag}>"), outcome);
+}
+
+async fn check_pause_cap<B: ChainBackend>(
+    client: &B,
+    report: &mut PreflightReport,
+    owner: SuiAddress,
+    type_tag: &TypeTag,
+) {
+    let outcome = deny::find_deny_cap(client, owner, type_tag.clone())
+        .await
+        .map(|cap| match cap {
+            Some(cap) if cap.allow_global_pause => Ok(()),
+            Some(cap) => Err(format!(
+                "{} of this address does not allow global pause",
+                cap.version.struct_name()
+            )),
+            None => Err("no DenyCap owned by this address".to_string()),
+        });
+    report.push_query(
+        format!("{owner} owns a DenyCapV2<{type_tag}> allowing global pause"),
+        outcome,
+    );
+}
+
+/// Checks that `owner` holds the `TreasuryCap`, naming the owner the registry records otherwise.
+async fn check_treasury_cap<B: ChainBackend>(
+    client: &B,
+    report: &mut PreflightReport,
+    owner: SuiAddress,
+    type_tag: &TypeTag,
+    registry: &CoinRegistry,
+) {
+    let recorded = registry
+        .coins
+        .iter()
+        .find(|coin| coin.type_tag == *type_tag)
+        .map(|coin| coin.treasury_cap_owner)
+        .filter(|recorded| *recorded != owner);
+    let outcome = coin::find_treasury_cap(client, owner, type_tag.clone())
+        .await
+        .map(|cap| match (cap, recorded) {
+            (Some(_), _) => Ok(()),
+            (None, Some(recorded)) => Err(format!(
+                "no TreasuryCap owned by this address, the registry records it with {recorded}"
+            )),
+            (None, None) => Err("no TreasuryCap owned by this address".to_string()),
+        });
+    report.push_query(format!("{owner} owns TreasuryCap<{type_tag}>"), outcome);
+}
+
+async fn check_not_denied<B: ChainBackend>(
+    client: &B,
+    report: &mut PreflightReport,
+    type_tag: &TypeTag,
+    deny_list: (ObjectID, SequenceNumber),
+    addr: SuiAddress,
+) {
+    let outcome = deny::is_denied(client, type_tag.clone(), deny_list, addr)
+        .await
+        .map(|denied| match denied {
+            false => Ok(()),
+            true => Err("address is in the deny-list".to_string()),
+        });
+    report.push_query(format!("{addr} is not denied for {type_tag}"), outcome);
+}
+
+async fn check_coin<B: ChainBackend>(
+    client: &B,
+    report: &mut PreflightReport,
+    owner: SuiAddress,
+    type_tag: &TypeTag,
+    coin_id: ObjectID,
+) {
+    let outcome = coin::get_coin(client, coin_id, type_tag, owner)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string());
+    report.push(
+        format!("{coin_id} is a Coin<{type_tag}> owned by {owner}"),
+        outcome,
+    );
+}
@@ -0,0 +1,220 @@
+use std::fmt;
+use std::path::Path;
+
+use anyhow::Result;
+use move_core_types::language_storage::TypeTag;
+use serde::{Deserialize, Serialize};
+use sui_sdk::rpc_types::{
+    ObjectChange, SuiExecutionStatus, SuiTransactionBlockDataAPI, SuiTransactionBlockEffectsAPI,
+    SuiTransactionBlockResponse,
+};
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::coin::Coin;
+use sui_sdk::types::digests::TransactionDigest;
+use tracing::debug;
+
+use crate::backend::ChainBackend;
+use crate::follow::{checkpoints_after, latest_checkpoint};
+use crate::history::{ledger_entries, LedgerEntry};
+
+/// Something the compliance desk wants to know about, as soon as it is checkpointed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WatchEvent {
+    /// A mint, burn or deny-list change
+    Admin(LedgerEntry),
+    /// A transfer of at least the configured threshold, without mints or burns
+    LargeTransfer {
+        digest: TransactionDigest,
+        checkpoint: u64,
+        timestamp_ms: Option<u64>,
+        sender: SuiAddress,
+        recipient: SuiAddress,
+        amount: u64,
+    },
+    /// A transaction spending the coin that failed because the deny-list refused an address
+    FailedFromDenied {
+        digest: TransactionDigest,
+        checkpoint: u64,
+        timestamp_ms: Option<u64>,
+        sender: SuiAddress,
+        error: String,
+    },
+}
+
+impl WatchEvent {
+    pub fn digest(&self) -> TransactionDigest {
+        match self {
+            WatchEvent::Admin(entry) => entry.digest,
+            WatchEvent::LargeTransfer { digest, .. }
+            | WatchEvent::FailedFromDenied { digest, .. } => *digest,
+        }
+    }
+}
+
+impl fmt::Display for WatchEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchEvent::Admin(entry) => write!(f, "{entry}"),
+            WatchEvent::LargeTransfer {
+                digest,
+                checkpoint,
+                sender,
+                recipient,
+                amount,
+                ..
+            } => write!(
+                f,
+                "checkpoint {checkpoint}  {digest}  {sender}  LARGE TRANSFER {amount} to {recipient}"
+            ),
+            WatchEvent::FailedFromDenied {
+                digest,
+                checkpoint,
+                sender,
+                error,
+                ..
+            } => write!(
+                f,
+                "checkpoint {checkpoint}  {digest}  {sender}  FAILED FROM DENIED ADDRESS: {error}"
+            ),
+        }
+    }
+}
+
+/// Follows checkpoints and reports the [`WatchEvent`]s of one coin.
+#[derive(Debug, Clone)]
+pub struct Watcher {
+    pub type_tag: TypeTag,
+    /// The last checkpoint looked at. `None` starts from the latest checkpoint.
+    pub cursor: Option<u64>,
+    /// Transfers of at least this amount are reported. `None` reports no transfers.
+    pub large_transfer: Option<u64>,
+}
+
+impl Watcher {
+    pub fn new(type_tag: TypeTag, large_transfer: Option<u64>) -> Self {
+        Watcher {
+            type_tag,
+            cursor: None,
+            large_transfer,
+        }
+    }
+
+    /// Reports the events of every checkpoint after the cursor, and moves the cursor past them.
+    pub async fn poll<B: ChainBackend>(&mut self, client: &B) -> Result<Vec<WatchEvent>> {
+        if self.cursor.is_none() {
+            self.cursor = latest_checkpoint(client).await?;
+            debug!("Watching from checkpoint {:?}", self.cursor);
+            return Ok(vec![]);
+        }
+        let mut events = vec![];
+        loop {
+            let (checkpoints, has_next_page) = checkpoints_after(client, self.cursor).await?;
+            for checkpoint in checkpoints {
+                for resp in &checkpoint.transactions {
+                    events.extend(self.events(resp, checkpoint.sequence_number));
+                    events.extend(self.failed_from_denied(resp, checkpoint.sequence_number));
+                }
+                self.cursor = Some(checkpoint.sequence_number);
+            }
+            if !has_next_page {
+                break;
+            }
+        }
+        Ok(events)
+    }
+
+    /// The admin actions and large transfers of a transaction.
+    pub fn events(&self, resp: &SuiTransactionBlockResponse, checkpoint: u64) -> Vec<WatchEvent> {
+        let entries = ledger_entries(resp, &self.type_tag);
+        let mints_or_burns = !entries.is_empty();
+        let mut events: Vec<WatchEvent> = entries.into_iter().map(WatchEvent::Admin).collect();
+
+        let (Some(threshold), Some(tx)) = (self.large_transfer, &resp.transaction) else {
+            return events;
+        };
+        if mints_or_burns {
+            return events;
+        }
+        let sender = *tx.data.sender();
+        for change in resp.balance_changes.iter().flatten() {
+            let Ok(recipient) = change.owner.get_owner_address() else {
+                continue;
+            };
+            if change.coin_type == self.type_tag
+                && recipient != sender
+                && change.amount >= threshold as i128
+            {
+                events.push(WatchEvent::LargeTransfer {
+                    digest: resp.digest,
+                    checkpoint,
+                    timestamp_ms: resp.timestamp_ms,
+                    sender,
+                    recipient,
+                    amount: change.amount as u64,
+                });
+            }
+        }
+        events
+    }
+
+    /// A failed transaction with a `Coin<T>` of the coin among its inputs, whose execution error
+    /// is the deny-list's. Deny-list v1 rejects transactions using a denied address's coins
+    /// before execution, so only the execution-time refusals of v2 coins show up here.
+    pub fn failed_from_denied(
+        &self,
+        resp: &SuiTransactionBlockResponse,
+        checkpoint: u64,
+    ) -> Option<WatchEvent> {
+        let (Some(tx), Some(effects)) = (&resp.transaction, &resp.effects) else {
+            return None;
+        };
+        let SuiExecutionStatus::Failure { error } = effects.status() else {
+            return None;
+        };
+        if !is_deny_failure(error) || !self.spends_coin(resp) {
+            return None;
+        }
+        Some(WatchEvent::FailedFromDenied {
+            digest: resp.digest,
+            checkpoint,
+            timestamp_ms: resp.timestamp_ms,
+            sender: *tx.data.sender(),
+            error: error.clone(),
+        })
+    }
+
+    /// Whether a `Coin<T>` of the coin is an input of the transaction. Failed transactions
+    /// still bump the version of their inputs, so they show up as mutated or deleted.
+    fn spends_coin(&self, resp: &SuiTransactionBlockResponse) -> bool {
+        let coin_type = Coin::type_(self.type_tag.clone());
+        resp.object_changes.iter().flatten().any(|change| {
+            let (ObjectChange::Mutated { object_type, .. }
+            | ObjectChange::Deleted { object_type, .. }) = change
+            else {
+                return false;
+            };
+            *object_type == coin_type
+        })
+    }
+}
+
+/// Whether an execution error is the deny-list refusing an address: the `AddressDeniedForCoin`
+/// failure of v2 coins, or an abort in `0x2::deny_list`.
+pub fn is_deny_failure(error: &str) -> bool {
+    error.contains("AddressDeniedForCoin")
+        || (error.contains("MoveAbort") && error.contains("Identifier(\"deny_list\")"))
+}
+
+/// Reads the cursor saved by [`save_cursor`], if any.
+pub fn load_cursor(path: &Path) -> Result<Option<u64>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(std::fs::read_to_string(path)?.trim().parse()?))
+}
+
+pub fn save_cursor(path: &Path, cursor: u64) -> Result<()> {
+    std::fs::write(path, cursor.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,460 @@
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use move_core_types::language_storage::TypeTag;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::digests::TransactionDigest;
+use tracing::{debug, warn};
+
+use crate::history::{LedgerAction, LedgerEntry};
+use crate::watch::WatchEvent;
+
+pub const DEFAULT_PENDING_FILE: &str = "webhooks.pending.json";
+/// Header carrying `sha256=<hex HMAC-SHA256 of the body>`
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The kinds of compliance events a sink can subscribe to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    DenyListAdd,
+    DenyListRemove,
+    Pause,
+    Unpause,
+    Mint,
+    Burn,
+    SupplyChange,
+}
+
+/// A compliance event with the parsed arguments of the call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", content = "args", rename_all = "snake_case")]
+pub enum ComplianceEvent {
+    DenyListAdd {
+        address: SuiAddress,
+    },
+    DenyListRemove {
+        address: SuiAddress,
+    },
+    /// The coin was paused for every address
+    Pause,
+    Unpause,
+    Mint {
+        amount: u64,
+        recipient: Option<SuiAddress>,
+    },
+    Burn {
+        amount: u64,
+    },
+    /// The net supply change of a transaction that minted or burnt
+    SupplyChange {
+        minted: u64,
+        burnt: u64,
+    },
+}
+
+impl ComplianceEvent {
+    pub fn trigger(&self) -> Trigger {
+        match self {
+            ComplianceEvent::DenyListAdd { .. } => Trigger::DenyListAdd,
+            ComplianceEvent::DenyListRemove { .. } => Trigger::DenyListRemove,
+            ComplianceEvent::Pause => Trigger::Pause,
+            ComplianceEvent::Unpause => Trigger::Unpause,
+            ComplianceEvent::Mint { .. } => Trigger::Mint,
+            ComplianceEvent::Burn { .. } => Trigger::Burn,
+            ComplianceEvent::SupplyChange { .. } => Trigger::SupplyChange,
+        }
+    }
+}
+
+/// The JSON body posted to the sinks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    /// Unique per event, so receivers can drop retried deliveries: `<digest>:<index>`
+    pub id: String,
+    pub coin_type: String,
+    pub digest: TransactionDigest,
+    /// The sender of the transaction
+    pub actor: SuiAddress,
+    pub checkpoint: Option<u64>,
+    pub timestamp_ms: Option<u64>,
+    #[serde(flatten)]
+    pub event: ComplianceEvent,
+}
+
+/// A notification not delivered yet, and the sinks it is still owed to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingDelivery {
+    pub notification: Notification,
+    pub sinks: Vec<String>,
+}
+
+/// The deliveries that failed every attempt, stored as JSON so they outlive the cursor moving
+/// past their events and restarts of the watch. They are retried at each poll.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PendingDeliveries {
+    pub pending: Vec<PendingDelivery>,
+    /// Deliveries a sink refused with a 4xx response other than 429. They are not retried, and
+    /// kept for inspection.
+    #[serde(default)]
+    pub rejected: Vec<PendingDelivery>,
+}
+
+/// The sinks a notification was not delivered to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Undelivered {
+    /// Failed every attempt, eg. unreachable or answering 5xx
+    pub failed: Vec<String>,
+    /// Refused the notification with a 4xx response other than 429
+    pub rejected: Vec<String>,
+}
+
+impl Undelivered {
+    pub fn is_empty(&self) -> bool {
+        self.failed.is_empty() && self.rejected.is_empty()
+    }
+}
+
+/// A sink refused a delivery, which retrying would not change.
+#[derive(Debug)]
+struct Rejected(reqwest::StatusCode);
+
+impl std::fmt::Display for Rejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Rejected with {}", self.0)
+    }
+}
+
+impl std::error::Error for Rejected {}
+
+impl PendingDeliveries {
+    /// Files what `notification` was not delivered to: failed sinks into `undelivered`, to
+    /// retry, and rejecting sinks into `self.rejected`.
+    fn record(
+        &mut self,
+        undelivered: &mut Vec<PendingDelivery>,
+        notification: Notification,
+        result: Undelivered,
+    ) {
+        if !result.rejected.is_empty() {
+            self.rejected.push(PendingDelivery {
+                notification: notification.clone(),
+                sinks: result.rejected,
+            });
+        }
+        if !result.failed.is_empty() {
+            undelivered.push(PendingDelivery {
+                notification,
+                sinks: result.failed,
+            });
+        }
+    }
+
+    pub fn load_or_default(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(PendingDeliveries::default());
+        }
+        serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| anyhow!("Invalid pending webhook deliveries {}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Where to post notifications, stored as JSON:
+///
+/// ```json
+/// {
+///   "sinks": [
+///     {
+///       "name": "case-management",
+///       "url": "https://cases.example.com/hooks/usdx",
+///       "secret_env": "CASE_MANAGEMENT_WEBHOOK_SECRET",
+///       "triggers": ["deny_list_add", "deny_list_remove"]
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub sinks: Vec<WebhookSink>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookSink {
+    pub name: String,
+    pub url: String,
+    /// The environment variable holding the HMAC key, so the file can be shared without it
+    pub secret_env: String,
+    /// The events to post. Empty posts every event.
+    #[serde(default)]
+    pub triggers: Vec<Trigger>,
+    /// Mints below this amount are not posted
+    #[serde(default)]
+    pub mint_threshold: u64,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled after each failed attempt
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+}
+
+fn default_max_attempts() -> u32 {
+    5
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    1_000
+}
+
+impl WebhookConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let config: WebhookConfig = serde_json::from_str(&std::fs::read_to_string(path)?)
+            .map_err(|e| anyhow!("Invalid webhook config {}: {e}", path.display()))?;
+        for sink in &config.sinks {
+            if sink.max_attempts == 0 {
+                return Err(anyhow!("Webhook {} needs at least one attempt", sink.name));
+            }
+            sink.secret()?;
+        }
+        Ok(config)
+    }
+}
+
+impl WebhookSink {
+    fn secret(&self) -> Result<String> {
+        std::env::var(&self.secret_env).map_err(|_| {
+            anyhow!(
+                "Webhook {} needs its secret in environment variable {}",
+                self.name,
+                self.secret_env
+            )
+        })
+    }
+
+    /// Whether the sink subscribed to `notification`.
+    pub fn wants(&self, notification: &Notification) -> bool {
+        let event = &notification.event;
+        if !self.triggers.is_empty() && !self.triggers.contains(&event.trigger()) {
+            return false;
+        }
+        match event {
+            ComplianceEvent::Mint { amount, .. } => *amount >= self.mint_threshold,
+            _ => true,
+        }
+    }
+}
+
+/// The notifications for a batch of [`WatchEvent`]s: one per admin action, plus a supply change
+/// per transaction that minted or burnt. Large transfers and failed transactions are not posted.
+pub fn notifications(type_tag: &TypeTag, events: &[WatchEvent]) -> Vec<Notification> {
+    // Entries of a transaction are consecutive
+    let mut transactions: Vec<Vec<&LedgerEntry>> = vec![];
+    for event in events {
+        let WatchEvent::Admin(entry) = event else {
+            continue;
+        };
+        match transactions.last_mut() {
+            Some(tx_entries) if tx_entries[0].digest == entry.digest => tx_entries.push(entry),
+            _ => transactions.push(vec![entry]),
+        }
+    }
+    let mut notifications = vec![];
+    for tx_entries in transactions {
+        let (mut minted, mut burnt) = (0u64, 0u64);
+        let mut events = vec![];
+        for entry in &tx_entries {
+            let event = match &entry.action {
+                LedgerAction::DenyListAdd { address } => {
+                    ComplianceEvent::DenyListAdd { address: *address }
+                }
+                LedgerAction::DenyListRemove { address } => {
+                    ComplianceEvent::DenyListRemove { address: *address }
+                }
+                LedgerAction::Pause => ComplianceEvent::Pause,
+                LedgerAction::Unpause => ComplianceEvent::Unpause,
+                LedgerAction::Mint { amount, recipient } => {
+                    minted = minted.saturating_add(*amount);
+                    ComplianceEvent::Mint {
+                        amount: *amount,
+                        recipient: *recipient,
+                    }
+                }
+                LedgerAction::Burn { amount } => {
+                    burnt = burnt.saturating_add(*amount);
+                    ComplianceEvent::Burn { amount: *amount }
+                }
+                LedgerAction::Event { .. } => continue,
+            };
+            events.push(event);
+        }
+        if minted > 0 || burnt > 0 {
+            events.push(ComplianceEvent::SupplyChange { minted, burnt });
+        }
+        let entry = tx_entries[0];
+        notifications.extend(
+            events
+                .into_iter()
+                .enumerate()
+                .map(|(i, event)| Notification {
+                    id: format!("{}:{i}", entry.digest),
+                    coin_type: type_tag.to_string(),
+                    digest: entry.digest,
+                    actor: entry.actor,
+                    checkpoint: entry.checkpoint,
+                    timestamp_ms: entry.timestamp_ms,
+                    event,
+                }),
+        );
+    }
+    notifications
+}
+
+/// `sha256=<hex HMAC-SHA256 of body>`, the value of [`SIGNATURE_HEADER`].
+pub fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC takes keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Posts notifications to the sinks subscribed to them.
+pub struct Notifier {
+    config: WebhookConfig,
+    http: reqwest::Client,
+}
+
+impl Notifier {
+    pub fn new(config: WebhookConfig) -> Self {
+        Notifier {
+            config,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Posts `notification` to every subscribed sink. A failing sink does not stop delivery to
+    /// the others; the names of the sinks that failed every attempt or rejected it are returned.
+    pub async fn notify(&self, notification: &Notification) -> Result<Undelivered> {
+        let sinks: Vec<&WebhookSink> = self
+            .config
+            .sinks
+            .iter()
+            .filter(|sink| sink.wants(notification))
+            .collect();
+        self.post_all(notification, &sinks, None).await
+    }
+
+    /// Retries the `pending` deliveries, once per sink so a sink that is still down does not
+    /// hold up the watch, then posts `notifications`. What failed again, and the new
+    /// notifications that failed every attempt, are left in `pending`; what a sink rejected is
+    /// moved to `rejected`.
+    pub async fn deliver(
+        &self,
+        pending: &mut PendingDeliveries,
+        notifications: Vec<Notification>,
+    ) -> Result<()> {
+        let mut undelivered = vec![];
+        for delivery in std::mem::take(&mut pending.pending) {
+            // Sinks removed from the config since are dropped
+            let sinks: Vec<&WebhookSink> = self
+                .config
+                .sinks
+                .iter()
+                .filter(|sink| delivery.sinks.contains(&sink.name))
+                .collect();
+            let result = self
+                .post_all(&delivery.notification, &sinks, Some(1))
+                .await?;
+            pending.record(&mut undelivered, delivery.notification, result);
+        }
+        for notification in notifications {
+            let result = self.notify(&notification).await?;
+            pending.record(&mut undelivered, notification, result);
+        }
+        pending.pending = undelivered;
+        Ok(())
+    }
+
+    /// Posts `notification` to `sinks`, with `max_attempts` each or the attempts of the sink. A
+    /// failure is only warned about on the first delivery, not on the retries of pending ones.
+    async fn post_all(
+        &self,
+        notification: &Notification,
+        sinks: &[&WebhookSink],
+        max_attempts: Option<u32>,
+    ) -> Result<Undelivered> {
+        let body = serde_json::to_vec(notification)?;
+        let mut undelivered = Undelivered::default();
+        for sink in sinks {
+            let Err(e) = self
+                .post(sink, &body, max_attempts.unwrap_or(sink.max_attempts))
+                .await
+            else {
+                continue;
+            };
+            if e.downcast_ref::<Rejected>().is_some() {
+                warn!("Webhook {} rejected {}: {e}", sink.name, notification.id);
+                undelivered.rejected.push(sink.name.clone());
+                continue;
+            }
+            if max_attempts.is_none() {
+                warn!(
+                    "Webhook {} pending for {}: {e:#}",
+                    sink.name, notification.id
+                );
+            } else {
+                debug!(
+                    "Webhook {} failed again for {}: {e:#}",
+                    sink.name, notification.id
+                );
+            }
+            undelivered.failed.push(sink.name.clone());
+        }
+        Ok(undelivered)
+    }
+
+    /// Posts `body`, retrying with exponential backoff on network errors, 429 and 5xx responses.
+    async fn post(&self, sink: &WebhookSink, body: &[u8], max_attempts: u32) -> Result<()> {
+        let signature = sign(sink.secret()?.as_bytes(), body);
+        let mut backoff = Duration::from_millis(sink.initial_backoff_ms);
+        let mut attempt = 1;
+        loop {
+            let result = self
+                .http
+                .post(&sink.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, &signature)
+                .body(body.to_vec())
+                .send()
+                .await;
+            let error = match result {
+                Ok(resp) if resp.status().is_success() => {
+                    debug!("Webhook {} accepted on attempt {attempt}", sink.name);
+                    return Ok(());
+                }
+                Ok(resp)
+                    if resp.status().is_client_error()
+                        && resp.status() != reqwest::StatusCode::TOO_MANY_REQUESTS =>
+                {
+                    return Err(Rejected(resp.status()).into());
+                }
+                Ok(resp) => anyhow!("Responded {}", resp.status()),
+                Err(e) => anyhow!(e),
+            };
+            if attempt >= max_attempts {
+                return Err(error.context(format!("Gave up after {attempt} attempts")));
+            }
+            debug!("Webhook {} attempt {attempt} failed: {error}", sink.name);
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            attempt += 1;
+        }
+    }
+}
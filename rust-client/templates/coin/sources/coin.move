@@ -0,0 +1,28 @@
+module {{package}}::{{module}} {
+    use std::option;
+
+    use sui::coin;
+    use sui::transfer;
+    use sui::tx_context::{Self, TxContext};
+
+    struct {{otw}} has drop {}
+
+    fun init(otw: {{otw}}, ctx: &mut TxContext) {
+        // Generated by `rust-client new-coin`
+        let (treasury_cap, deny_cap, meta_data) = coin::create_regulated_currency_v2(
+            otw,
+            {{decimals}},
+            b"{{symbol}}",
+            b"{{name}}",
+            b"{{description}}",
+            {{icon_url}},
+            {{allow_global_pause}},
+            ctx
+        );
+
+        let sender = tx_context::sender(ctx);
+        transfer::public_transfer(treasury_cap, sender);
+        transfer::public_transfer(deny_cap, sender);
+        transfer::public_transfer(meta_data, sender);
+    }
+}
@@ -0,0 +1,171 @@
+// Each test uses some of these helpers, the ones using a local network or the mock ones
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use move_core_types::language_storage::TypeTag;
+use rust_client::backend::mock::MockBackend;
+use rust_client::publish::{publish, PackageIds, PublishedCoin};
+use rust_client::registry::{CoinRegistry, KnownMultisig};
+use rust_client::tx_run::AppConfig;
+use sui_keys::keystore::{AccountKeystore, InMemKeystore, Keystore};
+use sui_sdk::rpc_types::{ObjectChange, SuiTransactionBlockResponse};
+use sui_sdk::types::base_types::{ObjectRef, SuiAddress};
+use sui_sdk::types::crypto::{EncodeDecodeBase64, PublicKey};
+use sui_sdk::types::multisig::MultiSigPublicKey;
+use sui_sdk::types::parse_sui_type_tag;
+use test_cluster::{TestCluster, TestClusterBuilder};
+
+/// The coin of the mock chain and of recorded fixtures.
+pub const COIN_TYPE: &str =
+    "0x5da522e939ce9fdcb15d4b3d03a16aa408706105cf90114cedc9613809f04c20::regulated_coin::REGULATED_COIN";
+
+/// Number of generated accounts. The first one publishes the coin and owns its caps.
+pub const NUM_ACCOUNTS: usize = 3;
+const FUNDING_AMOUNT: u64 = 100_000_000_000;
+
+/// A local Sui network running in-process, with a freshly published regulated coin.
+pub struct TestEnv {
+    pub cluster: TestCluster,
+    pub type_tag: TypeTag,
+    pub published: PublishedCoin,
+    pub admin: SuiAddress,
+    pub users: Vec<SuiAddress>,
+}
+
+impl TestEnv {
+    pub async fn new() -> Result<Self> {
+        let cluster = TestClusterBuilder::new().build().await;
+        let addresses = keystore().addresses();
+        let rgp = cluster.get_reference_gas_price().await;
+        for addr in &addresses {
+            cluster
+                .fund_address_and_return_gas(rgp, Some(FUNDING_AMOUNT), *addr)
+                .await;
+        }
+
+        let admin = addresses[0];
+        let keystore = keystore();
+        let published = publish(
+            cluster.sui_client(),
+            keystore.get_key(&admin)?,
+            &move_package_path(),
+        )
+        .await?;
+
+        Ok(Self {
+            cluster,
+            type_tag: published.type_tag.clone(),
+            published,
+            admin,
+            users: addresses[1..].to_vec(),
+        })
+    }
+
+    /// A config signing as `addr`, which must be one of the generated accounts.
+    pub fn config(&self, addr: SuiAddress) -> AppConfig {
+        AppConfig {
+            client: self.cluster.sui_client().clone(),
+            keystore: keystore(),
+            active_address: addr,
+            type_tag: self.type_tag.clone(),
+            packages: PackageIds::new(&self.type_tag, None).unwrap(),
+            registry: CoinRegistry::default(),
+            policy: None,
+            audit_log: None,
+            screeners: vec![],
+        }
+    }
+}
+
+/// Keys are derived from a fixed seed, so every call returns the same accounts.
+pub fn keystore() -> Keystore {
+    Keystore::from(InMemKeystore::new_insecure_for_tests(NUM_ACCOUNTS))
+}
+
+pub fn move_package_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../move/regulated_coin_example")
+}
+
+/// The address whose 64 hex digits are all `hex_digit`.
+pub fn address(hex_digit: char) -> SuiAddress {
+    SuiAddress::from_str(&format!("0x{}", hex_digit.to_string().repeat(64))).unwrap()
+}
+
+/// The first object created by a transaction, eg. the coin of a mint.
+pub fn created_object(resp: &SuiTransactionBlockResponse) -> Result<ObjectRef> {
+    resp.object_changes
+        .as_ref()
+        .and_then(|changes| {
+            changes
+                .iter()
+                .find(|change| matches!(change, ObjectChange::Created { .. }))
+        })
+        .map(|created| created.object_ref())
+        .ok_or(anyhow!("No object created"))
+}
+
+/// A 2-of-3 multisig of the first three keys of `keystore`.
+pub fn multisig(keystore: &Keystore, name: &str) -> Result<KnownMultisig> {
+    let public_keys = keystore.addresses()[..3]
+        .iter()
+        .map(|address| Ok(keystore.get_key(address)?.public()))
+        .collect::<Result<Vec<PublicKey>>>()?;
+    let weights = vec![1, 1, 1];
+    let multisig_key = MultiSigPublicKey::new(public_keys.clone(), weights.clone(), 2)
+        .map_err(|e| anyhow!("{e}"))?;
+    Ok(KnownMultisig {
+        name: name.to_string(),
+        address: SuiAddress::from(&multisig_key),
+        public_keys: public_keys.iter().map(|key| key.encode_base64()).collect(),
+        weights,
+        threshold: 2,
+    })
+}
+
+/// How the coin of a [`mock_config`] was created.
+pub enum MockCoin {
+    /// `coin::create_regulated_currency`
+    V1,
+    /// `coin::create_regulated_currency_v2`
+    V2 { allow_global_pause: bool },
+}
+
+/// A mock chain with a coin of [`COIN_TYPE`] whose caps are owned by the first of `accounts`
+/// generated keys, which signs and has gas. Returns the config and the other addresses.
+pub fn mock_config(
+    accounts: usize,
+    coin: MockCoin,
+) -> Result<(AppConfig<MockBackend>, Vec<SuiAddress>)> {
+    let keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(accounts));
+    let addresses = keystore.addresses();
+    let admin = addresses[0];
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+
+    let client = MockBackend::new();
+    match coin {
+        MockCoin::V1 => {
+            client.create_regulated_coin(type_tag.clone(), admin);
+        }
+        MockCoin::V2 { allow_global_pause } => {
+            client.create_regulated_coin_v2(type_tag.clone(), admin, allow_global_pause);
+        }
+    }
+    client.add_gas(admin, 1_000_000_000);
+    Ok((
+        AppConfig {
+            client,
+            keystore,
+            active_address: admin,
+            packages: PackageIds::new(&type_tag, None)?,
+            type_tag,
+            registry: CoinRegistry::default(),
+            policy: None,
+            audit_log: None,
+            screeners: vec![],
+        },
+        addresses[1..].to_vec(),
+    ))
+}
@@ -0,0 +1,168 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use rust_client::audit::{AuditLog, AuditStatus, SignedAuditRecord};
+use rust_client::backend::mock::{DenyReasonEvent, MockBackend};
+use rust_client::tx_run::{
+    execute_command, execute_command_with, AppCommand, AppConfig, CommandContext,
+};
+use sui_keys::keystore::AccountKeystore;
+
+use common::{mock_config, MockCoin};
+
+/// A mock chain with a regulated coin whose caps are owned by the admin, recording to a new log.
+fn setup(name: &str) -> Result<(AppConfig<MockBackend>, PathBuf)> {
+    let (mut config, _) = mock_config(2, MockCoin::V1)?;
+    let path = std::env::temp_dir().join(format!("{name}-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    config.audit_log = Some(AuditLog::new(&path));
+    Ok((config, path))
+}
+
+fn records(path: &Path) -> Result<Vec<SignedAuditRecord>> {
+    std::fs::read_to_string(path)?
+        .lines()
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+fn write_lines(path: &Path, lines: &[String]) -> Result<()> {
+    std::fs::write(path, lines.join("\n") + "\n")?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_records() -> Result<()> {
+    let (config, path) = setup("audit-records")?;
+    let user = config.keystore.addresses()[1];
+
+    let resp = execute_command(AppCommand::MintAndTransfer(10_000, user), &config).await?;
+    let context = CommandContext {
+        ticket: Some("OPS-12".to_string()),
+        ..CommandContext::default()
+    };
+    execute_command_with(AppCommand::DenyListAdd(user), &context, &config).await?;
+    execute_command(AppCommand::DenyListRemove(user), &config).await?;
+    // Refused by the pre-flight checks, as the address is no longer denied
+    assert!(execute_command(AppCommand::DenyListRemove(user), &config)
+        .await
+        .is_err());
+
+    let records = records(&path)?;
+    assert_eq!(records.len(), 4);
+    let first = &records[0].record;
+    assert_eq!(first.seq, 0);
+    assert_eq!(first.operator, config.active_address);
+    assert_eq!(first.command, AppCommand::MintAndTransfer(10_000, user));
+    assert_eq!(first.digest, Some(resp.digest));
+    assert_eq!(first.status, AuditStatus::Success);
+    assert_eq!(records[1].record.context, context);
+    assert_eq!(records[1].record.prev_hash, records[0].hash);
+    let refused = &records[3].record;
+    assert_eq!(refused.status, AuditStatus::Error);
+    assert_eq!(refused.digest, None);
+    assert!(refused.error.is_some());
+
+    let report = AuditLog::new(&path).verify()?;
+    assert!(report.problems.is_empty(), "{:?}", report.problems);
+    assert_eq!(report.records, 4);
+    assert_eq!(report.head.as_ref(), Some(&records[3].hash));
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tampering() -> Result<()> {
+    let (config, path) = setup("audit-tampering")?;
+    let user = config.keystore.addresses()[1];
+    for amount in [100, 200, 300] {
+        execute_command(AppCommand::MintAndTransfer(amount, user), &config).await?;
+    }
+    let log = AuditLog::new(&path);
+    let lines: Vec<String> = std::fs::read_to_string(&path)?
+        .lines()
+        .map(String::from)
+        .collect();
+
+    // Edited amount
+    let mut edited = lines.clone();
+    edited[1] = edited[1].replace("[200,", "[20,");
+    write_lines(&path, &edited)?;
+    let problems = log.verify()?.problems;
+    assert_eq!(problems, ["line 2: edited, its hash does not match"]);
+
+    // Removed record
+    write_lines(&path, &[lines[0].clone(), lines[2].clone()])?;
+    let problems = log.verify()?.problems;
+    assert_eq!(problems.len(), 2);
+    assert!(problems[0].contains("expected record 1, found 2"));
+
+    // Re-hashed after an edit, but not signed by the operator
+    let mut record: SignedAuditRecord = serde_json::from_str(&lines[2])?;
+    record.record.command = AppCommand::MintAndTransfer(30, user);
+    record.hash = record.record.hash()?;
+    let rehashed = serde_json::to_string(&record)?;
+    write_lines(&path, &[lines[0].clone(), lines[1].clone(), rehashed])?;
+    let problems = log.verify()?.problems;
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].starts_with("line 3: not signed by its operator"));
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reasons() -> Result<()> {
+    let (config, path) = setup("audit-reasons")?;
+    let user = config.keystore.addresses()[1];
+
+    let local = CommandContext {
+        reason: Some("SANCTIONS".to_string()),
+        case: Some("CASE-7".to_string()),
+        ..CommandContext::default()
+    };
+    execute_command_with(AppCommand::DenyListAdd(user), &local, &config).await?;
+    assert!(config.client.deny_reasons(&config.type_tag).is_empty());
+
+    let on_chain = CommandContext {
+        reason: Some("CLEARED".to_string()),
+        on_chain: true,
+        ..CommandContext::default()
+    };
+    execute_command_with(AppCommand::DenyListRemove(user), &on_chain, &config).await?;
+    assert!(!config.client.is_denied(&config.type_tag, user));
+    assert_eq!(
+        config.client.deny_reasons(&config.type_tag),
+        [DenyReasonEvent {
+            addr: user,
+            added: false,
+            reason: "CLEARED".to_string(),
+            case_id: String::new(),
+        }]
+    );
+
+    // Nothing to record on chain
+    let empty = CommandContext {
+        on_chain: true,
+        ..CommandContext::default()
+    };
+    assert!(
+        execute_command_with(AppCommand::DenyListAdd(user), &empty, &config)
+            .await
+            .is_err()
+    );
+    assert!(!config.client.is_denied(&config.type_tag, user));
+
+    let records = records(&path)?;
+    assert_eq!(records[0].record.context, local);
+    assert_eq!(records[1].record.context, on_chain);
+    // Unset reasons are left out, so records written before them keep their hashes
+    let line = std::fs::read_to_string(&path)?;
+    let first: serde_json::Value = serde_json::from_str(line.lines().next().unwrap())?;
+    assert!(first["context"].get("on_chain").is_none());
+    assert!(AuditLog::new(&path).verify()?.problems.is_empty());
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
@@ -1,99 +1,60 @@
-use std::str::FromStr;
+mod common;
 
 use anyhow::{anyhow, Result};
-use move_core_types::account_address::AccountAddress;
-use move_core_types::identifier::Identifier;
-use move_core_types::language_storage::{StructTag, TypeTag};
-use rust_client::tx_run::{execute_command, AppCommand, AppConfig};
-use sui_config::{sui_config_dir, SUI_CLIENT_CONFIG, SUI_KEYSTORE_FILENAME};
-use sui_keys::keystore::{AccountKeystore, FileBasedKeystore};
-use sui_sdk::rpc_types::{ObjectChange, SuiTransactionBlockResponse};
-use sui_sdk::types::base_types::{ObjectID, ObjectRef, SuiAddress};
-use sui_sdk::wallet_context::WalletContext;
+use rust_client::backend::ChainBackend;
+use rust_client::tx_run::{execute_command, AppCommand};
+use shared_crypto::intent::{Intent, IntentMessage};
+use sui_keys::keystore::AccountKeystore;
+use sui_sdk::types::crypto::Signature;
+use sui_sdk::types::programmable_transaction_builder::ProgrammableTransactionBuilder;
+use sui_sdk::types::transaction::{Transaction, TransactionData};
 
-// Change from here
-const PACKAGE_ID: &'static str =
-    "0x5da522e939ce9fdcb15d4b3d03a16aa408706105cf90114cedc9613809f04c20";
-const MODULE: &'static str = "regulated_coin";
-// To here
+use common::{created_object, keystore, TestEnv};
 
-fn cmd_sui_client_switch(new_addr: SuiAddress) -> Result<()> {
-    println!("SWITCHING TO ADDRESS: {new_addr}");
-    let sui_client_switch = format!("sui client switch --address {new_addr}");
-    let _ = std::process::Command::new("sh")
-        .arg("-c")
-        .arg(sui_client_switch)
-        .output()?;
-    Ok(())
-}
-
-fn get_other_address(different_from: SuiAddress) -> Result<SuiAddress> {
-    let keystore = FileBasedKeystore::new(&sui_config_dir()?.join(SUI_KEYSTORE_FILENAME))?;
-    Ok(keystore
-        .keys()
-        .into_iter()
-        .find(|pub_key| SuiAddress::from(pub_key) != different_from)
-        .map(|pub_key| SuiAddress::from(&pub_key))
-        .ok_or(anyhow!("No other address found"))?)
-}
-
-async fn get_config() -> Result<AppConfig> {
-    let package_id = ObjectID::from_hex_literal(PACKAGE_ID)?;
-    let otw = MODULE.to_uppercase();
-    let type_tag = TypeTag::Struct(Box::new(StructTag {
-        address: AccountAddress::new(package_id.as_ref().try_into()?),
-        module: Identifier::from_str(MODULE)?,
-        name: Identifier::from_str(&otw)?,
-        type_params: vec![],
-    }));
-    let wallet_context =
-        WalletContext::new(&sui_config_dir()?.join(SUI_CLIENT_CONFIG), None, None).await?;
-
-    Ok(AppConfig {
-        client: wallet_context.get_client().await?,
-        wallet_context,
-        type_tag,
-    })
-}
+const GAS_BUDGET: u64 = 50_000_000;
 
 #[tokio::test]
 async fn test_is_blocked() -> Result<()> {
-    let mut config = get_config().await?;
-    let admin_addr = config.wallet_context.active_address()?;
-    let deny_addr = get_other_address(admin_addr)?;
-
-    let command = AppCommand::DenyListAdd(deny_addr);
-    println!("CURRENT_ADDRESS: {admin_addr}");
-    let _ = execute_command(command, config).await?;
+    let env = TestEnv::new().await?;
+    let admin_addr = env.admin;
+    let deny_addr = env.users[0];
+    let config = env.config(admin_addr);
 
     let command = AppCommand::MintAndTransfer(10000, deny_addr);
-    let resp_mint = execute_command(command, get_config().await?).await?;
-    let coin: ObjectRef = resp_mint
-        .object_changes
-        .unwrap()
-        .into_iter()
-        .find(|obj_chng| match obj_chng {
-            ObjectChange::Created { .. } => true,
-            _ => false,
-        })
-        .map(|created| created.object_ref())
-        .ok_or(anyhow!("No coin created"))?;
-
-    cmd_sui_client_switch(deny_addr)?;
-    // Wrap in function to ensure client will switch to initial
-    async fn run_as_deny_addr(
-        coin_id: ObjectID,
-        transfer_to: SuiAddress,
-    ) -> Result<SuiTransactionBlockResponse> {
-        let config = get_config().await?;
-        let command = AppCommand::Transfer(coin_id, transfer_to);
-        execute_command(command, config).await
-    }
-    let resp2 = run_as_deny_addr(coin.0, admin_addr).await; // Notice we do not use '?' so that
-                                                            // cmd_sui_client_switch runs again
-    cmd_sui_client_switch(admin_addr)?;
-    assert!(resp2.is_err());
-    assert!(get_config().await?.wallet_context.active_address()? == admin_addr);
+    let coin = created_object(&execute_command(command, &config).await?)?;
+
+    execute_command(AppCommand::DenyListAdd(deny_addr), &config).await?;
+
+    // Refused by the pre-flight checks of the client
+    let deny_config = env.config(deny_addr);
+    let resp = execute_command(AppCommand::Transfer(coin.0, admin_addr), &deny_config).await;
+    assert!(resp.is_err());
+
+    // Built and signed without the client's checks, the transfer is rejected by the chain
+    let client = env.cluster.sui_client();
+    let gas = client
+        .coin_read_api()
+        .get_coins(deny_addr, None, None, None)
+        .await?
+        .data
+        .first()
+        .ok_or(anyhow!("No gas coin for {deny_addr}"))?
+        .object_ref();
+    let mut builder = ProgrammableTransactionBuilder::new();
+    builder.transfer_object(admin_addr, coin)?;
+    let tx_data = TransactionData::new_programmable(
+        deny_addr,
+        vec![gas],
+        builder.finish(),
+        GAS_BUDGET,
+        client.get_reference_gas_price().await?,
+    );
+    let msg = IntentMessage::new(Intent::sui_transaction(), tx_data);
+    let sig = Signature::new_secure(&msg, keystore().get_key(&deny_addr)?);
+    let e = ChainBackend::execute(client, Transaction::from_data(msg.value, vec![sig]))
+        .await
+        .unwrap_err();
+    assert!(e.to_string().contains("denied"), "{e}");
 
     Ok(())
 }
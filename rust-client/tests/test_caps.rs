@@ -0,0 +1,170 @@
+mod common;
+
+use anyhow::Result;
+use rust_client::backend::mock::MockBackend;
+use rust_client::caps;
+use rust_client::registry::{CoinRegistry, KnownAddress, ManagedCoin};
+use rust_client::tx_run::{
+    execute_command, preflight, AppCommand, AppConfig, CommandContext, DenyCapVersion,
+};
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::object::Owner;
+use sui_sdk::types::parse_sui_type_tag;
+
+use common::{mock_config, multisig, MockCoin, COIN_TYPE};
+
+/// A mock chain with a regulated coin whose caps are owned by the admin, a registry with a
+/// multisig of the first keys and a key, and an address the registry does not know.
+fn setup() -> Result<(AppConfig<MockBackend>, SuiAddress, SuiAddress, SuiAddress)> {
+    let (mut config, users) = mock_config(
+        4,
+        MockCoin::V2 {
+            allow_global_pause: false,
+        },
+    )?;
+    let (key, unknown) = (users[1], users[2]);
+    let multisig = multisig(&config.keystore, "treasury-2-of-3")?;
+    let address = multisig.address;
+    config.registry = CoinRegistry {
+        keys: vec![KnownAddress {
+            name: "alice".to_string(),
+            address: key,
+        }],
+        multisigs: vec![multisig],
+        ..CoinRegistry::default()
+    };
+    Ok((config, address, key, unknown))
+}
+
+async fn preflight_passes(config: &AppConfig<MockBackend>, to: SuiAddress) -> Result<bool> {
+    let report = preflight::run(
+        &config.client,
+        config.active_address,
+        &config.type_tag,
+        &config.registry,
+        &AppCommand::TransferCaps(to),
+    )
+    .await?;
+    Ok(report.passed())
+}
+
+#[tokio::test]
+async fn test_show() -> Result<()> {
+    let (config, _, _, unknown) = setup()?;
+    let admin = config.active_address;
+    let report = caps::show(&config.client, &config.type_tag, &config.registry).await?;
+    assert!(report.missing.is_empty());
+    let treasury_cap = report.get("TreasuryCap").unwrap();
+    assert_eq!(treasury_cap.owner, Owner::AddressOwner(admin));
+    assert_eq!(treasury_cap.known_as, None);
+    let deny_cap = report.get(DenyCapVersion::V2.struct_name()).unwrap();
+    assert_eq!(deny_cap.owner, Owner::AddressOwner(admin));
+    assert_eq!(
+        report.get("CoinMetadata").unwrap().owner,
+        Owner::AddressOwner(admin)
+    );
+
+    // Found by their ids, whoever owns them
+    let other = parse_sui_type_tag(&COIN_TYPE.replace("REGULATED_COIN", "OTHER"))?;
+    config.client.create_regulated_coin(other.clone(), unknown);
+    let report = caps::show(&config.client, &other, &config.registry).await?;
+    assert!(report.missing.is_empty());
+    let treasury_cap = report.get("TreasuryCap").unwrap();
+    assert_eq!(treasury_cap.owner, Owner::AddressOwner(unknown));
+    assert_eq!(treasury_cap.known_as, None);
+
+    // A coin that is not regulated has no deny-cap
+    let plain = parse_sui_type_tag(&COIN_TYPE.replace("REGULATED_COIN", "PLAIN"))?;
+    config.client.create_coin(plain.clone(), unknown);
+    let report = caps::show(&config.client, &plain, &config.registry).await?;
+    assert_eq!(report.missing, vec!["DenyCap"]);
+    assert!(report.to_string().contains("never created"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer() -> Result<()> {
+    let (config, multisig, _, _) = setup()?;
+    let admin = config.active_address;
+    let context = CommandContext {
+        ticket: Some("CHG-12".to_string()),
+        ..CommandContext::default()
+    };
+    let dry_run = caps::dry_run(&config, multisig, &context).await?;
+    assert!(dry_run.error.is_none());
+
+    let registry_path =
+        std::env::temp_dir().join(format!("caps-registry-{}.json", std::process::id()));
+    let transfer = caps::transfer(&config, multisig, &context, &registry_path).await?;
+    for name in ["TreasuryCap", "DenyCapV2"] {
+        let cap = transfer.caps.get(name).unwrap();
+        assert_eq!(cap.owner, Owner::AddressOwner(multisig));
+        assert_eq!(cap.known_as.as_deref(), Some("multisig treasury-2-of-3"));
+    }
+    // The coin is not registered
+    assert!(!transfer.recorded);
+    assert!(!registry_path.exists());
+
+    let report = caps::show(&config.client, &config.type_tag, &config.registry).await?;
+    assert_eq!(
+        report.get("TreasuryCap").unwrap().owner,
+        Owner::AddressOwner(multisig)
+    );
+    // The active address can no longer mint, nor move the caps again
+    assert!(
+        execute_command(AppCommand::MintAndTransfer(100, admin), &config)
+            .await
+            .is_err()
+    );
+    assert!(caps::transfer(&config, multisig, &context, &registry_path)
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_recipient() -> Result<()> {
+    let (mut config, multisig, key, unknown) = setup()?;
+    let admin = config.active_address;
+    assert!(!preflight_passes(&config, unknown).await?);
+    assert!(!preflight_passes(&config, admin).await?);
+    assert!(preflight_passes(&config, key).await?);
+    assert!(preflight_passes(&config, multisig).await?);
+
+    let registry_path =
+        std::env::temp_dir().join(format!("caps-recipient-{}.json", std::process::id()));
+    assert!(
+        caps::transfer(&config, unknown, &CommandContext::default(), &registry_path)
+            .await
+            .is_err()
+    );
+    let report = caps::show(&config.client, &config.type_tag, &config.registry).await?;
+    assert_eq!(
+        report.get("TreasuryCap").unwrap().owner,
+        Owner::AddressOwner(admin)
+    );
+
+    // A multisig whose keys do not derive its address
+    config.registry.multisigs[0].threshold = 3;
+    assert!(!preflight_passes(&config, multisig).await?);
+
+    config.registry.coins.push(ManagedCoin {
+        alias: "REG".to_string(),
+        type_tag: config.type_tag.clone(),
+        treasury_cap_owner: admin,
+        deny_cap_owner: admin,
+        latest_package_id: None,
+    });
+    let transfer = caps::transfer(&config, key, &CommandContext::default(), &registry_path).await?;
+    assert!(transfer.recorded);
+    let report = caps::show(&config.client, &config.type_tag, &config.registry).await?;
+    assert_eq!(
+        report.get("DenyCapV2").unwrap().known_as.as_deref(),
+        Some("key alice")
+    );
+    let saved = CoinRegistry::load(&registry_path)?;
+    assert_eq!(saved.coins[0].treasury_cap_owner, key);
+    assert_eq!(saved.coins[0].deny_cap_owner, key);
+    std::fs::remove_file(&registry_path)?;
+    Ok(())
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use anyhow::Result;
+use rust_client::tx_run::{execute_command, get_deny_list, is_denied, AppCommand};
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::SuiClient;
+
+use common::{created_object, TestEnv};
+
+async fn balance(client: &SuiClient, env: &TestEnv, owner: SuiAddress) -> Result<u128> {
+    Ok(client
+        .coin_read_api()
+        .get_balance(owner, Some(env.type_tag.to_string()))
+        .await?
+        .total_balance)
+}
+
+#[tokio::test]
+async fn test_mint_and_transfer() -> Result<()> {
+    let env = TestEnv::new().await?;
+    let config = env.config(env.admin);
+    let user = env.users[0];
+
+    execute_command(AppCommand::MintAndTransfer(10_000, user), &config).await?;
+    assert_eq!(balance(&config.client, &env, user).await?, 10_000);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer() -> Result<()> {
+    let env = TestEnv::new().await?;
+    let config = env.config(env.admin);
+    let (user, other) = (env.users[0], env.users[1]);
+
+    let resp = execute_command(AppCommand::MintAndTransfer(10_000, user), &config).await?;
+    let coin = created_object(&resp)?;
+    execute_command(AppCommand::Transfer(coin.0, other), &env.config(user)).await?;
+    assert_eq!(balance(&config.client, &env, user).await?, 0);
+    assert_eq!(balance(&config.client, &env, other).await?, 10_000);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_burn() -> Result<()> {
+    let env = TestEnv::new().await?;
+    let config = env.config(env.admin);
+
+    let resp = execute_command(AppCommand::MintAndTransfer(10_000, env.admin), &config).await?;
+    let coin = created_object(&resp)?;
+    execute_command(AppCommand::Burn(coin.0), &config).await?;
+    let supply = config
+        .client
+        .coin_read_api()
+        .get_total_supply(env.type_tag.to_string())
+        .await?;
+    assert_eq!(supply.value, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deny_add_and_remove() -> Result<()> {
+    let env = TestEnv::new().await?;
+    let config = env.config(env.admin);
+    let user = env.users[0];
+    let deny_list = get_deny_list(&config.client).await?;
+
+    execute_command(AppCommand::DenyListAdd(user), &config).await?;
+    assert!(is_denied(&config.client, env.type_tag.clone(), deny_list, user).await?);
+
+    execute_command(AppCommand::DenyListRemove(user), &config).await?;
+    assert!(!is_denied(&config.client, env.type_tag.clone(), deny_list, user).await?);
+    Ok(())
+}
@@ -0,0 +1,200 @@
+mod common;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use rust_client::backend::mock::MockBackend;
+use rust_client::backend::ChainBackend;
+use rust_client::emergency;
+use rust_client::policy::{MintLedger, Policy, PolicyEngine, TimeOfDay, TimeWindow};
+use rust_client::tx_run::{
+    execute_command, get_deny_cap, get_treasury_cap, preflight, AppCommand, AppConfig,
+    CommandContext,
+};
+use sui_sdk::rpc_types::SuiObjectDataOptions;
+use sui_sdk::types::base_types::ObjectID;
+use sui_sdk::types::object::Owner;
+
+use common::{mock_config, MockCoin};
+
+async fn owner(config: &AppConfig<MockBackend>, id: ObjectID) -> Result<Option<Owner>> {
+    let resp = config
+        .client
+        .get_object_with_options(id, SuiObjectDataOptions::new().with_owner())
+        .await?;
+    Ok(resp.data.and_then(|data| data.owner))
+}
+
+#[tokio::test]
+async fn test_emergency_freeze() -> Result<()> {
+    let (config, users) = mock_config(
+        4,
+        MockCoin::V2 {
+            allow_global_pause: true,
+        },
+    )?;
+    let cold = users[2];
+    let admin = config.active_address;
+    let deny_cap = get_deny_cap(&config.client, admin, config.type_tag.clone()).await?;
+    let treasury_cap = get_treasury_cap(&config.client, admin, config.type_tag.clone()).await?;
+
+    // Each address once
+    let addresses = [users[0], users[1], users[0]];
+    let plan = emergency::plan(&config, &addresses, Some(cold)).await?;
+    assert!(plan.pause());
+    assert_eq!(plan.deny, vec![users[0], users[1]]);
+    assert_eq!(plan.treasury_cap, Some(treasury_cap.0));
+    assert_eq!(plan.confirmation_phrase(), "FREEZE REGULATED_COIN");
+    assert!(plan.to_string().contains("ENABLE"));
+    let report = preflight::run(
+        &config.client,
+        admin,
+        &config.type_tag,
+        &config.registry,
+        &plan.command(),
+    )
+    .await?;
+    assert!(report.passed());
+
+    let context = CommandContext {
+        ticket: Some("INC-4711".to_string()),
+        ..CommandContext::default()
+    };
+    let report = emergency::freeze(&config, plan, &context).await?;
+    assert!(report.complete());
+    assert!(report.paused.next_epoch);
+    assert!(report
+        .denied
+        .iter()
+        .all(|(_, status)| !status.current_epoch && status.next_epoch));
+    assert!(config.client.is_paused(&config.type_tag));
+    assert!(config.client.is_denied(&config.type_tag, users[1]));
+    assert_eq!(
+        owner(&config, deny_cap.object_ref.0).await?,
+        Some(Owner::AddressOwner(cold))
+    );
+    assert_eq!(
+        owner(&config, treasury_cap.0).await?,
+        Some(Owner::AddressOwner(cold))
+    );
+    assert_eq!(report.caps.len(), 2);
+    let printed = report.to_string();
+    assert!(printed.contains("INC-4711"));
+    assert!(printed.contains("Freeze complete"));
+
+    // The caps are gone from the active address
+    assert!(execute_command(AppCommand::Unpause, &config).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_emergency_freeze_without_pause() -> Result<()> {
+    // A v1 cap cannot pause, but still denies
+    let (config, users) = mock_config(4, MockCoin::V1)?;
+    let plan = emergency::plan(&config, &users[..2], None).await?;
+    assert!(!plan.pause_allowed && !plan.pause());
+    assert!(plan.to_string().contains("not supported"));
+    let report = emergency::freeze(&config, plan, &CommandContext::default()).await?;
+    assert!(report.complete());
+    assert!(!report.paused.next_epoch);
+    assert!(config.client.is_denied(&config.type_tag, users[0]));
+    assert!(config.client.is_denied(&config.type_tag, users[1]));
+    assert!(!config.client.is_paused(&config.type_tag));
+    // The caps stay with the active address
+    assert_eq!(
+        report.caps[0].owner,
+        Owner::AddressOwner(config.active_address)
+    );
+
+    // Nor with a DenyCapV2 that does not allow it
+    let (config, users) = mock_config(
+        4,
+        MockCoin::V2 {
+            allow_global_pause: false,
+        },
+    )?;
+    let plan = emergency::plan(&config, &users[..1], None).await?;
+    assert!(!plan.pause());
+    assert!(plan.to_string().contains("not allowed"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_outside_windows() -> Result<()> {
+    let (mut config, users) = mock_config(
+        4,
+        MockCoin::V2 {
+            allow_global_pause: true,
+        },
+    )?;
+    // A window that opens in an hour
+    let now = Utc::now();
+    let window = TimeWindow {
+        start: TimeOfDay((now + Duration::hours(1)).time()),
+        end: TimeOfDay((now + Duration::hours(2)).time()),
+    };
+    let policy = Policy {
+        windows: vec![window],
+        ..Policy::default()
+    };
+    config.policy = Some(PolicyEngine::new(policy, MintLedger::open_in_memory()?));
+    assert!(execute_command(AppCommand::DenyListAdd(users[0]), &config)
+        .await
+        .is_err());
+
+    let plan = emergency::plan(&config, &users[..2], None).await?;
+    let report = emergency::freeze(&config, plan, &CommandContext::default()).await?;
+    assert!(report.complete());
+    assert!(config.client.is_paused(&config.type_tag));
+    assert!(config.client.is_denied(&config.type_tag, users[0]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_already_frozen() -> Result<()> {
+    let (config, users) = mock_config(
+        4,
+        MockCoin::V2 {
+            allow_global_pause: true,
+        },
+    )?;
+    execute_command(AppCommand::DenyListAdd(users[0]), &config).await?;
+    execute_command(AppCommand::Pause, &config).await?;
+
+    let plan = emergency::plan(&config, &users[..2], None).await?;
+    assert!(plan.already_paused && !plan.pause());
+    assert_eq!(plan.deny, vec![users[1]]);
+    assert_eq!(plan.already_denied, vec![users[0]]);
+    let report = emergency::freeze(&config, plan, &CommandContext::default()).await?;
+    assert!(report.complete());
+    assert_eq!(report.denied.len(), 1);
+
+    // Nothing is left to do
+    let plan = emergency::plan(&config, &users[..2], None).await?;
+    assert!(emergency::freeze(&config, plan, &CommandContext::default())
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_confirmation() -> Result<()> {
+    let (config, users) = mock_config(
+        4,
+        MockCoin::V2 {
+            allow_global_pause: true,
+        },
+    )?;
+    let plan = emergency::plan(&config, &users[..1], None).await?;
+    plan.confirm("FREEZE REGULATED_COIN\n")?;
+    assert!(plan.confirm("freeze regulated_coin").is_err());
+    assert!(plan.confirm("FREEZE").is_err());
+    assert!(plan.confirm("").is_err());
+
+    // The caps are not moved to the active address
+    let plan = emergency::plan(&config, &users[..1], Some(config.active_address)).await?;
+    assert!(emergency::freeze(&config, plan, &CommandContext::default())
+        .await
+        .is_err());
+    assert!(!config.client.is_paused(&config.type_tag));
+    Ok(())
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use anyhow::Result;
+use rust_client::enforce::{self, EvidenceReport};
+use rust_client::tx_run::CommandContext;
+use sui_sdk::types::parse_sui_type_tag;
+
+use common::{mock_config, MockCoin, COIN_TYPE};
+
+#[tokio::test]
+async fn test_enforce() -> Result<()> {
+    let (config, users) = mock_config(2, MockCoin::V1)?;
+    let user = users[0];
+    let first = config.client.add_coin(user, config.type_tag.clone(), 300);
+    let second = config.client.add_coin(user, config.type_tag.clone(), 200);
+    // Neither other coins of the user nor coins of others are frozen
+    let other_type = parse_sui_type_tag(&COIN_TYPE.replace("REGULATED_COIN", "OTHER"))?;
+    config.client.add_coin(user, other_type, 1000);
+    config
+        .client
+        .add_coin(config.active_address, config.type_tag.clone(), 50);
+
+    let context = CommandContext {
+        ticket: Some("INC-4711".to_string()),
+        reason: Some("COURT_ORDER".to_string()),
+        case: Some("2026-CV-118".to_string()),
+        ..CommandContext::default()
+    };
+    let report = enforce::enforce(&config, user, &context).await?;
+    assert!(config.client.is_denied(&config.type_tag, user));
+    assert_eq!(report.address, user);
+    assert_eq!(report.balance, 500);
+    assert_eq!(report.balance_before, 500);
+    let mut ids: Vec<_> = report.coins.iter().map(|coin| coin.id).collect();
+    ids.sort();
+    let mut expected = vec![first, second];
+    expected.sort();
+    assert_eq!(ids, expected);
+    assert!(report.changed.is_empty());
+    assert_eq!(report.context, context);
+    // Denied for v1 coins at once
+    assert_eq!(report.effective_epoch, report.current_epoch);
+    assert!(!report.pending());
+    assert!(report
+        .to_string()
+        .contains("Frozen balance: 500 in 2 coins"));
+
+    let path = std::env::temp_dir().join(format!("evidence-{}.json", std::process::id()));
+    report.save(&path)?;
+    let saved: EvidenceReport = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+    assert_eq!(saved, report);
+    std::fs::remove_file(&path)?;
+
+    // Already frozen
+    assert!(enforce::enforce(&config, user, &context).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_enforce_v2() -> Result<()> {
+    let (config, users) = mock_config(
+        2,
+        MockCoin::V2 {
+            allow_global_pause: false,
+        },
+    )?;
+    let user = users[0];
+    config.client.set_epoch(7);
+    config.client.add_coin(user, config.type_tag.clone(), 300);
+
+    let report = enforce::enforce(&config, user, &CommandContext::default()).await?;
+    assert_eq!(report.current_epoch, 7);
+    assert_eq!(report.effective_epoch, 8);
+    assert!(report.pending());
+    assert!(report
+        .to_string()
+        .contains("Frozen balance: 300 in 1 coins, pending until epoch 8"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_enforce_empty() -> Result<()> {
+    let (config, users) = mock_config(2, MockCoin::V1)?;
+    let user = users[0];
+    let report = enforce::enforce(&config, user, &CommandContext::default()).await?;
+    assert!(report.coins.is_empty());
+    assert_eq!(report.balance, 0);
+    assert!(config.client.is_denied(&config.type_tag, user));
+    Ok(())
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use std::time::Duration;
+
+use anyhow::Result;
+use rust_client::history::{history, LedgerAction, LedgerEntry};
+use rust_client::tx_run::{execute_command, AppCommand, AppConfig};
+
+use common::{created_object, TestEnv};
+
+/// The history of the coin, once the fullnode has indexed `expected` entries.
+async fn wait_for_history(config: &AppConfig, expected: usize) -> Result<Vec<LedgerEntry>> {
+    for _ in 0..50 {
+        let entries = history(&config.client, &config.type_tag, &config.packages).await?;
+        if entries.len() >= expected {
+            return Ok(entries);
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    anyhow::bail!("History did not reach {expected} entries")
+}
+
+#[tokio::test]
+async fn test_history() -> Result<()> {
+    let env = TestEnv::new().await?;
+    let config = env.config(env.admin);
+    let user = env.users[0];
+
+    execute_command(AppCommand::MintAndTransfer(10_000, user), &config).await?;
+    let resp = execute_command(AppCommand::MintAndTransfer(500, env.admin), &config).await?;
+    let coin = created_object(&resp)?;
+    execute_command(AppCommand::Burn(coin.0), &config).await?;
+    execute_command(AppCommand::DenyListAdd(user), &config).await?;
+    execute_command(AppCommand::DenyListRemove(user), &config).await?;
+
+    let entries = wait_for_history(&config, 5).await?;
+    let actions: Vec<LedgerAction> = entries.iter().map(|entry| entry.action.clone()).collect();
+    assert_eq!(
+        actions,
+        vec![
+            LedgerAction::Mint {
+                amount: 10_000,
+                recipient: Some(user),
+            },
+            LedgerAction::Mint {
+                amount: 500,
+                recipient: Some(env.admin),
+            },
+            LedgerAction::Burn { amount: 500 },
+            LedgerAction::DenyListAdd { address: user },
+            LedgerAction::DenyListRemove { address: user },
+        ]
+    );
+    assert!(entries.iter().all(|entry| entry.actor == env.admin));
+    assert!(entries.iter().all(|entry| entry.timestamp_ms.is_some()));
+    Ok(())
+}
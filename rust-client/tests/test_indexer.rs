@@ -0,0 +1,159 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use anyhow::Result;
+use rust_client::backend::mock::MockBackend;
+use rust_client::history::LedgerAction;
+use rust_client::indexer::{sync, IndexedTx, IndexerDb};
+use rust_client::tx_run::{execute_command, AppCommand};
+use sui_sdk::types::parse_sui_type_tag;
+
+use common::{address, created_object, TestEnv, COIN_TYPE};
+
+/// Recorded transactions of `COIN_TYPE`: two mints, a transfer from alice to bob, a burn,
+/// denying bob and alice, and allowing alice again.
+fn fixture() -> Result<BTreeMap<u64, Vec<IndexedTx>>> {
+    let txs: Vec<IndexedTx> = serde_json::from_str(include_str!("fixtures/indexed_txs.json"))?;
+    let mut checkpoints: BTreeMap<u64, Vec<IndexedTx>> = BTreeMap::new();
+    for tx in txs {
+        checkpoints.entry(tx.checkpoint).or_default().push(tx);
+    }
+    Ok(checkpoints)
+}
+
+#[test]
+fn test_fixture() -> Result<()> {
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let mut db = IndexerDb::open_in_memory(&type_tag)?;
+    for (checkpoint, txs) in fixture()? {
+        db.apply_checkpoint(checkpoint, &txs)?;
+    }
+    let (admin, alice, bob) = (address('a'), address('1'), address('2'));
+
+    assert_eq!(db.cursor()?, Some(18));
+    assert_eq!(db.supply()?, 10_000);
+    assert_eq!(db.balance(alice)?, 6_000);
+    assert_eq!(db.balance(bob)?, 4_000);
+    assert_eq!(db.balance(admin)?, 0);
+    assert_eq!(db.deny_list()?, vec![bob]);
+    assert_eq!(
+        db.counterparties(1709287970000)?,
+        vec![
+            (alice, 1709287970000),
+            (bob, 1709287970000),
+            (admin, 1709287975000)
+        ]
+    );
+
+    let history = db.history()?;
+    assert_eq!(history.len(), 6);
+    assert_eq!(
+        history[0].action,
+        LedgerAction::Mint {
+            amount: 10_000,
+            recipient: Some(alice),
+        }
+    );
+    assert_eq!(
+        history[5].action,
+        LedgerAction::DenyListRemove { address: alice }
+    );
+    assert!(history
+        .windows(2)
+        .all(|pair| pair[0].checkpoint <= pair[1].checkpoint));
+    Ok(())
+}
+
+#[test]
+fn test_resume() -> Result<()> {
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let path = std::env::temp_dir().join(format!("indexer-{}.db", std::process::id()));
+    let checkpoints: Vec<(u64, Vec<IndexedTx>)> = fixture()?.into_iter().collect();
+    let (first, rest) = checkpoints.split_at(2);
+
+    let mut db = IndexerDb::open(&path, &type_tag)?;
+    for (checkpoint, txs) in first {
+        db.apply_checkpoint(*checkpoint, txs)?;
+    }
+    drop(db);
+
+    let mut db = IndexerDb::open(&path, &type_tag)?;
+    assert_eq!(db.cursor()?, Some(12));
+    // Indexed checkpoints are not applied twice
+    assert!(db.apply_checkpoint(first[1].0, &first[1].1).is_err());
+    for (checkpoint, txs) in rest {
+        db.apply_checkpoint(*checkpoint, txs)?;
+    }
+    assert_eq!(db.supply()?, 10_000);
+    assert_eq!(db.balance(address('1'))?, 6_000);
+    drop(db);
+
+    // A database belongs to one coin
+    assert!(IndexerDb::open(&path, &parse_sui_type_tag("0x2::sui::SUI")?).is_err());
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sync_start() -> Result<()> {
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let client = MockBackend::new();
+    client.set_checkpoint(10);
+    client.create_regulated_coin(type_tag.clone(), address('a'));
+
+    // Mid-chain, the mints and transfers before the start would be missing
+    let mut db = IndexerDb::open_in_memory(&type_tag)?;
+    let e = sync(&client, &mut db, &type_tag, Some(11))
+        .await
+        .unwrap_err();
+    assert!(e.to_string().contains("published in checkpoint 10"));
+    assert_eq!(db.cursor()?, None);
+
+    sync(&client, &mut db, &type_tag, Some(10)).await?;
+    sync(&client, &mut db, &type_tag, Some(3)).await?;
+    // The start only applies to a new database
+    db.apply_checkpoint(12, &[])?;
+    sync(&client, &mut db, &type_tag, Some(11)).await?;
+    assert_eq!(db.cursor()?, Some(12));
+
+    // Without a start a new database begins at the publish, which must be found
+    let mut db = IndexerDb::open_in_memory(&type_tag)?;
+    sync(&client, &mut db, &type_tag, None).await?;
+    let unknown = parse_sui_type_tag("0x3::unknown::UNKNOWN")?;
+    let mut db = IndexerDb::open_in_memory(&unknown)?;
+    assert!(sync(&client, &mut db, &unknown, None).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sync() -> Result<()> {
+    let env = TestEnv::new().await?;
+    let config = env.config(env.admin);
+    let (user, other) = (env.users[0], env.users[1]);
+
+    let resp = execute_command(AppCommand::MintAndTransfer(10_000, user), &config).await?;
+    let coin = created_object(&resp)?;
+    execute_command(AppCommand::Transfer(coin.0, other), &env.config(user)).await?;
+    execute_command(AppCommand::DenyListAdd(user), &config).await?;
+
+    let mut db = IndexerDb::open_in_memory(&env.type_tag)?;
+    // Wait for the transactions to be checkpointed
+    for _ in 0..50 {
+        sync(&config.client, &mut db, &env.type_tag, None).await?;
+        if db.deny_list()? == vec![user] {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    assert_eq!(db.deny_list()?, vec![user]);
+    assert_eq!(db.supply()?, 10_000);
+    assert_eq!(db.balance(user)?, 0);
+    assert_eq!(db.balance(other)?, 10_000);
+    assert_eq!(db.history()?.len(), 2);
+
+    // Nothing new to index
+    assert_eq!(sync(&config.client, &mut db, &env.type_tag, None).await?, 0);
+    Ok(())
+}
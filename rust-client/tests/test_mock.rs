@@ -0,0 +1,183 @@
+mod common;
+
+use std::str::FromStr;
+
+use anyhow::Result;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use rust_client::backend::mock::MockBackend;
+use rust_client::tx_run::preflight::PreflightFailure;
+use rust_client::tx_run::{
+    execute_command, get_coin_objects, verify_coin_type, AppCommand, AppConfig, DenyCapVersion,
+};
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::object::Owner;
+use sui_sdk::types::parse_sui_type_tag;
+
+use common::{mock_config, MockCoin, COIN_TYPE};
+
+/// A mock chain with a regulated coin whose caps are owned by the admin, and a funded user.
+fn setup() -> Result<(AppConfig<MockBackend>, SuiAddress)> {
+    let (config, users) = mock_config(2, MockCoin::V1)?;
+    config.client.add_gas(users[0], 1_000_000_000);
+    Ok((config, users[0]))
+}
+
+fn type_tag() -> TypeTag {
+    parse_sui_type_tag(COIN_TYPE).unwrap()
+}
+
+#[tokio::test]
+async fn test_mint_and_transfer() -> Result<()> {
+    let (config, user) = setup()?;
+
+    let resp = execute_command(AppCommand::MintAndTransfer(10_000, user), &config).await?;
+    assert!(resp.errors.is_empty());
+    assert_eq!(config.client.balance(user, &type_tag()), 10_000);
+    assert_eq!(config.client.total_supply(&type_tag()), 10_000);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_transfer_and_burn() -> Result<()> {
+    let (config, user) = setup()?;
+    let admin = config.active_address;
+    let coin = config.client.add_coin(admin, type_tag(), 500);
+
+    execute_command(AppCommand::Transfer(coin, user), &config).await?;
+    assert_eq!(config.client.owner(coin), Some(Owner::AddressOwner(user)));
+
+    let coin = config.client.add_coin(admin, type_tag(), 700);
+    execute_command(AppCommand::Burn(coin), &config).await?;
+    assert_eq!(config.client.owner(coin), None);
+    assert_eq!(config.client.total_supply(&type_tag()), 500);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deny_add_and_remove() -> Result<()> {
+    let (config, user) = setup()?;
+
+    execute_command(AppCommand::DenyListAdd(user), &config).await?;
+    assert!(config.client.is_denied(&type_tag(), user));
+
+    execute_command(AppCommand::DenyListRemove(user), &config).await?;
+    assert!(!config.client.is_denied(&type_tag(), user));
+
+    // Pre-flight refuses to remove an address that is not denied
+    let e = execute_command(AppCommand::DenyListRemove(user), &config)
+        .await
+        .unwrap_err();
+    let failure = e.downcast_ref::<PreflightFailure>().unwrap();
+    assert!(failure.refused());
+    assert_eq!(failure.failures.len(), 1);
+    assert!(e.to_string().contains("[FAIL]"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unreadable_caps() -> Result<()> {
+    let (mut config, user) = setup()?;
+    let admin = config.active_address;
+
+    // Owning no DenyCap refuses the command
+    config.active_address = user;
+    let e = execute_command(AppCommand::DenyListAdd(admin), &config)
+        .await
+        .unwrap_err();
+    assert!(e.downcast_ref::<PreflightFailure>().unwrap().refused());
+
+    // Failing to read the caps does not
+    config.active_address = admin;
+    config.client.set_owned_objects_unavailable(true);
+    let e = execute_command(AppCommand::DenyListAdd(user), &config)
+        .await
+        .unwrap_err();
+    assert!(!e.downcast_ref::<PreflightFailure>().unwrap().refused());
+    assert!(e.to_string().contains("[ERROR]"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_denied_transfer() -> Result<()> {
+    let (mut config, user) = setup()?;
+    let admin = config.active_address;
+    let coin = config.client.add_coin(user, type_tag(), 1_000);
+    execute_command(AppCommand::DenyListAdd(user), &config).await?;
+
+    config.active_address = user;
+    assert!(execute_command(AppCommand::Transfer(coin, admin), &config)
+        .await
+        .is_err());
+    assert_eq!(config.client.owner(coin), Some(Owner::AddressOwner(user)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mint_to_denied_address() -> Result<()> {
+    let (config, user) = setup()?;
+    execute_command(AppCommand::DenyListAdd(user), &config).await?;
+
+    assert!(
+        execute_command(AppCommand::MintAndTransfer(10, user), &config)
+            .await
+            .is_err()
+    );
+    assert_eq!(config.client.total_supply(&type_tag()), 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rejects_foreign_objects() -> Result<()> {
+    let (config, user) = setup()?;
+    let admin = config.active_address;
+
+    let nft = config.client.add_object(
+        admin,
+        StructTag::from_str(
+            "0x5da522e939ce9fdcb15d4b3d03a16aa408706105cf90114cedc9613809f04c20::nft::NFT",
+        )?,
+    );
+    assert!(execute_command(AppCommand::Transfer(nft, user), &config)
+        .await
+        .is_err());
+
+    let other_coin = parse_sui_type_tag("0x2::sui::SUI")?;
+    let sui = config.client.add_coin(admin, other_coin, 100);
+    assert!(execute_command(AppCommand::Burn(sui), &config)
+        .await
+        .is_err());
+    assert_eq!(config.client.owner(sui), Some(Owner::AddressOwner(admin)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_verify_coin_type() -> Result<()> {
+    let client = MockBackend::new();
+    // The caps need not be owned by a known address
+    let owner = SuiAddress::random_for_testing_only();
+    client.create_regulated_coin(type_tag(), owner);
+    assert_eq!(
+        verify_coin_type(&client, &type_tag()).await?.symbol,
+        "REGULATED_COIN"
+    );
+    // The publisher owns the metadata and may update it, which does not hide the caps
+    client.update_metadata(&type_tag(), "Updated");
+    let objects = get_coin_objects(&client, &type_tag()).await?;
+    assert!(matches!(objects.deny_cap, Some((_, DenyCapVersion::V1))));
+    verify_coin_type(&client, &type_tag()).await?;
+
+    let v2 = parse_sui_type_tag("0x3::usdx::USDX")?;
+    client.create_regulated_coin_v2(v2.clone(), owner, false);
+    let objects = get_coin_objects(&client, &v2).await?;
+    assert!(matches!(objects.deny_cap, Some((_, DenyCapVersion::V2))));
+    assert!(objects.treasury_cap.is_some());
+    verify_coin_type(&client, &v2).await?;
+
+    // Not regulated, or not a coin at all
+    let plain = parse_sui_type_tag("0x3::plain::PLAIN")?;
+    client.create_coin(plain.clone(), owner);
+    assert!(verify_coin_type(&client, &plain).await.is_err());
+    let unknown = parse_sui_type_tag("0x3::unknown::UNKNOWN")?;
+    assert!(verify_coin_type(&client, &unknown).await.is_err());
+    Ok(())
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use anyhow::Result;
+use rust_client::backend::mock::MockBackend;
+use rust_client::tx_run::{
+    deny_list_status, execute_command, execute_command_with, get_deny_cap, get_deny_list,
+    global_pause_status, AppCommand, AppConfig, CommandContext, DenyCapVersion, EpochStatus,
+};
+use sui_sdk::types::base_types::SuiAddress;
+
+use common::{mock_config, MockCoin};
+
+async fn status(config: &AppConfig<MockBackend>, addr: SuiAddress) -> Result<EpochStatus> {
+    let deny_list = get_deny_list(&config.client).await?;
+    deny_list_status(&config.client, config.type_tag.clone(), deny_list, addr).await
+}
+
+async fn paused(config: &AppConfig<MockBackend>) -> Result<EpochStatus> {
+    let deny_list = get_deny_list(&config.client).await?;
+    global_pause_status(&config.client, config.type_tag.clone(), deny_list).await
+}
+
+#[tokio::test]
+async fn test_deny_v2() -> Result<()> {
+    let (config, users) = mock_config(
+        2,
+        MockCoin::V2 {
+            allow_global_pause: false,
+        },
+    )?;
+    let user = users[0];
+    let cap = get_deny_cap(
+        &config.client,
+        config.active_address,
+        config.type_tag.clone(),
+    )
+    .await?;
+    assert_eq!(cap.version, DenyCapVersion::V2);
+    assert!(!cap.allow_global_pause);
+
+    let context = CommandContext {
+        reason: Some("SANCTIONS".to_string()),
+        on_chain: true,
+        ..CommandContext::default()
+    };
+    execute_command_with(AppCommand::DenyListAdd(user), &context, &config).await?;
+    // Spending stops at once, receiving from the next epoch
+    assert!(config.client.is_denied(&config.type_tag, user));
+    let denied = status(&config, user).await?;
+    assert!(!denied.current_epoch && denied.next_epoch);
+    assert_eq!(config.client.deny_reasons(&config.type_tag).len(), 1);
+
+    config.client.set_epoch(1);
+    let denied = status(&config, user).await?;
+    assert!(denied.current_epoch && denied.next_epoch);
+
+    execute_command(AppCommand::DenyListRemove(user), &config).await?;
+    assert!(!config.client.is_denied(&config.type_tag, user));
+    let denied = status(&config, user).await?;
+    assert!(denied.current_epoch && !denied.next_epoch);
+    // Already removed, even though it only takes effect in the next epoch
+    assert!(execute_command(AppCommand::DenyListRemove(user), &config)
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pause() -> Result<()> {
+    let (mut config, users) = mock_config(
+        2,
+        MockCoin::V2 {
+            allow_global_pause: true,
+        },
+    )?;
+    let user = users[0];
+    let coin = config.client.add_coin(user, config.type_tag.clone(), 100);
+    config.client.add_gas(user, 1_000_000_000);
+    let admin = config.active_address;
+    assert!(execute_command(AppCommand::Unpause, &config).await.is_err());
+
+    execute_command(AppCommand::Pause, &config).await?;
+    assert!(config.client.is_paused(&config.type_tag));
+    assert!(config.client.is_denied(&config.type_tag, user));
+    assert_eq!(
+        paused(&config).await?,
+        EpochStatus {
+            current_epoch: false,
+            next_epoch: true,
+        }
+    );
+    assert!(execute_command(AppCommand::Pause, &config).await.is_err());
+
+    // Nobody can spend the coin
+    config.active_address = user;
+    assert!(execute_command(AppCommand::Transfer(coin, admin), &config)
+        .await
+        .is_err());
+
+    config.active_address = admin;
+    config.client.set_epoch(1);
+    assert!(paused(&config).await?.current_epoch);
+    execute_command(AppCommand::Unpause, &config).await?;
+    assert!(!config.client.is_paused(&config.type_tag));
+    config.active_address = user;
+    execute_command(AppCommand::Transfer(coin, admin), &config).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pause_not_allowed() -> Result<()> {
+    let (config, _) = mock_config(
+        2,
+        MockCoin::V2 {
+            allow_global_pause: false,
+        },
+    )?;
+    assert!(execute_command(AppCommand::Pause, &config).await.is_err());
+    assert!(!config.client.is_paused(&config.type_tag));
+
+    // Nor with a v1 cap
+    let (config, _) = mock_config(2, MockCoin::V1)?;
+    let cap = get_deny_cap(
+        &config.client,
+        config.active_address,
+        config.type_tag.clone(),
+    )
+    .await?;
+    assert_eq!(cap.version, DenyCapVersion::V1);
+    assert!(execute_command(AppCommand::Pause, &config).await.is_err());
+    Ok(())
+}
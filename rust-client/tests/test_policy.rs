@@ -0,0 +1,261 @@
+mod common;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rust_client::backend::mock::MockBackend;
+use rust_client::policy::{CommandKind, MintLedger, Policy, PolicyEngine, PolicyViolation};
+use rust_client::tx_run::{
+    execute_command, execute_command_with, AppCommand, AppConfig, CommandContext,
+};
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::digests::TransactionDigest;
+use sui_sdk::types::parse_sui_type_tag;
+
+use common::{mock_config, MockCoin, COIN_TYPE};
+
+/// A mock chain with a regulated coin whose caps are owned by the admin, signing under `policy`.
+fn setup(policy: Policy) -> Result<(AppConfig<MockBackend>, SuiAddress, SuiAddress)> {
+    let (mut config, users) = mock_config(3, MockCoin::V1)?;
+    config.policy = Some(PolicyEngine::new(policy, MintLedger::open_in_memory()?));
+    Ok((config, users[0], users[1]))
+}
+
+fn violation(result: Result<impl std::fmt::Debug>) -> PolicyViolation {
+    result
+        .expect_err("the policy should refuse the command")
+        .downcast::<PolicyViolation>()
+        .expect("a policy violation")
+}
+
+fn at(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+}
+
+#[tokio::test]
+async fn test_mint_limits() -> Result<()> {
+    let policy = Policy {
+        max_mint: Some(1_000),
+        daily_mint_cap: Some(1_500),
+        ..Policy::default()
+    };
+    let (config, user, _) = setup(policy)?;
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+
+    let result = execute_command(AppCommand::MintAndTransfer(u64::MAX, user), &config).await;
+    assert_eq!(
+        violation(result),
+        PolicyViolation::MintTooLarge {
+            amount: u64::MAX,
+            max: 1_000
+        }
+    );
+    assert_eq!(config.client.total_supply(&type_tag), 0);
+
+    execute_command(AppCommand::MintAndTransfer(1_000, user), &config).await?;
+    let result = execute_command(AppCommand::MintAndTransfer(600, user), &config).await;
+    assert!(matches!(
+        violation(result),
+        PolicyViolation::DailyMintCap { minted: 1_000, .. }
+    ));
+    execute_command(AppCommand::MintAndTransfer(500, user), &config).await?;
+    assert_eq!(config.client.balance(user, &type_tag), 1_500);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mint_recipients() -> Result<()> {
+    let (mut config, user, other) = setup(Policy::default())?;
+    let policy = Policy {
+        mint_recipients: Some(vec![user]),
+        ..Policy::default()
+    };
+    config.policy = Some(PolicyEngine::new(policy, MintLedger::open_in_memory()?));
+
+    let result = execute_command(AppCommand::MintAndTransfer(100, other), &config).await;
+    assert_eq!(
+        violation(result),
+        PolicyViolation::RecipientNotAllowed { recipient: other }
+    );
+    execute_command(AppCommand::MintAndTransfer(100, user), &config).await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_ticket() -> Result<()> {
+    let policy = Policy {
+        deny_list_remove_requires_ticket: true,
+        ..Policy::default()
+    };
+    let (config, user, _) = setup(policy)?;
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+
+    execute_command(AppCommand::DenyListAdd(user), &config).await?;
+    let result = execute_command(AppCommand::DenyListRemove(user), &config).await;
+    assert_eq!(violation(result), PolicyViolation::TicketRequired);
+    let blank = CommandContext {
+        ticket: Some(" ".to_string()),
+        ..CommandContext::default()
+    };
+    let result = execute_command_with(AppCommand::DenyListRemove(user), &blank, &config).await;
+    assert_eq!(violation(result), PolicyViolation::TicketRequired);
+    assert!(config.client.is_denied(&type_tag, user));
+
+    let context = CommandContext {
+        ticket: Some("OPS-12".to_string()),
+        ..CommandContext::default()
+    };
+    execute_command_with(AppCommand::DenyListRemove(user), &context, &config).await?;
+    assert!(!config.client.is_denied(&type_tag, user));
+    Ok(())
+}
+
+#[test]
+fn test_caps_per_operator_and_month() -> Result<()> {
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let operator = SuiAddress::random_for_testing_only();
+    let trusted = SuiAddress::random_for_testing_only();
+    let policy: Policy = serde_json::from_value(serde_json::json!({
+        "daily_mint_cap": 1_000,
+        "monthly_mint_cap": 2_500,
+        "operators": [{ "operator": trusted.to_string(), "daily_mint_cap": 10_000 }],
+    }))?;
+    let engine = PolicyEngine::new(policy, MintLedger::open_in_memory()?);
+    let context = CommandContext::default();
+    let mint = |amount| AppCommand::MintAndTransfer(amount, operator);
+
+    for day in ["2026-01-05T10:00:00Z", "2026-01-06T10:00:00Z"] {
+        engine.check(&type_tag, operator, &mint(1_000), &context, at(day))?;
+        engine.record(
+            &type_tag,
+            operator,
+            &mint(1_000),
+            TransactionDigest::random(),
+            at(day),
+        )?;
+    }
+    // Within the daily cap, but over the monthly one
+    let result = engine.check(
+        &type_tag,
+        operator,
+        &mint(600),
+        &context,
+        at("2026-01-20T10:00:00Z"),
+    );
+    assert!(matches!(
+        violation(result),
+        PolicyViolation::MonthlyMintCap { minted: 2_000, .. }
+    ));
+    engine.check(
+        &type_tag,
+        operator,
+        &mint(500),
+        &context,
+        at("2026-01-20T10:00:00Z"),
+    )?;
+    engine.check(
+        &type_tag,
+        operator,
+        &mint(1_000),
+        &context,
+        at("2026-02-01T00:00:00Z"),
+    )?;
+
+    // Operators with their own caps are not capped monthly, nor by the mints of others
+    engine.check(
+        &type_tag,
+        trusted,
+        &mint(10_000),
+        &context,
+        at("2026-01-06T11:00:00Z"),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn test_windows() -> Result<()> {
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let operator = SuiAddress::random_for_testing_only();
+    let policy: Policy = serde_json::from_str(
+        r#"{ "windows": [
+            { "start": "09:00", "end": "17:00" },
+            { "start": "22:00", "end": "02:00" }
+        ] }"#,
+    )?;
+    let engine = PolicyEngine::new(policy, MintLedger::open_in_memory()?);
+    let context = CommandContext::default();
+    let command = AppCommand::DenyListAdd(operator);
+    let check = |time: &str| engine.check(&type_tag, operator, &command, &context, at(time));
+
+    check("2026-01-05T09:00:00Z")?;
+    check("2026-01-05T23:30:00Z")?;
+    check("2026-01-06T01:59:00Z")?;
+    assert!(matches!(
+        violation(check("2026-01-05T17:00:00Z")),
+        PolicyViolation::OutsideWindow { .. }
+    ));
+    assert!(matches!(
+        violation(check("2026-01-06T05:00:00Z")),
+        PolicyViolation::OutsideWindow { .. }
+    ));
+
+    // Incidents do not wait for the window
+    let freeze = AppCommand::EmergencyFreeze {
+        pause: true,
+        addresses: vec![operator],
+        cold_address: None,
+    };
+    for command in [freeze, AppCommand::Pause] {
+        engine.check(
+            &type_tag,
+            operator,
+            &command,
+            &context,
+            at("2026-01-06T05:00:00Z"),
+        )?;
+    }
+    assert!(engine
+        .check(
+            &type_tag,
+            operator,
+            &AppCommand::Unpause,
+            &context,
+            at("2026-01-06T05:00:00Z"),
+        )
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_invalid_policy() {
+    assert!(serde_json::from_str::<Policy>(r#"{ "max_mnit": 1000 }"#).is_err());
+    assert!(serde_json::from_str::<Policy>(
+        r#"{ "windows": [{ "start": "9am", "end": "17:00" }] }"#
+    )
+    .is_err());
+    assert!(serde_json::from_str::<Policy>(r#"{ "require_proposal": ["mnit"] }"#).is_err());
+}
+
+#[test]
+fn test_require_proposal_needs_checkers() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("require-proposal-{}.json", std::process::id()));
+    std::fs::write(
+        &path,
+        r#"{ "require_proposal": ["mint", "deny-list-remove"] }"#,
+    )?;
+    assert!(Policy::load(&path).is_err());
+
+    let checker = SuiAddress::random_for_testing_only();
+    std::fs::write(
+        &path,
+        format!(
+            r#"{{ "require_proposal": ["mint", "deny-list-remove"], "checkers": ["{checker}"] }}"#
+        ),
+    )?;
+    let policy = Policy::load(&path)?;
+    assert_eq!(
+        policy.require_proposal,
+        [CommandKind::Mint, CommandKind::DenyListRemove]
+    );
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
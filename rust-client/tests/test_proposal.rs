@@ -0,0 +1,360 @@
+mod common;
+
+use anyhow::Result;
+use rust_client::audit::{AuditLog, SignedAuditRecord};
+use rust_client::backend::mock::MockBackend;
+use rust_client::policy::{CommandKind, MintLedger, Policy, PolicyEngine, PolicyViolation};
+use rust_client::proposal::{self, Decision, Proposal, ProposalDb, ProposalStatus};
+use rust_client::tx_run::{execute_command, AppCommand, AppConfig, CommandContext};
+use sui_keys::keystore::AccountKeystore;
+use sui_sdk::types::base_types::SuiAddress;
+use sui_sdk::types::parse_sui_type_tag;
+
+use common::{mock_config, MockCoin, COIN_TYPE};
+
+/// A mock chain with a regulated coin whose caps are owned by the maker, the two checkers of the
+/// policy and a user.
+fn setup() -> Result<(AppConfig<MockBackend>, [SuiAddress; 3])> {
+    let (mut config, users) = mock_config(4, MockCoin::V1)?;
+    let policy = Policy {
+        checkers: vec![users[0], users[1]],
+        ..Policy::default()
+    };
+    config.policy = Some(PolicyEngine::new(policy, MintLedger::open_in_memory()?));
+    Ok((config, [users[0], users[1], users[2]]))
+}
+
+/// Reviews proposal `id` as `checker`, with the signature the checker makes on its own machine.
+fn review(
+    config: &AppConfig<MockBackend>,
+    db: &mut ProposalDb,
+    id: u64,
+    checker: SuiAddress,
+    decision: Decision,
+    comment: Option<String>,
+) -> Result<Proposal> {
+    let message = proposal::review_message(&db.get(id)?, decision)?;
+    let signature = proposal::sign_review(&message, config.keystore.get_key(&checker)?);
+    proposal::review(config, db, id, checker, decision, comment, &signature)
+}
+
+#[tokio::test]
+async fn test_four_eyes() -> Result<()> {
+    let (config, [checker1, checker2, user]) = setup()?;
+    let maker = config.active_address;
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let mut db = ProposalDb::open_in_memory()?;
+
+    let command = AppCommand::MintAndTransfer(10_000, user);
+    let proposal = proposal::propose(
+        &config,
+        &mut db,
+        command.clone(),
+        &CommandContext::default(),
+        2,
+    )
+    .await?;
+    assert_eq!(proposal.status, ProposalStatus::Pending);
+    assert_eq!(proposal.command, command);
+    assert_eq!(proposal.maker, maker);
+    assert!(proposal.dry_run.error.is_none());
+    // Nothing is executed until approved
+    assert_eq!(config.client.balance(user, &type_tag), 0);
+    let id = proposal.id;
+
+    assert!(review(&config, &mut db, id, maker, Decision::Approve, None).is_err());
+    let proposal = review(&config, &mut db, id, checker1, Decision::Approve, None)?;
+    assert_eq!(proposal.status, ProposalStatus::Pending);
+    assert!(review(&config, &mut db, id, checker1, Decision::Approve, None).is_err());
+    assert!(proposal::execute(&config, &mut db, id).await.is_err());
+
+    let comment = Some("Matches ticket OPS-12".to_string());
+    let proposal = review(&config, &mut db, id, checker2, Decision::Approve, comment)?;
+    assert_eq!(proposal.status, ProposalStatus::Approved);
+    assert_eq!(proposal.approvals(), 2);
+
+    let resp = proposal::execute(&config, &mut db, id).await?;
+    assert!(resp.errors.is_empty());
+    assert_eq!(config.client.balance(user, &type_tag), 10_000);
+    let proposal = db.get(id)?;
+    assert_eq!(proposal.status, ProposalStatus::Executed);
+    assert_eq!(proposal.executed_digest, Some(resp.digest));
+    assert!(proposal::execute(&config, &mut db, id).await.is_err());
+
+    let events: Vec<(String, SuiAddress)> = db
+        .audit_trail(Some(id))?
+        .into_iter()
+        .map(|entry| (entry.event, entry.actor))
+        .collect();
+    assert_eq!(
+        events,
+        [
+            ("created".to_string(), maker),
+            ("approved".to_string(), checker1),
+            ("approved".to_string(), checker2),
+            ("executed".to_string(), maker),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reject() -> Result<()> {
+    let (config, [checker1, checker2, user]) = setup()?;
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let mut db = ProposalDb::open_in_memory()?;
+    execute_command(AppCommand::DenyListAdd(user), &config).await?;
+
+    let id = proposal::propose(
+        &config,
+        &mut db,
+        AppCommand::DenyListRemove(user),
+        &CommandContext::default(),
+        1,
+    )
+    .await?
+    .id;
+    let proposal = review(&config, &mut db, id, checker1, Decision::Reject, None)?;
+    assert_eq!(proposal.status, ProposalStatus::Rejected);
+    assert!(review(&config, &mut db, id, checker2, Decision::Approve, None).is_err());
+    assert!(proposal::execute(&config, &mut db, id).await.is_err());
+    assert!(config.client.is_denied(&type_tag, user));
+
+    assert_eq!(db.list(Some(ProposalStatus::Rejected))?.len(), 1);
+    assert!(db.list(Some(ProposalStatus::Pending))?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failed_preflight() -> Result<()> {
+    let (config, [_, _, user]) = setup()?;
+    let mut db = ProposalDb::open_in_memory()?;
+
+    // Not denied, so there is nothing to propose
+    assert!(proposal::propose(
+        &config,
+        &mut db,
+        AppCommand::DenyListRemove(user),
+        &CommandContext::default(),
+        1,
+    )
+    .await
+    .is_err());
+    assert!(db.list(None)?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_checkers() -> Result<()> {
+    let (mut config, [checker1, checker2, user]) = setup()?;
+    let maker = config.active_address;
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let mut db = ProposalDb::open_in_memory()?;
+    let command = AppCommand::MintAndTransfer(10_000, user);
+    let context = CommandContext::default();
+
+    // More approvals than checkers could never be given
+    assert!(
+        proposal::propose(&config, &mut db, command.clone(), &context, 3)
+            .await
+            .is_err()
+    );
+    let id = proposal::propose(&config, &mut db, command.clone(), &context, 1)
+        .await?
+        .id;
+    // Another key of the keystore is not a checker
+    assert!(review(&config, &mut db, id, user, Decision::Approve, None).is_err());
+    // The signature must be the checker's, of its decision on this proposal
+    let proposal = db.get(id)?;
+    let approval = proposal::review_message(&proposal, Decision::Approve)?;
+    let signed_by_checker1 = proposal::sign_review(&approval, config.keystore.get_key(&checker1)?);
+    for (checker, decision) in [(checker2, Decision::Approve), (checker1, Decision::Reject)] {
+        assert!(proposal::review(
+            &config,
+            &mut db,
+            id,
+            checker,
+            decision,
+            None,
+            &signed_by_checker1
+        )
+        .is_err());
+    }
+    assert!(db.get(id)?.reviews.is_empty());
+
+    // An approval written to the database without the checker's key does not count
+    db.insert_review(id, checker1, Decision::Approve, None, &[0; 97])?;
+    let forged = db.get(id)?;
+    assert_eq!(forged.status, ProposalStatus::Approved);
+    assert!(proposal::verified_approvals(&forged, &[checker1, checker2]).is_err());
+    assert!(proposal::execute(&config, &mut db, id).await.is_err());
+    assert_eq!(config.client.balance(user, &type_tag), 0);
+
+    let id = proposal::propose(&config, &mut db, command, &context, 1)
+        .await?
+        .id;
+    review(&config, &mut db, id, checker2, Decision::Approve, None)?;
+    // The gas coin of the proposed transaction is used before execution
+    execute_command(AppCommand::DenyListAdd(checker1), &config).await?;
+    // Only the maker executes
+    config.active_address = checker2;
+    assert!(proposal::execute(&config, &mut db, id).await.is_err());
+    config.active_address = maker;
+    proposal::execute(&config, &mut db, id).await?;
+    assert_eq!(config.client.balance(user, &type_tag), 10_000);
+    assert_eq!(db.get(id)?.status, ProposalStatus::Executed);
+
+    // Without checkers in the policy nothing can be proposed
+    config.policy = None;
+    assert!(
+        proposal::propose(&config, &mut db, AppCommand::DenyListAdd(user), &context, 1)
+            .await
+            .is_err()
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_policy_at_execution() -> Result<()> {
+    let (mut config, [checker1, checker2, user]) = setup()?;
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let mut db = ProposalDb::open_in_memory()?;
+    let context = CommandContext::default();
+
+    let mut ids = vec![];
+    for _ in 0..2 {
+        let command = AppCommand::MintAndTransfer(1_000, user);
+        let id = proposal::propose(&config, &mut db, command, &context, 1)
+            .await?
+            .id;
+        review(&config, &mut db, id, checker1, Decision::Approve, None)?;
+        ids.push(id);
+    }
+
+    // Both fit the daily cap on their own, but not together
+    config.policy = Some(PolicyEngine::new(
+        Policy {
+            daily_mint_cap: Some(1_500),
+            checkers: vec![checker1, checker2],
+            ..Policy::default()
+        },
+        MintLedger::open_in_memory()?,
+    ));
+    proposal::execute(&config, &mut db, ids[0]).await?;
+    let e = proposal::execute(&config, &mut db, ids[1])
+        .await
+        .unwrap_err();
+    assert!(e.downcast_ref::<PolicyViolation>().is_some(), "{e}");
+    assert_eq!(config.client.balance(user, &type_tag), 1_000);
+    assert_eq!(db.get(ids[1])?.status, ProposalStatus::Approved);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_context() -> Result<()> {
+    let (mut config, [checker1, _, user]) = setup()?;
+    let path = std::env::temp_dir().join(format!("proposal-context-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    config.audit_log = Some(AuditLog::new(&path));
+    let mut db = ProposalDb::open_in_memory()?;
+    let context = CommandContext {
+        ticket: Some("OPS-12".to_string()),
+        reason: Some("SANCTIONS".to_string()),
+        ..CommandContext::default()
+    };
+
+    let command = AppCommand::DenyListAdd(user);
+    let id = proposal::propose(&config, &mut db, command.clone(), &context, 1)
+        .await?
+        .id;
+    assert_eq!(db.get(id)?.context, context);
+    review(&config, &mut db, id, checker1, Decision::Approve, None)?;
+    proposal::execute(&config, &mut db, id).await?;
+
+    // Audited with the context it was proposed with
+    let records: Vec<SignedAuditRecord> = std::fs::read_to_string(&path)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].record.command, command);
+    assert_eq!(records[0].record.context, context);
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_require_proposal() -> Result<()> {
+    let (mut config, [checker1, checker2, user]) = setup()?;
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let mut db = ProposalDb::open_in_memory()?;
+    let context = CommandContext::default();
+    config.policy = Some(PolicyEngine::new(
+        Policy {
+            checkers: vec![checker1, checker2],
+            require_proposal: vec![CommandKind::Mint, CommandKind::DenyListRemove],
+            ..Policy::default()
+        },
+        MintLedger::open_in_memory()?,
+    ));
+
+    let command = AppCommand::MintAndTransfer(1_000, user);
+    let e = execute_command(command.clone(), &config).await.unwrap_err();
+    assert_eq!(
+        e.downcast_ref::<PolicyViolation>(),
+        Some(&PolicyViolation::ProposalRequired {
+            kind: CommandKind::Mint
+        })
+    );
+    assert_eq!(config.client.balance(user, &type_tag), 0);
+
+    let id = proposal::propose(&config, &mut db, command, &context, 1)
+        .await?
+        .id;
+    review(&config, &mut db, id, checker1, Decision::Approve, None)?;
+    proposal::execute(&config, &mut db, id).await?;
+    assert_eq!(config.client.balance(user, &type_tag), 1_000);
+
+    // Other commands are executed directly
+    execute_command(AppCommand::DenyListAdd(user), &config).await?;
+    let e = execute_command(AppCommand::DenyListRemove(user), &config)
+        .await
+        .unwrap_err();
+    assert!(e.downcast_ref::<PolicyViolation>().is_some(), "{e}");
+    assert!(config.client.is_denied(&type_tag, user));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reviewed_transaction() -> Result<()> {
+    let (mut config, [checker1, _, user]) = setup()?;
+    let maker = config.active_address;
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let mut db = ProposalDb::open_in_memory()?;
+    let context = CommandContext::default();
+
+    let mut ids = vec![];
+    for _ in 0..2 {
+        let command = AppCommand::DenyListAdd(user);
+        let id = proposal::propose(&config, &mut db, command, &context, 1)
+            .await?
+            .id;
+        review(&config, &mut db, id, checker1, Decision::Approve, None)?;
+        ids.push(id);
+    }
+
+    // Built for another coin, the deny-list change is not the reviewed one
+    let other = parse_sui_type_tag("0x3::usdx::USDX")?;
+    config.client.create_regulated_coin(other.clone(), maker);
+    config.type_tag = other.clone();
+    assert!(proposal::execute(&config, &mut db, ids[0]).await.is_err());
+    assert!(!config.client.is_denied(&other, user));
+    assert_eq!(db.get(ids[0])?.status, ProposalStatus::Approved);
+
+    // A DenyCap used since the proposal is the same object at another version
+    config.type_tag = type_tag.clone();
+    execute_command(AppCommand::DenyListAdd(checker1), &config).await?;
+    proposal::execute(&config, &mut db, ids[1]).await?;
+    assert!(config.client.is_denied(&type_tag, user));
+    Ok(())
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use anyhow::Result;
+use rust_client::publish::{update_env_file, upgrade, verify_package_ids, write_env, PackageIds};
+use rust_client::tx_run::{execute_command, verify_coin_type, AppCommand};
+use sui_keys::keystore::AccountKeystore;
+use sui_sdk::types::parse_sui_type_tag;
+use sui_sdk::types::SUI_FRAMEWORK_PACKAGE_ID;
+
+use common::{keystore, move_package_path, TestEnv};
+
+#[tokio::test]
+async fn test_publish() -> Result<()> {
+    let env = TestEnv::new().await?;
+    let client = env.cluster.sui_client();
+
+    let metadata = verify_coin_type(client, &env.type_tag).await?;
+    assert_eq!(metadata.id, Some(env.published.coin_metadata));
+    assert_eq!(
+        env.type_tag,
+        parse_sui_type_tag(&format!(
+            "{}::regulated_coin::REGULATED_COIN",
+            env.published.package_id
+        ))?
+    );
+
+    let dir = std::env::temp_dir().join(format!("publish-{}", env.published.package_id));
+    std::fs::create_dir_all(&dir)?;
+    let env_file = dir.join(".env");
+    // Left by an upgrade of a coin published earlier
+    std::fs::write(
+        &env_file,
+        "RUST_LOG=rust_client=DEBUG\nPACKAGE_ID=0x1\nLATEST_PACKAGE_ID=0x2\n",
+    )?;
+    write_env(&env_file, &env.published)?;
+
+    let written = std::fs::read_to_string(&env_file)?;
+    assert!(written.starts_with("RUST_LOG=rust_client=DEBUG\n"));
+    assert!(written.contains(&format!("PACKAGE_ID={}\n", env.published.package_id)));
+    assert!(written.contains(&format!("COIN_TYPE={}\n", env.type_tag)));
+    assert!(written.contains(&format!("DENY_CAP_ID={}\n", env.published.deny_cap)));
+    assert!(!written.contains("PACKAGE_ID=0x1\n"));
+    assert!(!written.contains("LATEST_PACKAGE_ID"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upgrade() -> Result<()> {
+    let env = TestEnv::new().await?;
+    let client = env.cluster.sui_client();
+
+    let upgraded = upgrade(
+        client,
+        keystore().get_key(&env.admin)?,
+        &move_package_path(),
+        env.published.upgrade_cap,
+    )
+    .await?;
+    assert_eq!(upgraded.previous_package_id, env.published.package_id);
+    assert_ne!(upgraded.package_id, env.published.package_id);
+    assert_eq!(upgraded.version, 2);
+
+    // The coin type keeps the original package id
+    let packages = PackageIds::new(&env.type_tag, Some(upgraded.package_id))?;
+    assert_eq!(packages.original, env.published.package_id);
+    verify_package_ids(client, &packages, &env.type_tag).await?;
+    let mut config = env.config(env.admin);
+    config.packages = packages;
+    execute_command(AppCommand::MintAndTransfer(100, env.users[0]), &config).await?;
+
+    let unrelated = PackageIds::new(&env.type_tag, Some(SUI_FRAMEWORK_PACKAGE_ID))?;
+    assert!(verify_package_ids(client, &unrelated, &env.type_tag)
+        .await
+        .is_err());
+
+    // Only the owner of the UpgradeCap can upgrade
+    assert!(upgrade(
+        client,
+        keystore().get_key(&env.users[0])?,
+        &move_package_path(),
+        env.published.upgrade_cap,
+    )
+    .await
+    .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_update_env_file() -> Result<()> {
+    let env_file = std::env::temp_dir().join(format!("update-env-{}", std::process::id()));
+    std::fs::write(&env_file, "A=1\nB=2\n")?;
+    update_env_file(&env_file, &[("B", "3".to_string()), ("C", "4".to_string())])?;
+    assert_eq!(std::fs::read_to_string(&env_file)?, "A=1\nB=3\nC=4\n");
+    std::fs::remove_file(&env_file)?;
+    Ok(())
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use rust_client::registry::{CoinRegistry, KnownAddress, ManagedCoin};
+use sui_keys::keystore::{InMemKeystore, Keystore};
+use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+use sui_sdk::types::parse_sui_type_tag;
+
+use common::{multisig, COIN_TYPE};
+
+fn coin(alias: &str, owner: SuiAddress) -> Result<ManagedCoin> {
+    Ok(ManagedCoin {
+        alias: alias.to_string(),
+        type_tag: parse_sui_type_tag(COIN_TYPE)?,
+        treasury_cap_owner: owner,
+        deny_cap_owner: owner,
+        latest_package_id: None,
+    })
+}
+
+fn path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{name}-{}.json", std::process::id()))
+}
+
+#[test]
+fn test_save_and_load() -> Result<()> {
+    let owner = SuiAddress::random_for_testing_only();
+    let mut registry = CoinRegistry::default();
+    registry.insert(coin("USDX", owner)?);
+    registry.insert(ManagedCoin {
+        latest_package_id: Some(ObjectID::random()),
+        ..coin("EURX", owner)?
+    });
+    let path = path("registry");
+    registry.save(&path)?;
+
+    let saved = std::fs::read_to_string(&path)?;
+    assert!(saved.contains(&format!(
+        "\"coin_type\": \"{}\"",
+        registry.coins[0].type_tag
+    )));
+    // Empty lists and unset packages are left out
+    assert!(!saved.contains("multisigs"));
+    assert_eq!(saved.matches("latest_package_id").count(), 1);
+
+    let loaded = CoinRegistry::load(&path)?;
+    assert_eq!(loaded.coins.len(), 2);
+    assert_eq!(loaded.get("USDX")?.treasury_cap_owner, owner);
+    assert_eq!(
+        loaded.get("EURX")?.latest_package_id,
+        registry.coins[1].latest_package_id
+    );
+    assert!(loaded.get("GBPX").is_err());
+    std::fs::remove_file(&path)?;
+
+    assert!(CoinRegistry::load_or_default(&path)?.coins.is_empty());
+    assert!(CoinRegistry::load(&path).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_insert_replaces_alias() -> Result<()> {
+    let (old, new) = (
+        SuiAddress::random_for_testing_only(),
+        SuiAddress::random_for_testing_only(),
+    );
+    let mut registry = CoinRegistry::default();
+    registry.insert(coin("USDX", old)?);
+    registry.insert(coin("USDX", new)?);
+    assert_eq!(registry.coins.len(), 1);
+    assert_eq!(registry.get("USDX")?.deny_cap_owner, new);
+    Ok(())
+}
+
+#[test]
+fn test_invalid_registry() -> Result<()> {
+    let owner = SuiAddress::random_for_testing_only();
+    let path = path("invalid-registry");
+    let registry = CoinRegistry {
+        coins: vec![coin("USDX", owner)?, coin("USDX", owner)?],
+        ..CoinRegistry::default()
+    };
+    registry.save(&path)?;
+    let e = CoinRegistry::load(&path).unwrap_err();
+    assert!(e.to_string().contains("Duplicate coin alias USDX"));
+
+    std::fs::write(
+        &path,
+        r#"{"coins": [{"alias": "USDX", "coin_type": "not a type"}]}"#,
+    )?;
+    let e = CoinRegistry::load(&path).unwrap_err();
+    assert!(e.to_string().contains("Invalid coin registry"));
+
+    // The address of a multisig must derive from its keys
+    let keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(3));
+    let mut registry = CoinRegistry {
+        multisigs: vec![multisig(&keystore, "treasury-2-of-3")?],
+        ..CoinRegistry::default()
+    };
+    registry.save(&path)?;
+    CoinRegistry::load(&path)?;
+    registry.multisigs[0].address = owner;
+    registry.save(&path)?;
+    let e = CoinRegistry::load(&path).unwrap_err();
+    assert!(e.to_string().contains("keys derive"), "{e}");
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[test]
+fn test_describe() -> Result<()> {
+    let keystore = Keystore::from(InMemKeystore::new_insecure_for_tests(3));
+    let multisig = multisig(&keystore, "treasury-2-of-3")?;
+    let (key, unknown) = (
+        SuiAddress::random_for_testing_only(),
+        SuiAddress::random_for_testing_only(),
+    );
+    let registry = CoinRegistry {
+        keys: vec![KnownAddress {
+            name: "alice".to_string(),
+            address: key,
+        }],
+        multisigs: vec![multisig.clone()],
+        ..CoinRegistry::default()
+    };
+    assert_eq!(
+        registry.describe(multisig.address).as_deref(),
+        Some("multisig treasury-2-of-3")
+    );
+    assert_eq!(registry.describe(key).as_deref(), Some("key alice"));
+    assert_eq!(registry.describe(unknown), None);
+    Ok(())
+}
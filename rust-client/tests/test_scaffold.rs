@@ -0,0 +1,73 @@
+mod common;
+
+use anyhow::Result;
+use rust_client::publish::publish;
+use rust_client::scaffold::{new_coin_package, CoinParams};
+use rust_client::tx_run::verify_coin_type;
+use sui_keys::keystore::AccountKeystore;
+
+use common::{keystore, TestEnv};
+
+fn usdx() -> CoinParams {
+    CoinParams {
+        description: "Regulated dollar".to_string(),
+        icon_url: Some("https://example.com/usdx.png".to_string()),
+        ..CoinParams::new("usdx", 6, "USDX", "USD X")
+    }
+}
+
+#[test]
+fn test_new_coin_package() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("new-coin-{}", std::process::id()));
+    let module_path = new_coin_package(&usdx(), &dir)?;
+
+    let module = std::fs::read_to_string(&module_path)?;
+    assert!(module.starts_with("module usdx::usdx {"));
+    assert!(module.contains("struct USDX has drop {}"));
+    assert!(module.contains("b\"USDX\""));
+    assert!(module.contains("b\"https://example.com/usdx.png\""));
+    assert!(module.contains("coin::create_regulated_currency_v2("));
+    assert!(std::fs::read_to_string(dir.join("Move.toml"))?.contains("usdx=\"0x0\""));
+    let compliance = std::fs::read_to_string(dir.join("sources/compliance.move"))?;
+    assert!(compliance.starts_with("module usdx::compliance {"));
+    assert!(!compliance.contains("regulated_coin_example"));
+
+    // Never overwrites an existing package
+    assert!(new_coin_package(&usdx(), &dir).is_err());
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_invalid_params() {
+    let dir = std::env::temp_dir().join("invalid-coin");
+    let invalid = [
+        CoinParams::new("Usdx", 6, "USDX", "USD X"),
+        CoinParams::new("usd-x", 6, "USDX", "USD X"),
+        CoinParams::new("usdx", 6, "", "USD X"),
+        CoinParams::new("usdx", 6, "USDX", "USD \"X\""),
+    ];
+    for params in invalid {
+        assert!(new_coin_package(&params, &dir).is_err(), "{params:?}");
+    }
+    assert!(!dir.exists());
+}
+
+#[tokio::test]
+async fn test_publish_new_coin() -> Result<()> {
+    let env = TestEnv::new().await?;
+    let client = env.cluster.sui_client();
+    let dir = std::env::temp_dir().join(format!("publish-new-coin-{}", std::process::id()));
+    new_coin_package(&usdx(), &dir)?;
+
+    let published = publish(client, keystore().get_key(&env.admin)?, &dir).await?;
+    let metadata = verify_coin_type(client, &published.type_tag).await?;
+    assert_eq!(metadata.decimals, 6);
+    assert_eq!(metadata.symbol, "USDX");
+    assert_eq!(
+        metadata.icon_url.as_deref(),
+        Some("https://example.com/usdx.png")
+    );
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use anyhow::Result;
+use rust_client::schedule::{self, ScheduleDb, ScheduleStatus, Trigger};
+use rust_client::tx_run::{execute_command, AppCommand, CommandContext};
+
+use common::{mock_config, MockCoin};
+
+const DAY_MS: u64 = 86_400_000;
+const NOW_MS: u64 = 1_790_000_000_000;
+
+#[tokio::test]
+async fn test_lift_freeze() -> Result<()> {
+    let (config, users) = mock_config(2, MockCoin::V1)?;
+    let user = users[0];
+    let mut db = ScheduleDb::open_in_memory()?;
+    execute_command(AppCommand::DenyListAdd(user), &config).await?;
+    let context = CommandContext {
+        ticket: Some("CHG-7".to_string()),
+        ..CommandContext::default()
+    };
+    let change = schedule::schedule(
+        &config,
+        &mut db,
+        AppCommand::DenyListRemove(user),
+        &context,
+        Trigger::At(NOW_MS + 30 * DAY_MS),
+        3,
+    )?;
+    assert_eq!(change.status, ScheduleStatus::Pending);
+    assert_eq!(change.scheduled_by, config.active_address);
+
+    let report = schedule::run_due(&config, &mut db, NOW_MS + 29 * DAY_MS).await?;
+    assert!(report.attempted.is_empty());
+    assert!(config.client.is_denied(&config.type_tag, user));
+
+    let report = schedule::run_due(&config, &mut db, NOW_MS + 30 * DAY_MS).await?;
+    assert_eq!(report.attempted.len(), 1);
+    let change = db.get(change.id)?;
+    assert_eq!(change.status, ScheduleStatus::Executed);
+    assert_eq!(change.context, context);
+    assert!(change.executed_digest().is_some());
+    assert!(!config.client.is_denied(&config.type_tag, user));
+
+    let report = schedule::run_due(&config, &mut db, NOW_MS + 31 * DAY_MS).await?;
+    assert!(report.attempted.is_empty());
+    assert!(schedule::cancel(&mut db, change.id).is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_epoch() -> Result<()> {
+    let (config, users) = mock_config(2, MockCoin::V1)?;
+    let user = users[0];
+    let mut db = ScheduleDb::open_in_memory()?;
+    config.client.set_epoch(4);
+    let change = schedule::schedule(
+        &config,
+        &mut db,
+        AppCommand::DenyListAdd(user),
+        &CommandContext::default(),
+        Trigger::Epoch(5),
+        1,
+    )?;
+    assert!(schedule::run_due(&config, &mut db, NOW_MS)
+        .await?
+        .attempted
+        .is_empty());
+    config.client.set_epoch(5);
+    let report = schedule::run_due(&config, &mut db, NOW_MS).await?;
+    assert_eq!(report.epoch, 5);
+    assert_eq!(db.get(change.id)?.status, ScheduleStatus::Executed);
+    assert!(config.client.is_denied(&config.type_tag, user));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_retries() -> Result<()> {
+    let (mut config, users) = mock_config(2, MockCoin::V1)?;
+    let user = users[0];
+    let mut db = ScheduleDb::open_in_memory()?;
+    // Without the DenyCap, the pre-flight checks fail every attempt
+    let admin = config.active_address;
+    config.active_address = user;
+    config.client.add_gas(user, 1_000_000_000);
+    let change = schedule::schedule(
+        &config,
+        &mut db,
+        AppCommand::DenyListAdd(admin),
+        &CommandContext::default(),
+        Trigger::At(NOW_MS),
+        2,
+    )?;
+
+    let report = schedule::run_due(&config, &mut db, NOW_MS).await?;
+    let attempted = &report.attempted[0];
+    assert_eq!(attempted.status, ScheduleStatus::Pending);
+    assert!(attempted.attempts[0].error.is_some());
+    assert!(attempted.next_attempt_ms > NOW_MS);
+    assert!(report.to_string().contains("retrying"));
+
+    // Waiting for the retry delay
+    let report = schedule::run_due(&config, &mut db, NOW_MS + 1).await?;
+    assert!(report.attempted.is_empty());
+
+    let report = schedule::run_due(&config, &mut db, attempted.next_attempt_ms).await?;
+    assert_eq!(report.attempted[0].status, ScheduleStatus::Failed);
+    assert_eq!(db.get(change.id)?.attempts.len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_already_in_effect() -> Result<()> {
+    let (config, users) = mock_config(2, MockCoin::V1)?;
+    let user = users[0];
+    let mut db = ScheduleDb::open_in_memory()?;
+    execute_command(AppCommand::DenyListAdd(user), &config).await?;
+    let change = schedule::schedule(
+        &config,
+        &mut db,
+        AppCommand::DenyListRemove(user),
+        &CommandContext::default(),
+        Trigger::At(NOW_MS),
+        3,
+    )?;
+    // As if an earlier attempt was executed, but its response was lost
+    execute_command(AppCommand::DenyListRemove(user), &config).await?;
+
+    // The pre-flight checks refuse the removal, which is already done
+    let report = schedule::run_due(&config, &mut db, NOW_MS).await?;
+    assert_eq!(report.attempted[0].status, ScheduleStatus::Executed);
+    let change = db.get(change.id)?;
+    assert_eq!(change.attempts.len(), 1);
+    assert_eq!(change.attempts[0].error, None);
+    assert_eq!(change.executed_digest(), None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancel() -> Result<()> {
+    let (config, users) = mock_config(2, MockCoin::V1)?;
+    let user = users[0];
+    let mut db = ScheduleDb::open_in_memory()?;
+    assert!(schedule::schedule(
+        &config,
+        &mut db,
+        AppCommand::MintAndTransfer(100, user),
+        &CommandContext::default(),
+        Trigger::At(NOW_MS),
+        1,
+    )
+    .is_err());
+
+    let change = schedule::schedule(
+        &config,
+        &mut db,
+        AppCommand::DenyListAdd(user),
+        &CommandContext::default(),
+        Trigger::At(NOW_MS),
+        1,
+    )?;
+    assert_eq!(
+        schedule::cancel(&mut db, change.id)?.status,
+        ScheduleStatus::Cancelled
+    );
+    assert!(schedule::run_due(&config, &mut db, NOW_MS)
+        .await?
+        .attempted
+        .is_empty());
+    assert!(!config.client.is_denied(&config.type_tag, user));
+    assert_eq!(db.list(Some(ScheduleStatus::Pending))?.len(), 0);
+    assert_eq!(db.list(None)?.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_parse_delay() -> Result<()> {
+    assert_eq!(schedule::parse_delay("30d")?, 30 * DAY_MS);
+    assert_eq!(schedule::parse_delay("12h")?, 12 * 3_600_000);
+    assert_eq!(schedule::parse_delay("15m")?, 15 * 60_000);
+    assert!(schedule::parse_delay("30").is_err());
+    assert!(schedule::parse_delay("d").is_err());
+    assert!(schedule::parse_delay("").is_err());
+    Ok(())
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Result;
+use rust_client::screen::{self, parse_list, ListFormat, ParsedList};
+use rust_client::tx_run::{execute_command, AppCommand, MAX_DENY_LIST_BATCH};
+use sui_sdk::types::base_types::SuiAddress;
+
+use common::{address, mock_config, MockCoin};
+
+const SDN_XML: &str = include_str!("fixtures/sdn.xml");
+const SDN_CSV: &str = include_str!("fixtures/sdn.csv");
+
+#[test]
+fn test_sdn_lists() -> Result<()> {
+    for (format, contents) in [(ListFormat::SdnXml, SDN_XML), (ListFormat::SdnCsv, SDN_CSV)] {
+        let list = parse_list(format, contents)?;
+        let addresses: Vec<SuiAddress> = list.addresses.iter().map(|l| l.address).collect();
+        // Not the registration number that looks like an address, nor the Ethereum address
+        assert_eq!(addresses, [address('3'), address('5')], "{format:?}");
+        // The Bitcoin and Ethereum addresses
+        assert_eq!(list.other, 2, "{format:?}");
+        assert!(list.addresses[0].source.starts_with("SDN 41001"));
+    }
+    let xml = parse_list(ListFormat::SdnXml, SDN_XML)?;
+    assert_eq!(xml.addresses[1].name, "John DOE & SONS");
+    let csv = parse_list(ListFormat::SdnCsv, SDN_CSV)?;
+    assert_eq!(csv.addresses[1].name, "DOE, John");
+
+    assert_eq!(
+        ListFormat::detect(Path::new("sdn.xml"), SDN_XML),
+        ListFormat::SdnXml
+    );
+    assert_eq!(
+        ListFormat::detect(Path::new("sdn.csv"), SDN_CSV),
+        ListFormat::SdnCsv
+    );
+    assert!(parse_list(ListFormat::SdnXml, "<html></html>").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_csv_list() -> Result<()> {
+    let contents = format!(
+        "Name,Address,Notes\n\
+         \"Mixer, \"\"the\"\"\",{},\"two\nlines\"\n\
+         \n\
+         Other,{},\n\
+         Short,0x1234,\n",
+        address('6'),
+        address('b').to_string().to_uppercase().replace("0X", "0x"),
+    );
+    assert_eq!(
+        ListFormat::detect(Path::new("list.csv"), &contents),
+        ListFormat::Csv
+    );
+    let list = parse_list(ListFormat::Csv, &contents)?;
+    assert_eq!(list.addresses.len(), 2);
+    assert_eq!(list.addresses[0].name, "Mixer, \"the\"");
+    assert_eq!(list.addresses[1].address, address('b'));
+    assert_eq!(list.addresses[1].source, "row 2");
+    assert_eq!(list.other, 1);
+
+    assert!(parse_list(ListFormat::Csv, "name,wallet\nx,0x1\n").is_err());
+    assert!(parse_list(ListFormat::Csv, "address\n\"0x1\n").is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_screen() -> Result<()> {
+    let (config, _) = mock_config(1, MockCoin::V1)?;
+    let mut list = parse_list(ListFormat::SdnXml, SDN_XML)?;
+    // Listed twice
+    list.addresses.push(list.addresses[0].clone());
+    list.addresses.push(screen::ListedAddress {
+        address: address('8'),
+        name: String::new(),
+        source: "row 1".to_string(),
+    });
+    execute_command(AppCommand::DenyListAdd(address('3')), &config).await?;
+    let counterparties = BTreeMap::from([(address('8'), 1_700_000_000_000), (address('9'), 0)]);
+
+    let report = screen::screen(
+        &config.client,
+        &config.type_tag,
+        list.clone(),
+        Some(&counterparties),
+    )
+    .await?;
+    assert_eq!(report.matches.len(), 3);
+    assert!(report.matches[0].denied);
+    assert_eq!(report.matches[2].last_seen_ms, Some(1_700_000_000_000));
+    // Recent counterparties first
+    assert_eq!(report.proposed(), [address('8'), address('5')]);
+    assert!(report.to_string().contains("COUNTERPARTY"));
+
+    let batches = report.deny_list_batches();
+    assert_eq!(batches, [vec![address('8'), address('5')]]);
+    for addresses in batches {
+        execute_command(AppCommand::DenyListAddMany(addresses), &config).await?;
+    }
+    let report = screen::screen(&config.client, &config.type_tag, list, None).await?;
+    assert!(report.proposed().is_empty());
+    assert!(!report.counterparties_checked);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_batches() -> Result<()> {
+    let (config, _) = mock_config(1, MockCoin::V1)?;
+    let list = ParsedList {
+        addresses: (0..MAX_DENY_LIST_BATCH + 1)
+            .map(|_| screen::ListedAddress {
+                address: SuiAddress::random_for_testing_only(),
+                name: String::new(),
+                source: String::new(),
+            })
+            .collect(),
+        other: 0,
+    };
+    let report = screen::screen(&config.client, &config.type_tag, list, None).await?;
+    let sizes: Vec<usize> = report.deny_list_batches().iter().map(Vec::len).collect();
+    assert_eq!(sizes, [MAX_DENY_LIST_BATCH, 1]);
+
+    assert!(
+        execute_command(AppCommand::DenyListAddMany(vec![]), &config)
+            .await
+            .is_err()
+    );
+    let too_many = report.proposed();
+    assert!(
+        execute_command(AppCommand::DenyListAddMany(too_many), &config)
+            .await
+            .is_err()
+    );
+    Ok(())
+}
@@ -0,0 +1,177 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use rust_client::backend::mock::MockBackend;
+use rust_client::screen::{
+    BlocklistScreener, DenyListScreener, HttpScreener, ListedAddress, ParsedList, Screener,
+    ScreeningHit, ScreeningRequest, ScreeningResponse,
+};
+use rust_client::tx_run::{
+    execute_command, execute_command_with, AppCommand, AppConfig, CommandContext,
+};
+use sui_sdk::types::base_types::SuiAddress;
+
+use common::{mock_config, MockCoin};
+
+const TOKEN: &str = "screening-token";
+
+/// A mock chain with a regulated coin whose caps are owned by the admin, screening recipients
+/// with `screeners`.
+fn setup(
+    screeners: impl FnOnce(&MockBackend) -> Vec<Box<dyn Screener>>,
+) -> Result<(AppConfig<MockBackend>, SuiAddress, SuiAddress)> {
+    let (mut config, users) = mock_config(3, MockCoin::V1)?;
+    config.screeners = screeners(&config.client);
+    Ok((config, users[0], users[1]))
+}
+
+fn blocklist(address: SuiAddress) -> Box<dyn Screener> {
+    let list = ParsedList {
+        addresses: vec![ListedAddress {
+            address,
+            name: "Example Mixer".to_string(),
+            source: "row 1".to_string(),
+        }],
+        other: 0,
+    };
+    Box::new(BlocklistScreener::new("blocklist".to_string(), list))
+}
+
+fn hit(result: Result<impl std::fmt::Debug>) -> ScreeningHit {
+    result
+        .expect_err("screening should refuse the command")
+        .downcast::<ScreeningHit>()
+        .expect("a screening hit")
+}
+
+fn overriding(justification: &str) -> CommandContext {
+    CommandContext {
+        override_screening: Some(justification.to_string()),
+        ..CommandContext::default()
+    }
+}
+
+#[tokio::test]
+async fn test_blocklist() -> Result<()> {
+    let flagged = SuiAddress::random_for_testing_only();
+    let (config, user, _) = setup(|_| vec![blocklist(flagged)])?;
+
+    execute_command(AppCommand::MintAndTransfer(100, user), &config).await?;
+    let result = execute_command(AppCommand::MintAndTransfer(100, flagged), &config).await;
+    let blocked = hit(result);
+    assert_eq!(blocked.address, flagged);
+    assert_eq!(blocked.hits[0].screener, "blocklist");
+    assert!(blocked.hits[0].reason.contains("Example Mixer"));
+    assert_eq!(config.client.total_supply(&config.type_tag), 100);
+
+    let result = execute_command_with(
+        AppCommand::MintAndTransfer(100, flagged),
+        &overriding(" "),
+        &config,
+    )
+    .await;
+    hit(result);
+    let context = overriding("Court-ordered release, case 12");
+    execute_command_with(AppCommand::MintAndTransfer(100, flagged), &context, &config).await?;
+    assert_eq!(config.client.balance(flagged, &config.type_tag), 100);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deny_list() -> Result<()> {
+    let (config, user, other) = setup(|client| {
+        let screener: Box<dyn Screener> = Box::new(DenyListScreener::new(client.clone()));
+        vec![screener]
+    })?;
+    let coin = config
+        .client
+        .add_coin(config.active_address, config.type_tag.clone(), 500);
+    execute_command(AppCommand::DenyListAdd(other), &config).await?;
+
+    let result = execute_command(AppCommand::MintAndTransfer(100, other), &config).await;
+    assert_eq!(hit(result).hits[0].screener, "deny-list");
+    let result = execute_command(AppCommand::Transfer(coin, other), &config).await;
+    hit(result);
+    execute_command(AppCommand::Transfer(coin, user), &config).await?;
+    // Denied addresses are still refused by the pre-flight checks
+    let result = execute_command_with(
+        AppCommand::MintAndTransfer(100, other),
+        &overriding("test"),
+        &config,
+    )
+    .await;
+    assert!(result.is_err());
+    Ok(())
+}
+
+/// A provider flagging `flagged`, answering 500 for `broken`, and recording the requests.
+async fn stub_provider(
+    flagged: SuiAddress,
+    broken: SuiAddress,
+) -> Result<(String, Arc<Mutex<Vec<ScreeningRequest>>>)> {
+    type Requests = Arc<Mutex<Vec<ScreeningRequest>>>;
+    let requests: Requests = Arc::default();
+    let handler = move |State(requests): State<Requests>,
+                        headers: HeaderMap,
+                        Json(request): Json<ScreeningRequest>| async move {
+        let authorized = headers
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            == Some(format!("Bearer {TOKEN}").as_str());
+        if !authorized {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        requests.lock().unwrap().push(request.clone());
+        if request.address == broken {
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+        let hit = request.address == flagged;
+        Ok(Json(ScreeningResponse {
+            hit,
+            reason: hit.then(|| "sanctioned entity".to_string()),
+        }))
+    };
+    let app = Router::new()
+        .route("/screen", post(handler))
+        .with_state(requests.clone());
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let url = format!("http://{}/screen", listener.local_addr()?);
+    tokio::spawn(axum::Server::from_tcp(listener)?.serve(app.into_make_service()));
+    Ok((url, requests))
+}
+
+#[tokio::test]
+async fn test_http_provider() -> Result<()> {
+    let (flagged, broken) = (
+        SuiAddress::random_for_testing_only(),
+        SuiAddress::random_for_testing_only(),
+    );
+    let (url, requests) = stub_provider(flagged, broken).await?;
+    let provider: Box<dyn Screener> =
+        Box::new(HttpScreener::new(url.clone(), Some(TOKEN.to_string()))?);
+    let (config, user, _) = setup(|_| vec![provider])?;
+
+    execute_command(AppCommand::MintAndTransfer(100, user), &config).await?;
+    let result = execute_command(AppCommand::MintAndTransfer(100, flagged), &config).await;
+    assert_eq!(hit(result).hits[0].reason, "sanctioned entity");
+    // An unavailable provider refuses rather than letting the recipient through
+    let result = execute_command(AppCommand::MintAndTransfer(100, broken), &config).await;
+    assert!(hit(result).hits[0].reason.starts_with("screening failed"));
+    // Deny-list changes are not screened
+    execute_command(AppCommand::DenyListAdd(flagged), &config).await?;
+
+    let requests = requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[0].address, user);
+    assert_eq!(requests[0].coin_type, config.type_tag.to_string());
+
+    let unauthorized = HttpScreener::new(url, None)?;
+    assert!(unauthorized.screen(&config.type_tag, user).await.is_err());
+    Ok(())
+}
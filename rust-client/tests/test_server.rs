@@ -0,0 +1,197 @@
+mod common;
+
+use anyhow::Result;
+use reqwest::StatusCode;
+use rust_client::backend::mock::MockBackend;
+use rust_client::policy::{CommandKind, MintLedger, Policy, PolicyEngine};
+use rust_client::server::{router, BalanceResponse, DenyStatusResponse, TxResponse};
+use serde_json::json;
+use sui_sdk::types::base_types::{ObjectID, SuiAddress};
+use sui_sdk::types::parse_sui_type_tag;
+
+use common::{mock_config, MockCoin, COIN_TYPE};
+
+const TOKEN: &str = "test-token";
+
+struct TestServer {
+    url: String,
+    http: reqwest::Client,
+    client: MockBackend,
+    admin: SuiAddress,
+    user: SuiAddress,
+}
+
+impl TestServer {
+    /// Serves a mock chain with a regulated coin whose caps are owned by the admin.
+    async fn start() -> Result<Self> {
+        Self::start_with(None).await
+    }
+
+    /// Like [`TestServer::start`], signing under `policy`.
+    async fn start_with(policy: Option<Policy>) -> Result<Self> {
+        let (mut config, users) = mock_config(2, MockCoin::V1)?;
+        if let Some(policy) = policy {
+            config.policy = Some(PolicyEngine::new(policy, MintLedger::open_in_memory()?));
+        }
+        let (client, admin, user) = (config.client.clone(), config.active_address, users[0]);
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let server = axum::Server::from_tcp(listener)?
+            .serve(router(config, TOKEN.to_string()).into_make_service());
+        tokio::spawn(server);
+        Ok(TestServer {
+            url,
+            http: reqwest::Client::new(),
+            client,
+            admin,
+            user,
+        })
+    }
+
+    fn get(&self, path: &str) -> reqwest::RequestBuilder {
+        self.http
+            .get(format!("{}{path}", self.url))
+            .bearer_auth(TOKEN)
+    }
+
+    fn post(&self, path: &str, body: serde_json::Value) -> reqwest::RequestBuilder {
+        self.http
+            .post(format!("{}{path}", self.url))
+            .bearer_auth(TOKEN)
+            .json(&body)
+    }
+
+    fn delete(&self, path: &str) -> reqwest::RequestBuilder {
+        self.http
+            .delete(format!("{}{path}", self.url))
+            .bearer_auth(TOKEN)
+    }
+}
+
+#[tokio::test]
+async fn test_authentication() -> Result<()> {
+    let server = TestServer::start().await?;
+    let path = format!("{}/balance/{}", server.url, server.user);
+
+    let resp = server.http.get(&path).send().await?;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = server.http.get(&path).bearer_auth("wrong").send().await?;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = server.http.get(&path).bearer_auth(TOKEN).send().await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mint_and_burn() -> Result<()> {
+    let server = TestServer::start().await?;
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+
+    let resp = server
+        .post(
+            "/mint",
+            json!({ "amount": "10000", "recipient": server.user.to_string() }),
+        )
+        .send()
+        .await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp.json::<TxResponse>().await?.success);
+
+    let resp = server
+        .get(&format!("/balance/{}", server.user))
+        .send()
+        .await?;
+    let balance: BalanceResponse = resp.json().await?;
+    assert_eq!(balance.balance, "10000");
+    assert_eq!(balance.coin_type, type_tag.to_string());
+
+    let coin = server.client.add_coin(server.admin, type_tag.clone(), 700);
+    let resp = server
+        .post("/burn", json!({ "coin": coin.to_string() }))
+        .send()
+        .await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(server.client.total_supply(&type_tag), 10_000);
+
+    // Fails the pre-flight checks: the coin does not exist
+    let resp = server
+        .post("/burn", json!({ "coin": ObjectID::random().to_string() }))
+        .send()
+        .await?;
+    assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let resp = server
+        .post(
+            "/mint",
+            json!({ "amount": "-1", "recipient": server.user.to_string() }),
+        )
+        .send()
+        .await?;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unreadable_caps() -> Result<()> {
+    let server = TestServer::start().await?;
+    let body = json!({ "amount": "100", "recipient": server.user.to_string() });
+
+    // The TreasuryCap could not be looked up, which is not a refusal of the request
+    server.client.set_owned_objects_unavailable(true);
+    let resp = server.post("/mint", body.clone()).send().await?;
+    assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+    assert!(resp.text().await?.contains("[ERROR]"));
+
+    server.client.set_owned_objects_unavailable(false);
+    let resp = server.post("/mint", body).send().await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_require_proposal() -> Result<()> {
+    let policy = Policy {
+        checkers: vec![SuiAddress::random_for_testing_only()],
+        require_proposal: vec![CommandKind::Mint],
+        ..Policy::default()
+    };
+    let server = TestServer::start_with(Some(policy)).await?;
+
+    let resp = server
+        .post(
+            "/mint",
+            json!({ "amount": "100", "recipient": server.user.to_string() }),
+        )
+        .send()
+        .await?;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    assert_eq!(
+        server.client.total_supply(&parse_sui_type_tag(COIN_TYPE)?),
+        0
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_deny_list() -> Result<()> {
+    let server = TestServer::start().await?;
+    let path = format!("/deny-list/{}", server.user);
+
+    let resp = server
+        .post("/deny-list", json!({ "address": server.user.to_string() }))
+        .send()
+        .await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let status: DenyStatusResponse = server.get(&path).send().await?.json().await?;
+    assert!(status.denied);
+
+    let resp = server.delete(&path).send().await?;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let status: DenyStatusResponse = server.get(&path).send().await?.json().await?;
+    assert!(!status.denied);
+
+    let resp = server.get("/deny-list/not-an-address").send().await?;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    Ok(())
+}
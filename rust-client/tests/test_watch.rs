@@ -0,0 +1,74 @@
+mod common;
+
+use std::time::Duration;
+
+use anyhow::Result;
+use rust_client::history::LedgerAction;
+use rust_client::tx_run::{execute_command, AppCommand};
+use rust_client::watch::{is_deny_failure, WatchEvent, Watcher};
+
+use common::{created_object, TestEnv};
+
+#[tokio::test]
+async fn test_watch() -> Result<()> {
+    let env = TestEnv::new().await?;
+    let config = env.config(env.admin);
+    let (user, other) = (env.users[0], env.users[1]);
+
+    let mut watcher = Watcher::new(env.type_tag.clone(), Some(5_000));
+    // The first poll only sets the cursor
+    assert!(watcher.poll(&config.client).await?.is_empty());
+    assert!(watcher.cursor.is_some());
+
+    let resp = execute_command(AppCommand::MintAndTransfer(10_000, user), &config).await?;
+    let coin = created_object(&resp)?;
+    execute_command(AppCommand::Transfer(coin.0, other), &env.config(user)).await?;
+    // Below the threshold
+    let resp = execute_command(AppCommand::MintAndTransfer(100, user), &config).await?;
+    let small = created_object(&resp)?;
+    execute_command(AppCommand::Transfer(small.0, other), &env.config(user)).await?;
+    execute_command(AppCommand::DenyListAdd(user), &config).await?;
+
+    let mut events = vec![];
+    for _ in 0..50 {
+        events.extend(watcher.poll(&config.client).await?);
+        if events.len() >= 4 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+
+    assert_eq!(events.len(), 4, "{events:?}");
+    assert!(matches!(
+        &events[0],
+        WatchEvent::Admin(entry) if entry.action == LedgerAction::Mint { amount: 10_000, recipient: Some(user) }
+    ));
+    assert!(matches!(
+        &events[1],
+        WatchEvent::LargeTransfer { sender, recipient, amount: 10_000, .. } if *sender == user && *recipient == other
+    ));
+    assert!(matches!(
+        &events[2],
+        WatchEvent::Admin(entry) if entry.action == LedgerAction::Mint { amount: 100, recipient: Some(user) }
+    ));
+    assert!(matches!(
+        &events[3],
+        WatchEvent::Admin(entry) if entry.action == LedgerAction::DenyListAdd { address: user }
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_deny_failure() {
+    assert!(is_deny_failure(
+        "AddressDeniedForCoin { address: 0xf6d3, coin_type: \"0x5da5::regulated_coin::REGULATED_COIN\" } in command 1"
+    ));
+    assert!(is_deny_failure(
+        "MoveAbort(MoveLocation { module: ModuleId { address: 0x2, name: Identifier(\"deny_list\") }, function: 3, instruction: 12, function_name: Some(\"v2_add\") }, 0) in command 0"
+    ));
+    // Other failures of a denied address's transactions are not the deny-list's doing
+    assert!(!is_deny_failure("InsufficientGas"));
+    assert!(!is_deny_failure(
+        "MoveAbort(MoveLocation { module: ModuleId { address: 0x2, name: Identifier(\"balance\") }, function: 6, instruction: 10, function_name: Some(\"split\") }, 2) in command 0"
+    ));
+}
@@ -0,0 +1,285 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use rust_client::history::{LedgerAction, LedgerEntry};
+use rust_client::watch::WatchEvent;
+use rust_client::webhook::{
+    notifications, sign, ComplianceEvent, Notification, Notifier, PendingDeliveries, Trigger,
+    WebhookConfig, WebhookSink, SIGNATURE_HEADER,
+};
+use sui_sdk::types::digests::TransactionDigest;
+use sui_sdk::types::parse_sui_type_tag;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use common::{address, COIN_TYPE};
+
+fn admin_event(digest: TransactionDigest, action: LedgerAction) -> WatchEvent {
+    WatchEvent::Admin(LedgerEntry {
+        timestamp_ms: Some(1_700_000_000_000),
+        checkpoint: Some(10),
+        digest,
+        actor: address('a'),
+        action,
+    })
+}
+
+fn sink(url: String, secret_env: &str) -> WebhookSink {
+    WebhookSink {
+        name: "cases".to_string(),
+        url,
+        secret_env: secret_env.to_string(),
+        triggers: vec![],
+        mint_threshold: 0,
+        max_attempts: 3,
+        initial_backoff_ms: 10,
+    }
+}
+
+/// Answers each request with the next status, and records the signature header and the body.
+async fn serve(statuses: Vec<u16>) -> Result<(String, Arc<Mutex<Vec<(String, String)>>>)> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}/hook", listener.local_addr()?);
+    let requests = Arc::new(Mutex::new(vec![]));
+    let recorded = requests.clone();
+    tokio::spawn(async move {
+        for status in statuses {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = vec![];
+            let mut buf = [0; 4096];
+            let (head, body) = loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                    continue;
+                };
+                let length: usize = head
+                    .lines()
+                    .find_map(|line| {
+                        line.to_lowercase()
+                            .strip_prefix("content-length: ")?
+                            .parse()
+                            .ok()
+                    })
+                    .unwrap_or(0);
+                if body.len() >= length {
+                    break (head.to_string(), body.to_string());
+                }
+            };
+            let signature = head
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(": ")?;
+                    name.eq_ignore_ascii_case(SIGNATURE_HEADER)
+                        .then(|| value.to_string())
+                })
+                .unwrap_or_default();
+            recorded.lock().unwrap().push((signature, body));
+            let response =
+                format!("HTTP/1.1 {status} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    Ok((url, requests))
+}
+
+#[test]
+fn test_notifications() -> Result<()> {
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let (mint_tx, deny_tx) = (TransactionDigest::random(), TransactionDigest::random());
+    let events = vec![
+        admin_event(
+            mint_tx,
+            LedgerAction::Mint {
+                amount: 1_000,
+                recipient: Some(address('1')),
+            },
+        ),
+        admin_event(mint_tx, LedgerAction::Burn { amount: 300 }),
+        WatchEvent::LargeTransfer {
+            digest: TransactionDigest::random(),
+            checkpoint: 11,
+            timestamp_ms: None,
+            sender: address('1'),
+            recipient: address('2'),
+            amount: 1_000,
+        },
+        admin_event(
+            deny_tx,
+            LedgerAction::DenyListAdd {
+                address: address('2'),
+            },
+        ),
+    ];
+
+    let notifications = notifications(&type_tag, &events);
+    let ids: Vec<&str> = notifications.iter().map(|n| n.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        [
+            format!("{mint_tx}:0"),
+            format!("{mint_tx}:1"),
+            format!("{mint_tx}:2"),
+            format!("{deny_tx}:0"),
+        ]
+    );
+    assert_eq!(
+        notifications[2].event,
+        ComplianceEvent::SupplyChange {
+            minted: 1_000,
+            burnt: 300
+        }
+    );
+    assert_eq!(notifications[3].actor, address('a'));
+
+    let json = serde_json::to_value(&notifications[3])?;
+    assert_eq!(json["event"], "deny_list_add");
+    assert_eq!(json["args"]["address"], address('2').to_string());
+    assert_eq!(json["digest"], deny_tx.to_string());
+    assert_eq!(json["coin_type"], type_tag.to_string());
+    assert_eq!(
+        serde_json::from_value::<Notification>(json)?,
+        notifications[3]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_sink_filter() -> Result<()> {
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let events = vec![
+        admin_event(
+            TransactionDigest::random(),
+            LedgerAction::Mint {
+                amount: 10,
+                recipient: None,
+            },
+        ),
+        admin_event(
+            TransactionDigest::random(),
+            LedgerAction::Mint {
+                amount: 1_000,
+                recipient: None,
+            },
+        ),
+        admin_event(
+            TransactionDigest::random(),
+            LedgerAction::DenyListRemove {
+                address: address('2'),
+            },
+        ),
+    ];
+    let notifications = notifications(&type_tag, &events);
+
+    let mut sink = sink("http://localhost".to_string(), "UNUSED");
+    sink.triggers = vec![Trigger::Mint, Trigger::DenyListAdd];
+    sink.mint_threshold = 100;
+    let wanted: Vec<&ComplianceEvent> = notifications
+        .iter()
+        .filter(|n| sink.wants(n))
+        .map(|n| &n.event)
+        .collect();
+    assert_eq!(
+        wanted,
+        [&ComplianceEvent::Mint {
+            amount: 1_000,
+            recipient: None
+        }]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delivery() -> Result<()> {
+    std::env::set_var("TEST_DELIVERY_WEBHOOK_SECRET", "s3cret");
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let events = vec![admin_event(
+        TransactionDigest::random(),
+        LedgerAction::DenyListAdd {
+            address: address('2'),
+        },
+    )];
+    let notification = notifications(&type_tag, &events).remove(0);
+
+    // Retried after a server error
+    let (url, requests) = serve(vec![500, 200]).await?;
+    let notifier = Notifier::new(WebhookConfig {
+        sinks: vec![sink(url, "TEST_DELIVERY_WEBHOOK_SECRET")],
+    });
+    assert!(notifier.notify(&notification).await?.is_empty());
+    let requests = requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 2);
+    for (signature, body) in requests {
+        assert_eq!(signature, sign(b"s3cret", body.as_bytes()));
+        assert_eq!(serde_json::from_str::<Notification>(&body)?, notification);
+    }
+
+    // Not retried when rejected
+    let (url, requests) = serve(vec![400]).await?;
+    let notifier = Notifier::new(WebhookConfig {
+        sinks: vec![sink(url, "TEST_DELIVERY_WEBHOOK_SECRET")],
+    });
+    let undelivered = notifier.notify(&notification).await?;
+    assert_eq!(undelivered.rejected, ["cases"]);
+    assert!(undelivered.failed.is_empty());
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    // Set aside instead of retried at each poll
+    let (url, requests) = serve(vec![404]).await?;
+    let notifier = Notifier::new(WebhookConfig {
+        sinks: vec![sink(url, "TEST_DELIVERY_WEBHOOK_SECRET")],
+    });
+    let mut pending = PendingDeliveries::default();
+    notifier
+        .deliver(&mut pending, vec![notification.clone()])
+        .await?;
+    assert!(pending.pending.is_empty());
+    assert_eq!(pending.rejected.len(), 1);
+    assert_eq!(pending.rejected[0].notification, notification);
+    notifier.deliver(&mut pending, vec![]).await?;
+    assert_eq!(requests.lock().unwrap().len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pending_deliveries() -> Result<()> {
+    std::env::set_var("TEST_PENDING_WEBHOOK_SECRET", "s3cret");
+    let type_tag = parse_sui_type_tag(COIN_TYPE)?;
+    let events = vec![admin_event(
+        TransactionDigest::random(),
+        LedgerAction::DenyListAdd {
+            address: address('2'),
+        },
+    )];
+    let notification = notifications(&type_tag, &events).remove(0);
+    let (url, requests) = serve(vec![500, 500, 500, 503, 200]).await?;
+    let notifier = Notifier::new(WebhookConfig {
+        sinks: vec![sink(url, "TEST_PENDING_WEBHOOK_SECRET")],
+    });
+
+    // Failed every attempt, kept for the next poll
+    let mut pending = PendingDeliveries::default();
+    notifier
+        .deliver(&mut pending, vec![notification.clone()])
+        .await?;
+    assert_eq!(pending.pending.len(), 1);
+    assert_eq!(pending.pending[0].notification, notification);
+    assert_eq!(pending.pending[0].sinks, ["cases"]);
+
+    // Survives a restart of the watch
+    let path = std::env::temp_dir().join(format!("webhooks-pending-{}.json", std::process::id()));
+    pending.save(&path)?;
+    let mut pending = PendingDeliveries::load_or_default(&path)?;
+    std::fs::remove_file(&path)?;
+
+    // Retried once per poll until delivered
+    notifier.deliver(&mut pending, vec![]).await?;
+    assert_eq!(pending.pending.len(), 1);
+    notifier.deliver(&mut pending, vec![]).await?;
+    assert!(pending.pending.is_empty());
+    assert_eq!(requests.lock().unwrap().len(), 5);
+    Ok(())
+}